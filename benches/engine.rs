@@ -0,0 +1,83 @@
+//! Throughput of the win detector, move generation and search on a few fixed positions.
+//!
+//! Run with `cargo bench`, criterion compares every run with the previous one.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use gomoku_rs::board::CellState;
+use gomoku_rs::engine::Engine;
+use gomoku_rs::game::Game;
+use gomoku_rs::rules::RuleSet;
+
+/// Positions as move lists, from a quiet opening to a crowded middle game.
+const POSITIONS: [(&str, &str); 3] = [
+    ("opening", "h8 i9 h9 h10 i8 g8"),
+    ("middle", "h8 g9 i9 g7 g8 f8 i8 j8 i7 i10 j10 e9 h6 d10 c11 e7 h10 d6 c5 h7"),
+    ("crowded", "h8 g9 i9 g7 g8 f8 i8 j8 i7 i10 j10 e9 h6 d10 c11 e7 h10 d6 c5 h7 i6 i5 k11 l12 f7 e6 e8 d9 f9 d7 d8 j6 c8 b8 h9 h11 j7 g10 k8 h5"),
+];
+
+fn position(rule_set: RuleSet, moves: &str) -> Game {
+    let mut game = Game::new(Default::default(), rule_set);
+    game.play_moves(moves).expect("benchmark positions are legal");
+    game
+}
+
+fn win_check(c: &mut Criterion) {
+    let mut group = c.benchmark_group("check_result");
+    for rule_set in RuleSet::ALL {
+        let game = position(rule_set, POSITIONS[2].1);
+        let board = game.board();
+        let occupied: Vec<usize> = (0..board.cells().len()).filter(|&i| board.cell(i) != Some(CellState::Empty)).collect();
+        group.throughput(Throughput::Elements(occupied.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(rule_set), &occupied, |b, occupied| {
+            b.iter(|| {
+                for &index in occupied {
+                    let _ = black_box(rule_set.rules().check_result(board, index));
+                }
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("is_legal");
+    for rule_set in [RuleSet::Freestyle, RuleSet::Renju] {
+        let game = position(rule_set, POSITIONS[2].1);
+        let board = game.board();
+        group.throughput(Throughput::Elements(board.cells().len() as u64));
+        group.bench_function(BenchmarkId::from_parameter(rule_set), |b| {
+            b.iter(|| {
+                for index in 0..board.cells().len() {
+                    let _ = black_box(rule_set.rules().is_legal(board, index));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn move_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("legal_moves");
+    let engine = Engine::new(1);
+    for (name, moves) in POSITIONS {
+        let game = position(RuleSet::Renju, moves);
+        group.bench_function(name, |b| b.iter(|| black_box(engine.legal_moves(&game))));
+    }
+    group.finish();
+}
+
+fn search(c: &mut Criterion) {
+    const DEPTH: u32 = 3;
+    let mut group = c.benchmark_group("search");
+    group.sample_size(10);
+    for (name, moves) in POSITIONS {
+        let game = position(RuleSet::Freestyle, moves);
+        // report nodes per second, the node count of a search is always the same
+        let nodes = Engine::new(DEPTH).search(&game).nodes;
+        group.throughput(Throughput::Elements(nodes));
+        group.bench_function(name, |b| b.iter(|| black_box(Engine::new(DEPTH).search(&game))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, win_check, move_generation, search);
+criterion_main!(benches);
@@ -0,0 +1,86 @@
+//! Searching the position on the board in the background, so the interface keeps running while
+//! the engine thinks and shows how far it got.
+
+use std::sync::{Arc, Mutex};
+
+use iced::Element;
+use iced::widget::{column, text};
+
+use gomoku_rs::engine::{Engine, BEST_MOVES_CAPACITY, WIN_SCORE};
+use gomoku_rs::game::Game;
+
+use crate::Message;
+use crate::thinking::{lock, Progress, Thinking};
+
+pub struct Analysis {
+    /// Held by the search while it runs.
+    engine: Arc<Mutex<Engine>>,
+    thinking: Thinking,
+    /// How far the search got when last looked at.
+    progress: Progress,
+}
+
+impl Analysis {
+    const DEPTH: u32 = 4;
+
+    pub fn new(game: &Game) -> Self {
+        let engine = Arc::new(Mutex::new(Engine::new(Self::DEPTH)));
+        let thinking = Thinking::start(&engine, game, None);
+        let progress = thinking.progress();
+        Self { engine, thinking, progress }
+    }
+
+    /// Starts searching `game` over, with what the engine learned about earlier positions of it.
+    pub fn restart(&mut self, game: &Game) {
+        // the old search lets go of the engine once it is stopped
+        self.thinking = Thinking::start(&self.engine, game, None);
+        self.progress = self.thinking.progress();
+    }
+
+    /// Starts searching `game`, a new one, forgetting the positions searched before.
+    pub fn forget(&mut self, game: &Game) {
+        self.thinking.stop();
+        lock(&self.engine).forget();
+        self.restart(game);
+    }
+
+    /// Looks how far the search got.
+    pub fn step(&mut self) {
+        self.progress = self.thinking.step();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.progress.finished
+    }
+
+    pub fn view(&self, game: &Game) -> Element<'_, Message> {
+        let result = &self.progress.result;
+        let board = game.board();
+        let nodes_per_second = match self.progress.elapsed.as_secs_f64() {
+            secs if secs > 0.0 => (result.nodes as f64 / secs) as u64,
+            _ => 0,
+        };
+        let score = match result.score {
+            score if score.abs() >= WIN_SCORE - Self::DEPTH as i32 => {
+                let plies = WIN_SCORE - score.abs();
+                format!("{} in {} plies", if score > 0 { "wins" } else { "loses" }, plies)
+            },
+            score => format!("{:+}", score),
+        };
+        let pv = result.pv.iter()
+            .map(|&index| board.pos_to_coord(board.index_to_pos(index)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let state = if self.is_finished() { "done" } else { "thinking" };
+        column![
+            text(format!("Engine ({})", state)).size(18),
+            text(format!("Depth {} of {}", result.depth, Self::DEPTH)),
+            text(format!("Nodes {}", result.nodes)),
+            text(format!("Nodes/s {}", nodes_per_second)),
+            text(format!("Hash {:.1}% ({} of {})", self.progress.best_moves_len as f64 * 100.0 / BEST_MOVES_CAPACITY as f64,
+                self.progress.best_moves_len, BEST_MOVES_CAPACITY)),
+            text(format!("Score {}", score)),
+            text(format!("Line {}", pv)),
+        ].spacing(4).padding(10).width(220).into()
+    }
+}
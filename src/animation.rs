@@ -0,0 +1,101 @@
+//! Rendering a game move by move into an animated GIF, for sharing it where SGF cannot be shown.
+
+use std::borrow::Cow;
+use std::io::Write;
+
+use crate::board::{Board, ChessColor, Pos};
+use crate::game::Game;
+
+const CELL_SIZE: usize = 24;
+const PADDING: usize = 20;
+const CHESS_RADIUS: f32 = 10.5;
+
+const BACKGROUND: u8 = 0;
+const GRID: u8 = 1;
+const OUTLINE: u8 = 2;
+const BLACK: u8 = 3;
+const WHITE: u8 = 4;
+const LAST_MOVE: u8 = 5;
+/// RGB colors of the indices above, matching the board on screen.
+const PALETTE: [u8; 18] = [
+    0xf0, 0xf0, 0xf0,
+    0x60, 0x64, 0x6b,
+    0x60, 0x60, 0x60,
+    0x20, 0x20, 0x20,
+    0xf0, 0xf0, 0xf0,
+    0xff, 0x00, 0x00,
+];
+
+/// Writes one frame for the starting position and one after every move of `game`, each shown for
+/// `delay_ms` milliseconds, the final position three times as long before the animation loops.
+pub fn write_gif<W: Write>(game: &Game, delay_ms: u32, writer: W) -> Result<(), String> {
+    let size = game.board().cells_per_row();
+    let side = PADDING * 2 + (size - 1) * CELL_SIZE + 1;
+    let side = u16::try_from(side).map_err(|_| format!("a {}x{} board is too large for a GIF", size, size))?;
+    let mut encoder = gif::Encoder::new(writer, side, side, &PALETTE).map_err(|e| e.to_string())?;
+    encoder.set_repeat(gif::Repeat::Infinite).map_err(|e| e.to_string())?;
+
+    let delay = u16::try_from(delay_ms / 10).unwrap_or(u16::MAX);
+    let mut board = Board::new(size);
+    let moves: Vec<_> = game.board().moves().collect();
+    for &mv in &moves[..game.setup_plies()] {
+        board.apply(mv).map_err(|e| e.to_string())?;
+    }
+    board.set_next(game.first_player());
+    // replayed through the rules so captured chesses disappear in the frame they are captured
+    let mut replay = Game::new(board, game.rule_set());
+    for ply in game.setup_plies()..=moves.len() {
+        if ply > game.setup_plies() {
+            replay.put_chess(moves[ply - 1].index).map_err(|e| e.to_string())?;
+        }
+        let frame = gif::Frame {
+            width: side,
+            height: side,
+            delay: if ply == moves.len() { delay.saturating_mul(3) } else { delay },
+            buffer: Cow::Owned(render(replay.board(), side as usize)),
+            ..gif::Frame::default()
+        };
+        encoder.write_frame(&frame).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Palette indices of `board` drawn on a `side` pixels large square.
+fn render(board: &Board, side: usize) -> Vec<u8> {
+    let mut pixels = vec![BACKGROUND; side * side];
+    let size = board.cells_per_row();
+    let end = PADDING + (size - 1) * CELL_SIZE;
+    for i in 0..size {
+        let line = PADDING + i * CELL_SIZE;
+        for along in PADDING..=end {
+            pixels[line * side + along] = GRID;
+            pixels[along * side + line] = GRID;
+        }
+    }
+
+    let center = |pos: Pos| (PADDING + pos.x * CELL_SIZE, PADDING + pos.y * CELL_SIZE);
+    let reach = CHESS_RADIUS.ceil() as usize;
+    for chess in board.stones() {
+        let (cx, cy) = center(chess.pos);
+        let color = if chess.color == ChessColor::Black { BLACK } else { WHITE };
+        for y in cy - reach..=cy + reach {
+            for x in cx - reach..=cx + reach {
+                let distance = ((x as f32 - cx as f32).powi(2) + (y as f32 - cy as f32).powi(2)).sqrt();
+                if distance <= CHESS_RADIUS {
+                    pixels[y * side + x] = if distance > CHESS_RADIUS - 1.5 { OUTLINE } else { color };
+                }
+            }
+        }
+    }
+
+    if let Some(last) = board.chesses().last() {
+        let (cx, cy) = center(last.pos);
+        for d in 0..=6 {
+            for width in 0..2 {
+                pixels[(cy + width) * side + cx - 3 + d] = LAST_MOVE;
+                pixels[(cy - 3 + d) * side + cx + width] = LAST_MOVE;
+            }
+        }
+    }
+    pixels
+}
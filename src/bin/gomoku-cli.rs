@@ -0,0 +1,287 @@
+//! Plays gomoku in the terminal, between humans typing coordinates and built-in engines.
+//!
+//! ```text
+//! gomoku-cli [--rules <freestyle|standard|renju|caro|pente>] [--engine1 <depth>] [--engine2 <depth>]
+//!            [--style1 <style>] [--style2 <style>] [--sgf <file|->] [--import <file>]
+//!            [--gif <file>] [--gif-delay <milliseconds>]
+//!            [--rules-script <file>] [--bot1 <file>] [--bot2 <file>]
+//! ```
+//!
+//! `--import` continues a game from a move list, Piskvork `.psq` or SGF file. `--gif` saves the
+//! finished game as an animation showing each move for `--gif-delay` milliseconds, 700 by default.
+//! `--engine1` lets an engine searching `depth` plies play black, `--engine2` plays white.
+//! `--style1` and `--style2` make them play `balanced`, `aggressive` or `defensive`.
+//! Humans type moves like `h8` on stdin, `undo` takes back the last move, `resign` gives up,
+//! `draw` offers a draw the opponent may accept and `quit` stops the game.
+//!
+//! Built with the `scripting` feature, `--rules-script` plays by the hooks of a Rhai script on
+//! top of `--rules`, and `--bot1` and `--bot2` let a script's `choose_move` play black or white.
+//! The hooks are described in `gomoku_rs::scripting`.
+
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+use std::time::Instant;
+
+use gomoku_rs::animation;
+use gomoku_rs::engine::{Engine, Style};
+use gomoku_rs::game::{Ending, Game, GameState};
+use gomoku_rs::rules::RuleSet;
+use gomoku_rs::board::{Board, ChessColor};
+use gomoku_rs::import;
+use gomoku_rs::sgf;
+#[cfg(feature = "scripting")]
+use gomoku_rs::scripting::{Script, ScriptRules};
+
+struct Options {
+    rule_set: RuleSet,
+    engine1: Option<u32>,
+    engine2: Option<u32>,
+    style1: Style,
+    style2: Style,
+    sgf: Option<String>,
+    import: Option<String>,
+    gif: Option<String>,
+    gif_delay: u32,
+    #[cfg(feature = "scripting")]
+    rules_script: Option<String>,
+    #[cfg(feature = "scripting")]
+    bot1: Option<String>,
+    #[cfg(feature = "scripting")]
+    bot2: Option<String>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options { rule_set: RuleSet::default(), engine1: None, engine2: None,
+        style1: Style::default(), style2: Style::default(), sgf: None, import: None, gif: None, gif_delay: 700,
+        #[cfg(feature = "scripting")]
+        rules_script: None,
+        #[cfg(feature = "scripting")]
+        bot1: None,
+        #[cfg(feature = "scripting")]
+        bot2: None,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--rules" => { options.rule_set = value()?.parse()?; },
+            "--engine1" => { options.engine1 = Some(parse_depth(&value()?)?); },
+            "--engine2" => { options.engine2 = Some(parse_depth(&value()?)?); },
+            "--style1" => { options.style1 = value()?.parse()?; },
+            "--style2" => { options.style2 = value()?.parse()?; },
+            "--sgf" => { options.sgf = Some(value()?); },
+            "--import" => { options.import = Some(value()?); },
+            "--gif" => { options.gif = Some(value()?); },
+            "--gif-delay" => {
+                let delay = value()?;
+                options.gif_delay = delay.parse().map_err(|_| format!("GIF delay must be a number of milliseconds, got \"{}\"", delay))?;
+            },
+            #[cfg(feature = "scripting")]
+            "--rules-script" => { options.rules_script = Some(value()?); },
+            #[cfg(feature = "scripting")]
+            "--bot1" => { options.bot1 = Some(value()?); },
+            #[cfg(feature = "scripting")]
+            "--bot2" => { options.bot2 = Some(value()?); },
+            "--help" | "-h" => {
+                return Err(String::from("usage: gomoku-cli [--rules <freestyle|standard|renju|caro|pente>] [--engine1 <depth>] [--engine2 <depth>] [--style1 <style>] [--style2 <style>] [--sgf <file|->] [--import <file>] [--gif <file>] [--gif-delay <milliseconds>] [--rules-script <file>] [--bot1 <file>] [--bot2 <file>]"));
+            },
+            _ => { return Err(format!("unknown argument \"{}\"", arg)); }
+        }
+    }
+    Ok(options)
+}
+
+fn parse_depth(value: &str) -> Result<u32, String> {
+    value.parse().map_err(|_| format!("engine depth must be a number, got \"{}\"", value))
+}
+
+#[cfg(feature = "scripting")]
+fn load_script(path: &str) -> Result<Script, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    Script::compile(&source).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let mut game = Game::new(Board::default(), options.rule_set);
+    #[cfg(feature = "scripting")]
+    let bots = {
+        let scripts = [&options.rules_script, &options.bot1, &options.bot2]
+            .map(|path| path.as_deref().map(load_script).transpose());
+        let [rules, bot1, bot2] = match scripts {
+            [Ok(rules), Ok(bot1), Ok(bot2)] => [rules, bot1, bot2],
+            [Err(e), ..] | [_, Err(e), _] | [.., Err(e)] => {
+                eprintln!("{}", e);
+                return ExitCode::FAILURE;
+            },
+        };
+        if let Some(script) = rules {
+            game.set_rules(std::sync::Arc::new(ScriptRules::new(script, options.rule_set)));
+        }
+        [bot1, bot2]
+    };
+    if let Some(path) = &options.import {
+        let result = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| import::load(&text, &mut game));
+        if let Err(e) = result {
+            eprintln!("Cannot import {}: {}", path, e);
+            return ExitCode::FAILURE;
+        }
+    }
+    let mut black_engine = options.engine1.map(Engine::new);
+    let mut white_engine = options.engine2.map(Engine::new);
+    for (engine, style) in [(&mut black_engine, options.style1), (&mut white_engine, options.style2)] {
+        if let Some(engine) = engine {
+            engine.set_style(style);
+        }
+    }
+    let mut lines = io::stdin().lock().lines();
+
+    println!("{}", game.board());
+    // thinking time runs from the previous move until the next one, across mistyped input
+    let mut turn_start = Instant::now();
+    while !game.state().is_over() {
+        let is_black = game.state() == GameState::Wait(ChessColor::Black);
+        let side = if is_black { "Black" } else { "White" };
+        let engine = if is_black { black_engine.as_mut() } else { white_engine.as_mut() };
+        match engine {
+            #[cfg(feature = "scripting")]
+            _ if let Some(bot) = &bots[usize::from(!is_black)] => {
+                let index = match bot.choose_move(&game) {
+                    Ok(index) => index,
+                    Err(e) => {
+                        eprintln!("{} bot: {}", side, e);
+                        return ExitCode::FAILURE;
+                    },
+                };
+                let coord = game.board().pos_to_coord(game.board().index_to_pos(index));
+                println!("{} plays {}", side, coord);
+                if let Err(e) = game.put_chess_timed(index, turn_start.elapsed()) {
+                    eprintln!("Bot move {} was refused: {}", coord, e);
+                    return ExitCode::FAILURE;
+                }
+            },
+            Some(engine) => {
+                let result = engine.search(&game);
+                let Some(index) = result.best_move else { break; };
+                let coord = game.board().pos_to_coord(game.board().index_to_pos(index));
+                println!("{} plays {} (depth {}, score {}, {} nodes)", side, coord, result.depth, result.score, result.nodes);
+                if let Err(e) = game.put_chess_timed(index, turn_start.elapsed()) {
+                    eprintln!("Engine move {} was refused: {}", coord, e);
+                    return ExitCode::FAILURE;
+                }
+            },
+            None => {
+                print!("{} to move: ", side);
+                let _ = io::stdout().flush();
+                let line = match lines.next() {
+                    Some(Ok(line)) => line,
+                    _ => break,
+                };
+                match line.trim() {
+                    "" => continue,
+                    "quit" | "q" => break,
+                    "resign" => {
+                        let _ = game.resign();
+                        continue;
+                    },
+                    "draw" => {
+                        let other = if is_black { "White" } else { "Black" };
+                        let other_engine = if is_black { white_engine.as_mut() } else { black_engine.as_mut() };
+                        let accepted = match other_engine {
+                            Some(engine) => engine.accepts_draw(&game),
+                            None => {
+                                print!("{} offers a draw, does {} accept? [y/n] ", side, other);
+                                let _ = io::stdout().flush();
+                                matches!(lines.next(), Some(Ok(answer)) if answer.trim().eq_ignore_ascii_case("y"))
+                            },
+                        };
+                        if accepted {
+                            let _ = game.agree_draw();
+                        } else {
+                            println!("{} declines the draw", other);
+                        }
+                        continue;
+                    },
+                    "undo" => {
+                        if !game.undo() {
+                            println!("Nothing to undo");
+                            continue;
+                        }
+                        // take back the engine's reply as well so it is the human's turn again
+                        let black_to_move = game.state() == GameState::Wait(ChessColor::Black);
+                        let engine_to_move = if black_to_move { black_engine.is_some() } else { white_engine.is_some() };
+                        #[cfg(feature = "scripting")]
+                        let engine_to_move = engine_to_move || bots[usize::from(!black_to_move)].is_some();
+                        if engine_to_move {
+                            game.undo();
+                        }
+                    },
+                    coord => {
+                        let Some(pos) = game.board().coord_to_pos(coord) else {
+                            println!("Cannot read \"{}\", type a coordinate like h8", coord);
+                            continue;
+                        };
+                        if let Err(e) = game.put_chess_timed(game.board().pos_to_index(pos), turn_start.elapsed()) {
+                            println!("Cannot put chess at {}: {}", coord, e);
+                            continue;
+                        }
+                    },
+                }
+            },
+        }
+        println!("{}", game.board());
+        if game.rule_set().has_captures() {
+            let board = game.board();
+            println!("Captured pairs: black {}, white {}", board.captured_by(ChessColor::Black) / 2, board.captured_by(ChessColor::White) / 2);
+        }
+        turn_start = Instant::now();
+    }
+
+    let result = match game.state() {
+        GameState::Win(ChessColor::Black) if game.ending() == Some(Ending::Captures) => "black wins by captures",
+        GameState::Win(ChessColor::White) if game.ending() == Some(Ending::Captures) => "white wins by captures",
+        GameState::Win(ChessColor::Black) => "black wins",
+        GameState::Win(ChessColor::White) => "white wins",
+        GameState::Draw if game.ending() == Some(Ending::Agreement) => "draw by agreement",
+        GameState::Draw => "draw",
+        _ => "unfinished",
+    };
+    match sgf::result(&game) {
+        Some(code) => println!("Result: {} ({})", result, code),
+        None => println!("Result: {}", result),
+    }
+    println!("Moves: {}", game.board().move_list());
+
+    match options.sgf.as_deref() {
+        Some("-") => print!("{}", sgf::write(&game)),
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, sgf::write(&game)) {
+                eprintln!("Cannot write SGF to {}: {}", path, e);
+                return ExitCode::FAILURE;
+            }
+            println!("Saved SGF to {}", path);
+        },
+        None => (),
+    }
+
+    if let Some(path) = &options.gif {
+        let result = std::fs::File::create(path)
+            .map_err(|e| e.to_string())
+            .and_then(|file| animation::write_gif(&game, options.gif_delay, io::BufWriter::new(file)));
+        if let Err(e) = result {
+            eprintln!("Cannot write GIF to {}: {}", path, e);
+            return ExitCode::FAILURE;
+        }
+        println!("Saved animation to {}", path);
+    }
+    ExitCode::SUCCESS
+}
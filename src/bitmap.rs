@@ -0,0 +1,19 @@
+//! Loading the pictures a board can be drawn with from image files, see [`Bitmap`].
+
+use gomoku_rs::board_view::Bitmap;
+
+/// Reads the image file at `path`, scaled down to at most `max_size` pixels on its longer side.
+#[cfg(feature = "native")]
+pub fn load(path: &str, max_size: u32) -> Result<Bitmap, String> {
+    let image = image::open(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+    let image = image.thumbnail(max_size, max_size).to_rgba8();
+    let pixels = image.pixels()
+        .map(|pixel| iced::Color::from_rgba8(pixel[0], pixel[1], pixel[2], pixel[3] as f32 / 255.0))
+        .collect();
+    Ok(Bitmap { width: image.width() as usize, height: image.height() as usize, pixels })
+}
+
+#[cfg(not(feature = "native"))]
+pub fn load(path: &str, _max_size: u32) -> Result<Bitmap, String> {
+    Err(format!("cannot read {}: there are no files to read here", path))
+}
@@ -0,0 +1,439 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Column and row of an intersection, (0, 0) is the top left corner.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub struct Pos {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Pos {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+}
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// Color of one player's chesses. A game of two is played by black and white, more players take
+/// the other colors too.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ChessColor {
+    Black,
+    White,
+    Red,
+    Blue,
+    Green,
+    Yellow,
+    Purple,
+    Orange,
+}
+
+impl ChessColor {
+    pub const ALL: [ChessColor; 8] = [
+        ChessColor::Black, ChessColor::White, ChessColor::Red, ChessColor::Blue,
+        ChessColor::Green, ChessColor::Yellow, ChessColor::Purple, ChessColor::Orange,
+    ];
+}
+
+impl fmt::Display for ChessColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ChessColor::Black => "Black",
+            ChessColor::White => "White",
+            ChessColor::Red => "Red",
+            ChessColor::Blue => "Blue",
+            ChessColor::Green => "Green",
+            ChessColor::Yellow => "Yellow",
+            ChessColor::Purple => "Purple",
+            ChessColor::Orange => "Orange",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for ChessColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ChessColor::ALL.iter()
+            .find(|color| color.to_string().eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("unknown chess color \"{}\"", s))
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Chess {
+    pub pos: Pos,
+    pub color: ChessColor,
+}
+
+/// Putting a chess of `player` at `index` as the `ply`-th chess of the game, counted from zero.
+///
+/// A game is the list of moves applied to an empty board, [`Board::unapply`] takes back the move
+/// returned by [`Board::last_move`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Move {
+    pub player: ChessColor,
+    pub index: usize,
+    pub ply: usize,
+}
+
+impl Move {
+    pub fn new(player: ChessColor, index: usize, ply: usize) -> Self {
+        Self { player, index, ply }
+    }
+}
+
+/// Why [`Board::apply`] or [`Board::try_put_chess`] refused a chess, or [`Board::unapply`] a move.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PlaceError {
+    OutOfRange,
+    Occupied,
+    /// The move does not follow the last one, or is not the last one when taken back.
+    OutOfOrder,
+}
+
+impl fmt::Display for PlaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlaceError::OutOfRange => write!(f, "point is outside the board"),
+            PlaceError::Occupied => write!(f, "point is occupied"),
+            PlaceError::OutOfOrder => write!(f, "move is out of order"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum CellState {
+    Empty,
+    Black,
+    White,
+    Red,
+    Blue,
+    Green,
+    Yellow,
+    Purple,
+    Orange,
+}
+
+impl CellState {
+    /// Color of the chess in the cell, `None` when it is empty.
+    pub fn color(self) -> Option<ChessColor> {
+        match self {
+            CellState::Empty => None,
+            CellState::Black => Some(ChessColor::Black),
+            CellState::White => Some(ChessColor::White),
+            CellState::Red => Some(ChessColor::Red),
+            CellState::Blue => Some(ChessColor::Blue),
+            CellState::Green => Some(ChessColor::Green),
+            CellState::Yellow => Some(ChessColor::Yellow),
+            CellState::Purple => Some(ChessColor::Purple),
+            CellState::Orange => Some(ChessColor::Orange),
+        }
+    }
+}
+
+impl From<ChessColor> for CellState {
+    fn from(color: ChessColor) -> Self {
+        match color {
+            ChessColor::Black => CellState::Black,
+            ChessColor::White => CellState::White,
+            ChessColor::Red => CellState::Red,
+            ChessColor::Blue => CellState::Blue,
+            ChessColor::Green => CellState::Green,
+            ChessColor::Yellow => CellState::Yellow,
+            ChessColor::Purple => CellState::Purple,
+            ChessColor::Orange => CellState::Orange,
+        }
+    }
+}
+
+/// Chesses on a square board, kept both as a grid of cells and in the order they were put.
+///
+/// Captured chesses stay in the order they were put but leave the grid, see
+/// [`Board::remove_chesses`].
+#[derive(Clone, Debug)]
+pub struct Board {
+    cells_per_row: usize,
+    cells: Vec<CellState>,
+    chesses: Vec<Chess>,
+    /// Whether each chess in `chesses` is still on the board.
+    on_board: Vec<bool>,
+    /// Plies of the chesses each move in `chesses` captured.
+    captures: Vec<Vec<usize>>,
+    /// Colors of the players in the order they move, black and white unless made for more players.
+    players: Vec<ChessColor>,
+    /// Color of the next chess, the player after the last one unless set for a composed position.
+    next: ChessColor,
+}
+
+impl Board {
+    pub const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+    pub fn new(cells_per_row: usize) -> Self {
+        Self::with_players(cells_per_row, vec![ChessColor::Black, ChessColor::White])
+    }
+
+    /// An empty board for players of `players` taking turns in that order, each with a color of
+    /// their own. Black and white play when there are none.
+    pub fn with_players(cells_per_row: usize, players: Vec<ChessColor>) -> Self {
+        let players = if players.is_empty() { vec![ChessColor::Black, ChessColor::White] } else { players };
+        let mut cells = Vec::with_capacity(cells_per_row * cells_per_row);
+        cells.resize(cells_per_row * cells_per_row, CellState::Empty);
+
+        Self {
+            cells_per_row,
+            cells,
+            chesses: vec![],
+            on_board: vec![],
+            captures: vec![],
+            next: players[0],
+            players,
+        }
+    }
+
+    /// Colors of the players in the order they move.
+    pub fn players(&self) -> &[ChessColor] {
+        &self.players
+    }
+
+    pub fn cells_per_row(&self) -> usize {
+        self.cells_per_row
+    }
+
+    pub fn cells(&self) -> &[CellState] {
+        &self.cells
+    }
+
+    /// What is at `index`, `None` outside the board.
+    pub fn cell(&self, index: usize) -> Option<CellState> {
+        self.cells.get(index).copied()
+    }
+
+    /// Every chess put so far in order, including captured ones.
+    pub fn chesses(&self) -> &[Chess] {
+        &self.chesses
+    }
+
+    /// The chesses which were not captured, in the order they were put.
+    pub fn stones(&self) -> impl Iterator<Item = &Chess> + '_ {
+        self.chesses.iter().zip(&self.on_board).filter(|(_, &on_board)| on_board).map(|(chess, _)| chess)
+    }
+
+    /// Number of chesses the moves of `color` captured.
+    pub fn captured_by(&self, color: ChessColor) -> usize {
+        self.chesses.iter().zip(&self.captures)
+            .filter(|(chess, _)| chess.color == color)
+            .map(|(_, captured)| captured.len())
+            .sum()
+    }
+
+    pub fn valid_index(&self, index: usize) -> bool {
+        index < self.cells_per_row * self.cells_per_row
+    }
+
+    pub fn valid_pos(&self, col: usize, row: usize) -> bool {
+        col < self.cells_per_row && row < self.cells_per_row
+    }
+
+    pub fn index_to_pos(&self, index: usize) -> Pos {
+        Pos::new(index % self.cells_per_row, index / self.cells_per_row)
+    }
+
+    pub fn pos_to_index(&self, pos: Pos) -> usize {
+        pos.x + pos.y * self.cells_per_row
+    }
+
+    pub fn pos_to_coord(&self, pos: Pos) -> String {
+        format!("{}{}", (b'a' + pos.x as u8) as char, self.cells_per_row - pos.y)
+    }
+
+    /// Parses a coordinate like "h8" as written by [`Board::pos_to_coord`].
+    pub fn coord_to_pos(&self, coord: &str) -> Option<Pos> {
+        let coord = coord.trim().to_ascii_lowercase();
+        let mut chars = coord.chars();
+        let col = chars.next().filter(|c| c.is_ascii_lowercase())? as usize - 'a' as usize;
+        let row: usize = chars.as_str().parse().ok()?;
+        if col < self.cells_per_row && row >= 1 && row <= self.cells_per_row {
+            Some(Pos::new(col, self.cells_per_row - row))
+        } else {
+            None
+        }
+    }
+
+    pub fn is_empty_at(&self, index: usize) -> bool {
+        self.valid_index(index) && self.cells[index] == CellState::Empty
+    }
+
+    pub fn has_chess(&self) -> bool {
+        !self.chesses.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        !self.cells.contains(&CellState::Empty)
+    }
+
+    pub fn next_is_black(&self) -> bool {
+        self.next == ChessColor::Black
+    }
+
+    /// Color of the next chess.
+    pub fn next(&self) -> ChessColor {
+        self.next
+    }
+
+    /// The player moving after `color`, which is the opponent in a game of two.
+    pub fn player_after(&self, color: ChessColor) -> ChessColor {
+        let i = self.players.iter().position(|&player| player == color).unwrap_or(0);
+        self.players[(i + 1) % self.players.len()]
+    }
+
+    /// Chooses the side to move in a position set up chess by chess, until the next move is applied.
+    pub fn set_next(&mut self, color: ChessColor) {
+        self.next = color;
+    }
+
+    /// Number of chesses put so far, which is the ply of the next move.
+    pub fn ply(&self) -> usize {
+        self.chesses.len()
+    }
+
+    /// The moves which led to this board from an empty one, in order.
+    pub fn moves(&self) -> impl Iterator<Item = Move> + '_ {
+        self.chesses.iter().enumerate().map(|(ply, c)| Move::new(c.color, self.pos_to_index(c.pos), ply))
+    }
+
+    pub fn last_move(&self) -> Option<Move> {
+        self.moves().last()
+    }
+
+    /// Puts the chess of `mv`, which must be on an empty cell and follow the last move.
+    pub fn apply(&mut self, mv: Move) -> Result<(), PlaceError> {
+        if !self.valid_index(mv.index) {
+            return Err(PlaceError::OutOfRange);
+        }
+        if mv.ply != self.ply() {
+            return Err(PlaceError::OutOfOrder);
+        }
+        if self.cells[mv.index] != CellState::Empty {
+            return Err(PlaceError::Occupied);
+        }
+        self.chesses.push(Chess { pos: self.index_to_pos(mv.index), color: mv.player });
+        self.on_board.push(true);
+        self.captures.push(vec![]);
+        self.next = self.player_after(mv.player);
+        self.cells[mv.index] = CellState::from(mv.player);
+        Ok(())
+    }
+
+    /// Puts a chess of `color` at `index` as the next move, for boards filled from outside a
+    /// [`crate::game::Game`].
+    pub fn try_put_chess(&mut self, color: ChessColor, index: usize) -> Result<Move, PlaceError> {
+        let mv = Move::new(color, index, self.ply());
+        self.apply(mv)?;
+        Ok(mv)
+    }
+
+    /// Takes the chesses at `indices` off the board as captured by the last move, they come back
+    /// when it is taken back. Empty points are skipped.
+    pub fn remove_chesses(&mut self, indices: &[usize]) {
+        for &index in indices {
+            if !self.valid_index(index) || self.cells[index] == CellState::Empty {
+                continue;
+            }
+            let pos = self.index_to_pos(index);
+            let Some(ply) = (0..self.chesses.len()).rev().find(|&ply| self.on_board[ply] && self.chesses[ply].pos == pos) else {
+                continue;
+            };
+            self.on_board[ply] = false;
+            self.cells[index] = CellState::Empty;
+            if let Some(captures) = self.captures.last_mut() {
+                captures.push(ply);
+            }
+        }
+    }
+
+    /// Takes back the last move and returns it, `None` on an empty board.
+    pub fn undo(&mut self) -> Option<Move> {
+        let mv = self.last_move()?;
+        self.unapply(mv).ok()?;
+        Some(mv)
+    }
+
+    /// Takes back `mv`, which must be the [`Board::last_move`].
+    pub fn unapply(&mut self, mv: Move) -> Result<(), PlaceError> {
+        if self.last_move() != Some(mv) {
+            return Err(PlaceError::OutOfOrder);
+        }
+        self.chesses.pop();
+        self.on_board.pop();
+        self.cells[mv.index] = CellState::Empty;
+        for ply in self.captures.pop().unwrap_or_default() {
+            let chess = self.chesses[ply];
+            let index = self.pos_to_index(chess.pos);
+            self.on_board[ply] = true;
+            self.cells[index] = CellState::from(chess.color);
+        }
+        self.next = mv.player;
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::with_players(self.cells_per_row, std::mem::take(&mut self.players));
+    }
+
+    /// Space separated coordinates of all chesses in the order they were put, e.g. "h8 i9 j10".
+    pub fn move_list(&self) -> String {
+        self.chesses.iter().map(|c| self.pos_to_coord(c.pos)).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Counts chesses of `state` next to `pos` going in the direction of (`dx`, `dy`), `pos` itself is not counted.
+    pub fn count_direction(&self, pos: Pos, dx: i32, dy: i32, state: CellState) -> usize {
+        let mut count = 0;
+        let mut col = pos.x as i32 + dx;
+        let mut row = pos.y as i32 + dy;
+        while col >= 0 && row >= 0 && (col as usize) < self.cells_per_row && (row as usize) < self.cells_per_row
+            && self.cells[self.pos_to_index(Pos::new(col as usize, row as usize))] == state {
+            count += 1;
+            col += dx;
+            row += dy;
+        }
+        count
+    }
+}
+
+/// Draws the board with `X` for black, `O` for white, the lower case initial for the other colors
+/// and the coordinates around it.
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let columns: String = (0..self.cells_per_row).map(|x| format!(" {}", (b'a' + x as u8) as char)).collect();
+        writeln!(f, "   {}", columns)?;
+        for y in 0..self.cells_per_row {
+            write!(f, "{:>2} ", self.cells_per_row - y)?;
+            for x in 0..self.cells_per_row {
+                let cell = match self.cells[self.pos_to_index(Pos::new(x, y))].color() {
+                    None => '.',
+                    Some(ChessColor::Black) => 'X',
+                    Some(ChessColor::White) => 'O',
+                    Some(color) => color.to_string().chars().next().unwrap_or('?').to_ascii_lowercase(),
+                };
+                write!(f, " {}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new(15)
+    }
+}
@@ -0,0 +1,1223 @@
+//! An iced canvas drawing a [`Board`] with its overlays: the last move marker, marks, a hint, an
+//! engine heatmap, forbidden points, threats with arrows to their blocks, the winning line and a
+//! context menu. The board can be shown turned and mirrored, see [`Orientation`].
+//!
+//! The view keeps only presentation state, the board itself is passed in when drawing. Clicks are
+//! reported as [`BoardEvent`]s, which the embedding application turns into its own messages:
+//!
+//! ```no_run
+//! use gomoku_rs::board::Board;
+//! use gomoku_rs::board_view::{BoardEvent, BoardView};
+//!
+//! #[derive(Debug, Clone)]
+//! enum Message {
+//!     Board(BoardEvent),
+//! }
+//!
+//! fn view<'a>(board_view: &'a BoardView, board: &'a Board) -> iced::Element<'a, Message> {
+//!     board_view.view(board, Message::Board)
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use iced::{keyboard, mouse, touch, Color, Size};
+use iced::keyboard::key::Named;
+use iced::alignment::{Horizontal, Vertical};
+use iced::mouse::{Cursor, Interaction};
+use iced::widget::canvas::{self, Cache, Canvas, Frame, Geometry, Path, Stroke, Text, event};
+use iced::{Element, Rectangle, Renderer, Theme, Point, Length};
+use iced::time::{Duration, Instant};
+
+use crate::board::{Board, CellState, Chess, ChessColor, Pos};
+use crate::threats::{Threat, ThreatKind};
+
+/// What the user did on the board.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum BoardEvent {
+    /// A left click or touch at the point with this index.
+    Click(usize),
+    /// A right click at this position in the canvas, on the point with this index if any.
+    OpenContextMenu(Point, Option<usize>),
+    /// A click next to the open context menu.
+    CloseContextMenu,
+    /// An entry of the open context menu was chosen.
+    ContextMenu(MenuAction),
+    /// The mouse moved to the point a click would land on, `None` when it left the board.
+    Hover(Option<usize>),
+    /// The mouse wheel turned over the board, one step back for each notch up and forward for each
+    /// notch down. Page Up and Page Down step the same while the pointer is over the board.
+    Scrub(i32),
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum MenuAction {
+    Undo,
+    Hint,
+    CopyPosition,
+    CopyTranscript,
+    MarkPoint,
+    PasteGame,
+    ExportAnimation,
+    Rotate,
+    Mirror,
+}
+
+impl MenuAction {
+    pub const ALL: [MenuAction; 9] = [
+        MenuAction::Undo, MenuAction::Hint, MenuAction::CopyPosition, MenuAction::CopyTranscript, MenuAction::MarkPoint, MenuAction::PasteGame,
+        MenuAction::ExportAnimation, MenuAction::Rotate, MenuAction::Mirror,
+    ];
+}
+
+/// How the board is turned on screen, for studying a position the way a book shows it. Only the
+/// drawing and the clicks are turned, the board keeps its coordinates.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct Orientation {
+    /// Quarter turns clockwise.
+    pub turns: u8,
+    /// Whether the board is mirrored left to right before it is turned.
+    pub mirrored: bool,
+}
+
+impl Orientation {
+    /// Turned a further quarter clockwise.
+    pub fn rotated(self) -> Self {
+        Self { turns: (self.turns + 1) % 4, ..self }
+    }
+
+    /// Mirrored left to right as it is shown now.
+    pub fn flipped(self) -> Self {
+        // mirroring after the turns equals mirroring first and turning the other way
+        Self { turns: (4 - self.turns) % 4, mirrored: !self.mirrored }
+    }
+
+    /// Where the point `pos` of a board with `size` rows is shown.
+    pub fn apply(self, pos: Pos, size: usize) -> Pos {
+        let last = size - 1;
+        let mut pos = if self.mirrored { Pos::new(last - pos.x, pos.y) } else { pos };
+        for _ in 0..self.turns {
+            pos = Pos::new(last - pos.y, pos.x);
+        }
+        pos
+    }
+
+    /// The point of a board with `size` rows which is shown at `pos`, undoing [`Orientation::apply`].
+    pub fn invert(self, pos: Pos, size: usize) -> Pos {
+        let last = size - 1;
+        let mut pos = pos;
+        for _ in 0..self.turns {
+            pos = Pos::new(pos.y, last - pos.x);
+        }
+        if self.mirrored { Pos::new(last - pos.x, pos.y) } else { pos }
+    }
+}
+
+/// A small picture for the board background or the chesses. Canvases cannot draw images, so
+/// every pixel is drawn as a rectangle and pictures have to be kept small.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bitmap {
+    pub width: usize,
+    pub height: usize,
+    /// Colors of the pixels row by row from the top left.
+    pub pixels: Vec<Color>,
+}
+
+/// How chesses are drawn.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum StoneSkin {
+    /// One color with an outline, the quickest to draw on a slow machine.
+    Flat,
+    /// Lit from the top left with a radial gradient, casting a soft shadow.
+    #[default]
+    Shaded,
+    /// Matte slate for black and striped clam shell for white, like traditional go stones.
+    ShellSlate,
+    /// Pictures given with [`BoardView::set_stone_pictures`], flat until there are some.
+    Pictures,
+}
+
+impl StoneSkin {
+    pub const ALL: [StoneSkin; 4] = [StoneSkin::Flat, StoneSkin::Shaded, StoneSkin::ShellSlate, StoneSkin::Pictures];
+}
+
+impl fmt::Display for StoneSkin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StoneSkin::Flat => "Flat",
+            StoneSkin::Shaded => "Shaded",
+            StoneSkin::ShellSlate => "Shell and slate",
+            StoneSkin::Pictures => "Pictures",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for StoneSkin {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        StoneSkin::ALL.iter()
+            .find(|skin| skin.to_string().eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("unknown stone skin \"{}\"", s))
+    }
+}
+
+/// How the last put chess is marked.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum MarkerStyle {
+    #[default]
+    Cross,
+    Dot,
+    Ring,
+    Square,
+}
+
+impl MarkerStyle {
+    pub const ALL: [MarkerStyle; 4] = [MarkerStyle::Cross, MarkerStyle::Dot, MarkerStyle::Ring, MarkerStyle::Square];
+}
+
+impl fmt::Display for MarkerStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MarkerStyle::Cross => "Cross",
+            MarkerStyle::Dot => "Dot",
+            MarkerStyle::Ring => "Ring",
+            MarkerStyle::Square => "Square",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for MarkerStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MarkerStyle::ALL.iter()
+            .find(|style| style.to_string().eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("unknown marker style \"{}\"", s))
+    }
+}
+
+/// How far from an intersection a click may land and still count as a click on it.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ClickSnap {
+    /// Within this many percent of half a cell.
+    Within(u16),
+    /// Always the nearest intersection, even from outside the grid.
+    Nearest,
+}
+
+impl ClickSnap {
+    pub const ALL: [ClickSnap; 5] = [ClickSnap::Within(40), ClickSnap::Within(60), ClickSnap::Within(80), ClickSnap::Within(100), ClickSnap::Nearest];
+}
+
+impl Default for ClickSnap {
+    fn default() -> Self {
+        ClickSnap::Within(60)
+    }
+}
+
+impl fmt::Display for ClickSnap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClickSnap::Within(percent) => write!(f, "{}%", percent),
+            ClickSnap::Nearest => write!(f, "Nearest"),
+        }
+    }
+}
+
+impl FromStr for ClickSnap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("nearest") {
+            return Ok(ClickSnap::Nearest);
+        }
+        match s.trim().trim_end_matches('%').parse() {
+            Ok(percent @ 10..=100) => Ok(ClickSnap::Within(percent)),
+            _ => Err(format!("click snap must be nearest or between 10% and 100%, got \"{}\"", s)),
+        }
+    }
+}
+
+/// Colors the board is drawn with.
+struct Palette {
+    background: Color,
+    grid: Color,
+    outline: Color,
+    black: Color,
+    white: Color,
+    last_move: Color,
+    mark: Color,
+    winning_line: Color,
+    hint: Color,
+    forbidden: Color,
+    /// Open threes are drawn in `threat`, fours in `forbidden` as they are more urgent.
+    threat: Color,
+    /// Heatmap colors from the worst through average to the best move.
+    heat: [Color; 3],
+    /// Markers are stroked this many times thicker than the grid lines.
+    marker_width: f32,
+}
+
+impl Palette {
+    fn normal() -> Self {
+        Self {
+            background: Color::from_rgb8(0xf0, 0xf0, 0xf0),
+            grid: Color::from_rgb8(0x60, 0x64, 0x6b),
+            outline: Color::from_rgb8(0x60, 0x60, 0x60),
+            black: Color::from_rgb8(0x20, 0x20, 0x20),
+            white: Color::from_rgb8(0xf0, 0xf0, 0xf0),
+            last_move: Color::from_rgb8(0xff, 0x00, 0x00),
+            mark: Color::from_rgb8(0x1e, 0x88, 0xe5),
+            winning_line: Color::from_rgb8(0xff, 0xb3, 0x00),
+            hint: Color::from_rgba8(0x2e, 0x7d, 0x32, 0.4),
+            forbidden: Color::from_rgb8(0xc6, 0x28, 0x28),
+            threat: Color::from_rgb8(0xef, 0x6c, 0x00),
+            heat: [Color::from_rgb8(0xd3, 0x2f, 0x2f), Color::from_rgb8(0xfb, 0xc0, 0x2d), Color::from_rgb8(0x38, 0x8e, 0x3c)],
+            marker_width: 1.0,
+        }
+    }
+
+    /// Pure black and white with markers from the Okabe-Ito palette, which stay apart for colorblind eyes.
+    fn high_contrast() -> Self {
+        Self {
+            background: Color::from_rgb8(0xff, 0xff, 0xff),
+            grid: Color::from_rgb8(0x00, 0x00, 0x00),
+            outline: Color::from_rgb8(0x00, 0x00, 0x00),
+            black: Color::from_rgb8(0x00, 0x00, 0x00),
+            white: Color::from_rgb8(0xff, 0xff, 0xff),
+            last_move: Color::from_rgb8(0xe6, 0x9f, 0x00),
+            mark: Color::from_rgb8(0x56, 0xb4, 0xe9),
+            winning_line: Color::from_rgb8(0xd5, 0x5e, 0x00),
+            hint: Color::from_rgba8(0x00, 0x9e, 0x73, 0.6),
+            forbidden: Color::from_rgb8(0xcc, 0x79, 0xa7),
+            threat: Color::from_rgb8(0xe6, 0x9f, 0x00),
+            heat: [Color::from_rgb8(0xd5, 0x5e, 0x00), Color::from_rgb8(0xf0, 0xe4, 0x42), Color::from_rgb8(0x00, 0x72, 0xb2)],
+            marker_width: 2.0,
+        }
+    }
+}
+
+/// The highlight of the point under the mouse, gliding over from where it was shown before.
+struct Hover {
+    from: Option<Point>,
+    index: usize,
+    start: Instant,
+}
+
+struct ContextMenu {
+    position: Point,
+    index: Option<usize>,
+}
+
+/// Draws a [`Board`] on a canvas and keeps everything which is only about presenting it.
+pub struct BoardView {
+    padding: f32,
+    cell_size: f32,
+    chess_size: f32,
+    line_width: f32,
+    grid_size: f32,
+    cells_per_row: usize,
+    marks: Vec<usize>,
+    hint: Option<usize>,
+    /// Empty points with how good they are from 0.0 (worst) to 1.0 (best).
+    heatmap: Vec<(usize, f32)>,
+    /// Points black may not play under Renju rules.
+    forbidden: Vec<usize>,
+    /// Moves offered to choose from during the opening, drawn as faint black chesses.
+    offers: Vec<usize>,
+    threats: Vec<Threat>,
+    winning_line: Vec<usize>,
+    celebration: Option<f32>,
+    context_menu: Option<ContextMenu>,
+    hover: Option<Hover>,
+    marker_style: MarkerStyle,
+    click_snap: ClickSnap,
+    /// Whether clicks put chesses, not while an earlier position is looked at.
+    clickable: bool,
+    /// Whether the area around each point a click lands on is outlined, see [`BoardView::set_show_hit_regions`].
+    show_hit_regions: bool,
+    orientation: Orientation,
+    /// Whether the board is shown turned half around on top of `orientation`, for the player
+    /// sitting across the table.
+    half_turn: bool,
+    background: Option<Arc<Bitmap>>,
+    stone_skin: StoneSkin,
+    /// Pictures of a black and a white chess for [`StoneSkin::Pictures`].
+    stone_pictures: Option<Arc<[Bitmap; 2]>>,
+    palette: Palette,
+    chesses_cache: Cache,
+    /// The chesses drawn into `chesses_cache`, newer ones are drawn on their own until there are
+    /// [`Self::MAX_UNCACHED`] of them.
+    cached_chesses: RefCell<Vec<Chess>>,
+    grid_cache: Cache,
+    /// The overlay is split into caches so a change of one part does not redraw the others,
+    /// this one holds the last move marker.
+    last_move_cache: Cache,
+    /// Heatmap, hint and threats, refreshed as the analysis goes on.
+    analysis_cache: Cache,
+    /// Forbidden points, offers, the player's marks and the winning line.
+    marks_cache: Cache,
+}
+
+impl BoardView {
+    const MENU_ITEM_WIDTH: f32 = 150.0;
+    const MENU_ITEM_HEIGHT: f32 = 28.0;
+    const HOVER_GLIDE: Duration = Duration::from_millis(90);
+    const MAX_UNCACHED: usize = 8;
+
+    pub fn new(cells_per_row: usize, padding: f32, cell_size: f32, chess_size: f32, line_width: f32) -> Self {
+        let grid_size = (cells_per_row - 1) as f32 * cell_size + line_width;
+
+        Self {
+            padding,
+            cell_size,
+            chess_size,
+            line_width,
+            grid_size,
+            cells_per_row,
+            marks: vec![],
+            hint: None,
+            heatmap: vec![],
+            forbidden: vec![],
+            offers: vec![],
+            threats: vec![],
+            winning_line: vec![],
+            celebration: None,
+            context_menu: None,
+            hover: None,
+            marker_style: MarkerStyle::default(),
+            click_snap: ClickSnap::default(),
+            clickable: true,
+            show_hit_regions: false,
+            orientation: Orientation::default(),
+            half_turn: false,
+            background: None,
+            stone_skin: StoneSkin::default(),
+            stone_pictures: None,
+            palette: Palette::normal(),
+            chesses_cache: Cache::default(),
+            cached_chesses: RefCell::new(vec![]),
+            grid_cache: Cache::default(),
+            last_move_cache: Cache::default(),
+            analysis_cache: Cache::default(),
+            marks_cache: Cache::default(),
+        }
+    }
+
+    /// Must be called whenever chesses are put on or removed from the board.
+    ///
+    /// The chesses cache is kept, drawing finds out whether the new chesses can be drawn on top of it.
+    pub fn chesses_changed(&mut self) {
+        self.hint = None;
+        self.heatmap.clear();
+        self.threats.clear();
+        self.winning_line.clear();
+        self.celebration = None;
+        self.clear_overlay();
+    }
+
+    fn clear_overlay(&self) {
+        self.last_move_cache.clear();
+        self.analysis_cache.clear();
+        self.marks_cache.clear();
+    }
+
+    pub fn toggle_mark(&mut self, index: usize) {
+        match self.marks.iter().position(|&i| i == index) {
+            Some(i) => { self.marks.remove(i); },
+            None => { self.marks.push(index); },
+        }
+        self.marks_cache.clear();
+    }
+
+    pub fn set_hint(&mut self, hint: Option<usize>) {
+        self.hint = hint;
+        self.analysis_cache.clear();
+    }
+
+    /// Colors points by engine `scores`, an empty list hides the heatmap.
+    pub fn set_heatmap(&mut self, scores: &[(usize, i32)]) {
+        // compress the scale so a single winning move does not wash out all other differences
+        let compress = |score: i32| (score.unsigned_abs() as f32).ln_1p().copysign(score as f32);
+        let min = scores.iter().map(|&(_, score)| compress(score)).fold(f32::INFINITY, f32::min);
+        let max = scores.iter().map(|&(_, score)| compress(score)).fold(f32::NEG_INFINITY, f32::max);
+        self.heatmap = scores.iter().map(|&(index, score)| {
+            let value = if max > min { (compress(score) - min) / (max - min) } else { 1.0 };
+            (index, value)
+        }).collect();
+        self.analysis_cache.clear();
+    }
+
+    pub fn set_forbidden(&mut self, forbidden: Vec<usize>) {
+        self.forbidden = forbidden;
+        self.marks_cache.clear();
+    }
+
+    pub fn set_offers(&mut self, offers: Vec<usize>) {
+        self.offers = offers;
+        self.marks_cache.clear();
+    }
+
+    /// Highlights the chesses of `threats` with arrows to the points blocking them.
+    pub fn set_threats(&mut self, threats: Vec<Threat>) {
+        self.threats = threats;
+        self.analysis_cache.clear();
+    }
+
+    pub fn set_winning_line(&mut self, line: Vec<usize>) {
+        self.winning_line = line;
+        self.marks_cache.clear();
+    }
+
+    /// Sets the progress of the winning animation from 0.0 to 1.0, `None` when it is over.
+    pub fn set_celebration(&mut self, progress: Option<f32>) {
+        // the winning line is drawn only once the animation is over
+        if self.celebration.is_some() != progress.is_some() {
+            self.marks_cache.clear();
+        }
+        self.celebration = progress;
+    }
+
+    pub fn set_click_snap(&mut self, snap: ClickSnap) {
+        if self.click_snap != snap {
+            self.click_snap = snap;
+            if self.show_hit_regions {
+                self.grid_cache.clear();
+            }
+        }
+    }
+
+    /// Outlines the area around each point a click lands on, a help for tuning [`ClickSnap`] and
+    /// checking the mapping of clicks to points after changing the geometry.
+    pub fn set_show_hit_regions(&mut self, show: bool) {
+        if self.show_hit_regions != show {
+            self.show_hit_regions = show;
+            self.grid_cache.clear();
+        }
+    }
+
+    /// Lets clicks on points put chesses when `true`, the context menu opens either way.
+    pub fn set_clickable(&mut self, clickable: bool) {
+        self.clickable = clickable;
+        if !clickable {
+            self.hover = None;
+        }
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+        self.chesses_cache.clear();
+        self.clear_overlay();
+    }
+
+    pub fn set_half_turn(&mut self, half_turn: bool) {
+        if self.half_turn != half_turn {
+            self.half_turn = half_turn;
+            self.chesses_cache.clear();
+            self.clear_overlay();
+        }
+    }
+
+    /// The orientation the board is drawn in, including the half turn.
+    fn shown_orientation(&self) -> Orientation {
+        if self.half_turn { self.orientation.rotated().rotated() } else { self.orientation }
+    }
+
+    /// Draws `background` under the grid, the palette's background color when `None`.
+    pub fn set_background(&mut self, background: Option<Arc<Bitmap>>) {
+        if self.background != background {
+            self.background = background;
+            self.grid_cache.clear();
+        }
+    }
+
+    pub fn set_stone_skin(&mut self, skin: StoneSkin) {
+        if self.stone_skin != skin {
+            self.stone_skin = skin;
+            self.chesses_cache.clear();
+        }
+    }
+
+    pub fn set_stone_pictures(&mut self, pictures: Option<Arc<[Bitmap; 2]>>) {
+        if self.stone_pictures != pictures {
+            self.stone_pictures = pictures;
+            self.chesses_cache.clear();
+        }
+    }
+
+    pub fn set_marker_style(&mut self, style: MarkerStyle) {
+        self.marker_style = style;
+        self.last_move_cache.clear();
+    }
+
+    pub fn set_high_contrast(&mut self, high_contrast: bool) {
+        self.palette = if high_contrast { Palette::high_contrast() } else { Palette::normal() };
+        self.grid_cache.clear();
+        self.chesses_cache.clear();
+        self.clear_overlay();
+    }
+
+    /// Moves the highlight to the point at `index` a click would land on, hides it when `None`.
+    pub fn set_hover(&mut self, index: Option<usize>) {
+        let now = Instant::now();
+        let from = self.hover_center(now);
+        self.hover = index.map(|index| Hover { from, index, start: now });
+    }
+
+    /// Whether the highlight is still gliding to its point, which needs a redraw every frame.
+    pub fn is_hover_gliding(&self) -> bool {
+        self.hover.as_ref().is_some_and(|hover| hover.from.is_some() && hover.start.elapsed() < Self::HOVER_GLIDE)
+    }
+
+    /// Where the highlight is drawn at `now`.
+    fn hover_center(&self, now: Instant) -> Option<Point> {
+        let hover = self.hover.as_ref()?;
+        let to = self.chess_center(Pos::new(hover.index % self.cells_per_row, hover.index / self.cells_per_row));
+        let Some(from) = hover.from else { return Some(to); };
+        let t = (now.saturating_duration_since(hover.start).as_secs_f32() / Self::HOVER_GLIDE.as_secs_f32()).min(1.0);
+        // ease out, fast at first and settling softly on the point
+        let t = 1.0 - (1.0 - t) * (1.0 - t);
+        Some(Point::new(from.x + (to.x - from.x) * t, from.y + (to.y - from.y) * t))
+    }
+
+    pub fn open_context_menu(&mut self, position: Point, index: Option<usize>) {
+        self.context_menu = Some(ContextMenu { position, index });
+    }
+
+    /// Closes the context menu, returns the index it was opened at.
+    pub fn close_context_menu(&mut self) -> Option<usize> {
+        self.context_menu.take().and_then(|menu| menu.index)
+    }
+
+    /// Draws `board`, turning what the user does on it into messages with `on_event`.
+    pub fn view<'a, Message: 'a>(&'a self, board: &'a Board, on_event: impl Fn(BoardEvent) -> Message + 'a) -> Element<'a, Message> {
+        Canvas::new(BoardCanvas { view: self, board, on_event: Box::new(on_event) }).width(Length::Fill).height(Length::Fill).into()
+    }
+
+    fn menu_action_enabled(&self, board: &Board, action: MenuAction) -> bool {
+        match action {
+            MenuAction::Undo => board.has_chess(),
+            MenuAction::Hint => !board.is_full(),
+            MenuAction::CopyPosition => board.has_chess(),
+            MenuAction::CopyTranscript => board.has_chess(),
+            MenuAction::MarkPoint => self.context_menu.as_ref().is_some_and(|menu| menu.index.is_some()),
+            MenuAction::PasteGame => true,
+            MenuAction::ExportAnimation => board.has_chess(),
+            MenuAction::Rotate | MenuAction::Mirror => true,
+        }
+    }
+
+    fn menu_action_label(&self, action: MenuAction) -> &'static str {
+        match action {
+            MenuAction::Undo => "Undo",
+            MenuAction::Hint => "Hint",
+            MenuAction::CopyPosition => "Copy position",
+            MenuAction::CopyTranscript => "Copy transcript",
+            MenuAction::PasteGame => "Paste game",
+            MenuAction::ExportAnimation => "Export animation",
+            MenuAction::Rotate => "Rotate 90° (R)",
+            MenuAction::Mirror => "Mirror (M)",
+            MenuAction::MarkPoint => {
+                let marked = self.context_menu.as_ref()
+                    .and_then(|menu| menu.index)
+                    .is_some_and(|index| self.marks.contains(&index));
+                if marked { "Unmark point" } else { "Mark point" }
+            },
+        }
+    }
+
+    /// Bounds of the opened context menu, moved to stay inside the canvas.
+    fn menu_bounds(&self, size: Size) -> Option<Rectangle> {
+        self.context_menu.as_ref().map(|menu| {
+            let menu_size = Size::new(Self::MENU_ITEM_WIDTH, Self::MENU_ITEM_HEIGHT * MenuAction::ALL.len() as f32);
+            let x = menu.position.x.min(size.width - menu_size.width).max(0.0);
+            let y = menu.position.y.min(size.height - menu_size.height).max(0.0);
+            Rectangle::new(Point::new(x, y), menu_size)
+        })
+    }
+
+    fn menu_action_at(&self, size: Size, pos: Point) -> Option<MenuAction> {
+        self.menu_bounds(size).and_then(|menu_bounds| {
+            if menu_bounds.contains(pos) {
+                let item = ((pos.y - menu_bounds.y) / Self::MENU_ITEM_HEIGHT) as usize;
+                MenuAction::ALL.get(item).copied()
+            } else {
+                None
+            }
+        })
+    }
+
+    /// How far from a point a click may land and still count, `None` when it always counts for the nearest one.
+    fn hit_radius(&self) -> Option<f32> {
+        match self.click_snap {
+            ClickSnap::Within(percent) => Some(self.cell_size * percent as f32 / 100.0 / 2.0),
+            ClickSnap::Nearest => None,
+        }
+    }
+
+    fn grid_pos(&self, board: &Board, x: f32, y: f32) -> Option<Pos> {
+        let pos_from_grid = Point::new(x - self.padding, y - self.padding);
+        let col = (pos_from_grid.x / self.cell_size).round() as i32;
+        let row = (pos_from_grid.y / self.cell_size).round() as i32;
+        let Some(hit_radius) = self.hit_radius() else {
+            let last = board.cells_per_row() as i32 - 1;
+            let shown = Pos::new(col.clamp(0, last) as usize, row.clamp(0, last) as usize);
+            return Some(self.shown_orientation().invert(shown, board.cells_per_row()));
+        };
+        if col >= 0 && row >= 0 && board.valid_pos(col as usize, row as usize) {
+            let dis = pos_from_grid.distance(Point::new(col as f32 * self.cell_size, row as f32 * self.cell_size));
+            // println!("board pos {}, grid pos {}, col {}, row {}, dis {}", Point::new(x, y), pos_from_grid, col, row, dis);
+            if dis > hit_radius {
+                None
+            } else {
+                Some(self.shown_orientation().invert(Pos::new(col as usize, row as usize), board.cells_per_row()))
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Center of the board point `pos` on screen, where the orientation shows it.
+    fn chess_center(&self, pos: Pos) -> Point {
+        let pos = self.shown_orientation().apply(pos, self.cells_per_row);
+        Point::new(
+            self.padding + pos.x as f32 * self.cell_size,
+            self.padding + pos.y as f32 * self.cell_size)
+    }
+
+    /// Draws an arrow from the edge of the chess at `from` to the point `to`.
+    fn draw_arrow(&self, frame: &mut Frame, from: Point, to: Point, color: Color) {
+        let (dx, dy) = (to.x - from.x, to.y - from.y);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length <= self.chess_size / 2.0 {
+            return;
+        }
+        let (ux, uy) = (dx / length, dy / length);
+        let start = Point::new(from.x + ux * self.chess_size / 2.0, from.y + uy * self.chess_size / 2.0);
+        let head = self.cell_size / 5.0;
+        let arrow = Path::new(|b| {
+            b.move_to(start);
+            b.line_to(to);
+            // the head's sides go back from the tip at 30 degrees to either side of the shaft
+            b.move_to(Point::new(to.x - head * (ux * 0.87 - uy * 0.5), to.y - head * (uy * 0.87 + ux * 0.5)));
+            b.line_to(to);
+            b.line_to(Point::new(to.x - head * (ux * 0.87 + uy * 0.5), to.y - head * (uy * 0.87 - ux * 0.5)));
+        });
+        frame.stroke(&arrow, Stroke::default().with_color(color).with_width(self.line_width * 1.5 * self.palette.marker_width));
+    }
+
+    /// Draws a chess of `color` at `center` in the stone skin.
+    fn draw_chess(&self, frame: &mut Frame, center: Point, color: ChessColor) {
+        let radius = self.chess_size / 2.0;
+        let is_black = color == ChessColor::Black;
+        let base = match color {
+            ChessColor::Black => self.palette.black,
+            ChessColor::White => self.palette.white,
+            ChessColor::Red => Color::from_rgb8(0xd3, 0x2f, 0x2f),
+            ChessColor::Blue => Color::from_rgb8(0x19, 0x76, 0xd2),
+            ChessColor::Green => Color::from_rgb8(0x38, 0x8e, 0x3c),
+            ChessColor::Yellow => Color::from_rgb8(0xfb, 0xc0, 0x2d),
+            ChessColor::Purple => Color::from_rgb8(0x7b, 0x1f, 0xa2),
+            ChessColor::Orange => Color::from_rgb8(0xf5, 0x7c, 0x00),
+        };
+        // slate, shells and the pictures are black and white, the other colors are shaded or flat
+        let skin = match self.stone_skin {
+            StoneSkin::ShellSlate | StoneSkin::Pictures if !matches!(color, ChessColor::Black | ChessColor::White) => StoneSkin::Flat,
+            skin => skin,
+        };
+        if matches!(skin, StoneSkin::Shaded | StoneSkin::ShellSlate) {
+            self.draw_chess_shadow(frame, center);
+        }
+        match (skin, &self.stone_pictures) {
+            (StoneSkin::Pictures, Some(pictures)) => {
+                let picture = &pictures[if is_black { 0 } else { 1 }];
+                let scale = self.chess_size / picture.width.max(picture.height) as f32;
+                let left = center.x - picture.width as f32 * scale / 2.0;
+                let top = center.y - picture.height as f32 * scale / 2.0;
+                for (i, &pixel) in picture.pixels.iter().enumerate().filter(|(_, pixel)| pixel.a > 0.0) {
+                    let (x, y) = ((i % picture.width) as f32, (i / picture.width) as f32);
+                    frame.fill_rectangle(
+                        Point::new(left + x * scale, top + y * scale),
+                        Size::new(scale + 0.5, scale + 0.5),
+                        pixel);
+                }
+            },
+            (StoneSkin::Shaded, _) => {
+                // the canvas has no radial gradients, rings shrinking towards the light and
+                // blending into the highlight make one, about one ring per pixel of the radius
+                let light = if is_black { Color::from_rgb8(0x7a, 0x7a, 0x7a) } else { Color::WHITE };
+                let shade = if is_black { base } else { mix(base, Color::BLACK, 0.25) };
+                let rings = (radius as usize).clamp(8, 32);
+                for i in 0..rings {
+                    let t = i as f32 / rings as f32;
+                    let ring_center = Point::new(center.x - radius * 0.4 * t, center.y - radius * 0.4 * t);
+                    // smoothstep keeps the rim dark and the highlight soft
+                    let light_amount = t * t * (3.0 - 2.0 * t);
+                    frame.fill(&Path::circle(ring_center, radius * (1.0 - 0.8 * t)), mix(shade, light, light_amount));
+                }
+            },
+            (StoneSkin::ShellSlate, _) if is_black => {
+                frame.fill(&Path::circle(center, radius), Color::from_rgb8(0x2b, 0x2d, 0x30));
+                frame.fill(&Path::circle(center, radius * 0.92), Color::from_rgb8(0x1c, 0x1d, 0x1f));
+            },
+            (StoneSkin::ShellSlate, _) => {
+                frame.fill(&Path::circle(center, radius), Color::from_rgb8(0xd8, 0xd4, 0xc8));
+                frame.fill(&Path::circle(center, radius * 0.94), Color::from_rgb8(0xf6, 0xf3, 0xea));
+                // the growth stripes of the shell, arcs of large circles centered below the chess
+                let stripe = Color::from_rgba8(0xb8, 0xb0, 0x9c, 0.6);
+                for k in 1..=4 {
+                    let arc_radius = radius * (1.2 + 0.35 * k as f32);
+                    let arc_center = Point::new(center.x, center.y + radius * 2.2);
+                    let points: Vec<Point> = (-20..=20)
+                        .map(|step| {
+                            let angle = step as f32 * 0.05;
+                            Point::new(arc_center.x + arc_radius * angle.sin(), arc_center.y - arc_radius * angle.cos())
+                        })
+                        .filter(|p| p.distance(center) < radius * 0.85)
+                        .collect();
+                    if let Some((first, rest)) = points.split_first() {
+                        let path = Path::new(|b| {
+                            b.move_to(*first);
+                            for &p in rest {
+                                b.line_to(p);
+                            }
+                        });
+                        frame.stroke(&path, Stroke::default().with_color(stripe).with_width(self.line_width / 2.0));
+                    }
+                }
+            },
+            _ => {
+                frame.fill(&Path::circle(center, radius), self.palette.outline);
+                frame.fill(&Path::circle(center, radius - self.line_width), base);
+            },
+        }
+    }
+
+    /// A blurred shadow below and right of the chess at `center`, faint rings growing outwards.
+    fn draw_chess_shadow(&self, frame: &mut Frame, center: Point) {
+        let radius = self.chess_size / 2.0;
+        let offset = radius * 0.12;
+        let shadow_center = Point::new(center.x + offset, center.y + offset * 1.5);
+        const RINGS: usize = 4;
+        for i in 0..RINGS {
+            let t = (i + 1) as f32 / RINGS as f32;
+            frame.fill(&Path::circle(shadow_center, radius * (1.0 + 0.1 * t)), Color::from_rgba(0.0, 0.0, 0.0, 0.12 * (1.0 - t) + 0.03));
+        }
+    }
+
+    fn draw_last_move_marker(&self, frame: &mut Frame, center: Point) {
+        let color = self.palette.last_move;
+        let stroke = Stroke::default().with_color(color).with_width(self.line_width * self.palette.marker_width);
+        let half_size = self.cell_size / 7.0 * self.palette.marker_width.sqrt();
+        match self.marker_style {
+            MarkerStyle::Cross => {
+                let cross = Path::new(|b| {
+                    b.move_to(Point::new(center.x - half_size, center.y));
+                    b.line_to(Point::new(center.x + half_size, center.y));
+                    b.move_to(Point::new(center.x, center.y - half_size));
+                    b.line_to(Point::new(center.x, center.y + half_size));
+                });
+                frame.stroke(&cross, stroke);
+            },
+            MarkerStyle::Dot => {
+                frame.fill(&Path::circle(center, half_size), color);
+            },
+            MarkerStyle::Ring => {
+                frame.stroke(&Path::circle(center, self.chess_size / 2.0 - self.line_width * 2.5), stroke);
+            },
+            MarkerStyle::Square => {
+                let top_left = Point::new(center.x - half_size, center.y - half_size);
+                frame.stroke(&Path::rectangle(top_left, Size::new(half_size * 2.0, half_size * 2.0)), stroke);
+            },
+        }
+    }
+}
+
+impl Default for BoardView {
+    fn default() -> Self {
+        Self::new(15, 45.0, 48.0, 42.0, 2.0)
+    }
+}
+
+/// Draws `image` scaled to cover a canvas of `size`, centered and cut off where it sticks out.
+fn draw_background(frame: &mut Frame, image: &Bitmap, size: Size) {
+    let scale = (size.width / image.width as f32).max(size.height / image.height as f32);
+    let left = (size.width - image.width as f32 * scale) / 2.0;
+    let top = (size.height - image.height as f32 * scale) / 2.0;
+    for (i, &color) in image.pixels.iter().enumerate() {
+        let (x, y) = ((i % image.width) as f32, (i / image.width) as f32);
+        // a little overlap keeps seams from showing between neighbouring pixels
+        let x0 = (left + x * scale).max(0.0);
+        let y0 = (top + y * scale).max(0.0);
+        let x1 = (left + (x + 1.0) * scale + 0.5).min(size.width);
+        let y1 = (top + (y + 1.0) * scale + 0.5).min(size.height);
+        if x1 > x0 && y1 > y0 {
+            frame.fill_rectangle(Point::new(x0, y0), Size::new(x1 - x0, y1 - y0), color);
+        }
+    }
+}
+
+/// Linear blend from `from` at 0.0 to `to` at 1.0.
+fn mix(from: Color, to: Color, t: f32) -> Color {
+    Color::from_rgb(
+        from.r + (to.r - from.r) * t,
+        from.g + (to.g - from.g) * t,
+        from.b + (to.b - from.b) * t)
+}
+
+/// The [`canvas::Program`] drawing a [`Board`] through its [`BoardView`].
+struct BoardCanvas<'a, Message> {
+    view: &'a BoardView,
+    board: &'a Board,
+    on_event: Box<dyn Fn(BoardEvent) -> Message + 'a>,
+}
+
+impl<Message> canvas::Program<Message> for BoardCanvas<'_, Message> {
+    /// Pixels a touchpad scrolled which did not add up to a step yet.
+    type State = f32;
+    fn update(
+        &self,
+        scrolled: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<Message>) {
+        let view = self.view;
+        let board = self.board;
+        let on_click = || {
+            match cursor.position_in(bounds) {
+                Some(pos) => {
+                    if view.context_menu.is_some() {
+                        return match view.menu_action_at(bounds.size(), pos) {
+                            Some(action) if view.menu_action_enabled(board, action) => {
+                                (event::Status::Captured, Some((self.on_event)(BoardEvent::ContextMenu(action))))
+                            },
+                            Some(_) => (event::Status::Captured, None),
+                            None => (event::Status::Captured, Some((self.on_event)(BoardEvent::CloseContextMenu))),
+                        };
+                    }
+                    match view.grid_pos(board, pos.x, pos.y).filter(|_| view.clickable) {
+                        Some(grid_pos) => {
+                            println!("Press at board {}, try to put chess at index {}", grid_pos, board.pos_to_index(grid_pos));
+                            (event::Status::Captured, Some((self.on_event)(BoardEvent::Click(board.pos_to_index(grid_pos)))))
+                        },
+                        None => (canvas::event::Status::Captured, None),
+                    }
+                },
+                None => (canvas::event::Status::Captured, None),
+            }
+        };
+
+        let on_right_click = || {
+            match cursor.position_in(bounds) {
+                Some(pos) => {
+                    let index = view.grid_pos(board, pos.x, pos.y).map(|grid_pos| board.pos_to_index(grid_pos));
+                    (event::Status::Captured, Some((self.on_event)(BoardEvent::OpenContextMenu(pos, index))))
+                },
+                None => (canvas::event::Status::Captured, None),
+            }
+        };
+
+        let on_move = || {
+            let index = cursor.position_in(bounds)
+                .filter(|_| view.context_menu.is_none() && view.clickable)
+                .and_then(|pos| view.grid_pos(board, pos.x, pos.y))
+                .map(|grid_pos| board.pos_to_index(grid_pos));
+            let message = (index != view.hover.as_ref().map(|hover| hover.index)).then(|| (self.on_event)(BoardEvent::Hover(index)));
+            (event::Status::Ignored, message)
+        };
+
+        match event {
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. } | mouse::Event::CursorLeft) => { on_move() },
+            canvas::Event::Touch(touch::Event::FingerPressed { .. }) => { on_click() },
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => { on_click() },
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => { on_right_click() },
+            canvas::Event::Mouse(mouse::Event::WheelScrolled { delta }) if cursor.is_over(bounds) => {
+                // a wheel turns in lines, a touchpad scrolls a cell's width of pixels for a step
+                let lines = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => y,
+                    mouse::ScrollDelta::Pixels { y, .. } => {
+                        *scrolled += y;
+                        let lines = (*scrolled / view.cell_size).trunc();
+                        *scrolled -= lines * view.cell_size;
+                        lines
+                    },
+                };
+                let steps = -lines.round() as i32;
+                (event::Status::Captured, (steps != 0).then(|| (self.on_event)(BoardEvent::Scrub(steps))))
+            },
+            canvas::Event::Keyboard(keyboard::Event::KeyPressed { key: keyboard::Key::Named(key @ (Named::PageUp | Named::PageDown)), .. })
+                if cursor.is_over(bounds) => {
+                let steps = if key == Named::PageUp { -1 } else { 1 };
+                (event::Status::Captured, Some((self.on_event)(BoardEvent::Scrub(steps))))
+            },
+            // other keys are left to the application's shortcuts
+            canvas::Event::Keyboard(_) => (event::Status::Ignored, None),
+            _ => (canvas::event::Status::Captured, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        _interaction: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor
+    ) -> Vec<Geometry> {
+        let view = self.view;
+        let board = self.board;
+        // println!("board draw called, already have {} chesses", board.chesses().len());
+
+        let grid = view.grid_cache.draw(renderer, bounds.size(), |frame| {
+            let bg_color = view.palette.background;
+            let grid_color = view.palette.grid;
+            frame.fill_rectangle(bounds.position(), bounds.size(), bg_color);
+            if let Some(image) = &view.background {
+                draw_background(frame, image, bounds.size());
+            }
+            for row in 0..view.cells_per_row {
+                frame.fill_rectangle(
+                    Point::new(view.padding, view.padding + row as f32 * view.cell_size),
+                    Size::new(view.grid_size, view.line_width),
+                    grid_color,
+                );
+                frame.fill_rectangle(
+                    Point::new(view.padding + row as f32 * view.cell_size, view.padding),
+                    Size::new(view.line_width, view.grid_size),
+                    grid_color,
+                );
+            }
+            if view.show_hit_regions {
+                let region_color = Color { a: 0.5, ..view.palette.hint };
+                for index in 0..view.cells_per_row * view.cells_per_row {
+                    let (col, row) = ((index % view.cells_per_row) as f32, (index / view.cells_per_row) as f32);
+                    let center = Point::new(view.padding + col * view.cell_size, view.padding + row * view.cell_size);
+                    // with `ClickSnap::Nearest` a point takes the square around it, the edge ones reach out of the grid
+                    let region = match view.hit_radius() {
+                        Some(radius) => Path::circle(center, radius),
+                        None => Path::rectangle(
+                            Point::new(center.x - view.cell_size / 2.0, center.y - view.cell_size / 2.0),
+                            Size::new(view.cell_size, view.cell_size)),
+                    };
+                    frame.stroke(&region, Stroke::default().with_color(region_color).with_width(1.0));
+                }
+            }
+        });
+
+        // while chesses are only added the cached ones stay, and a few new ones are drawn on their own
+        let stones: Vec<Chess> = board.stones().copied().collect();
+        let extends_cache = {
+            let cached = view.cached_chesses.borrow();
+            stones.len() >= cached.len()
+                && stones.len() - cached.len() <= BoardView::MAX_UNCACHED
+                && stones.iter().zip(cached.iter()).all(|(stone, cached)| stone == cached)
+        };
+        if !extends_cache {
+            view.chesses_cache.clear();
+        }
+        let chesses = view.chesses_cache.draw(renderer, bounds.size(), |frame| {
+            for c in stones.iter() {
+                view.draw_chess(frame, view.chess_center(c.pos), c.color);
+            }
+            *view.cached_chesses.borrow_mut() = stones.clone();
+        });
+        let uncached = {
+            let mut frame = Frame::new(renderer, bounds.size());
+            for c in stones.iter().skip(view.cached_chesses.borrow().len()) {
+                view.draw_chess(&mut frame, view.chess_center(c.pos), c.color);
+            }
+            frame.into_geometry()
+        };
+
+        let last_move = view.last_move_cache.draw(renderer, bounds.size(), |frame| {
+            if let Some(last_chess) = board.chesses().last() {
+                view.draw_last_move_marker(frame, view.chess_center(last_chess.pos));
+            }
+        });
+
+        let analysis = view.analysis_cache.draw(renderer, bounds.size(), |frame| {
+            for &(index, value) in view.heatmap.iter() {
+                let center = view.chess_center(board.index_to_pos(index));
+                let [bad, average, good] = view.palette.heat;
+                let color = if value < 0.5 { mix(bad, average, value * 2.0) } else { mix(average, good, value * 2.0 - 1.0) };
+                frame.fill(&Path::circle(center, view.chess_size / 2.5), Color { a: 0.55, ..color });
+            }
+
+            for threat in view.threats.iter() {
+                let color = if threat.kind == ThreatKind::Four { view.palette.forbidden } else { view.palette.threat };
+                let stroke = Stroke::default().with_color(color).with_width(view.line_width * view.palette.marker_width);
+                let centers: Vec<Point> = threat.chesses.iter().map(|&index| view.chess_center(board.index_to_pos(index))).collect();
+                for &center in centers.iter() {
+                    frame.stroke(&Path::circle(center, view.chess_size / 2.0 + view.line_width), stroke.clone());
+                }
+                for &block in threat.blocks.iter() {
+                    let to = view.chess_center(board.index_to_pos(block));
+                    let distance = |p: &Point| (p.x - to.x).powi(2) + (p.y - to.y).powi(2);
+                    if let Some(&from) = centers.iter().min_by(|a, b| distance(a).total_cmp(&distance(b))) {
+                        view.draw_arrow(frame, from, to, color);
+                    }
+                }
+            }
+
+            if let Some(index) = view.hint {
+                let center = view.chess_center(board.index_to_pos(index));
+                frame.fill(&Path::circle(center, view.chess_size / 2.0), view.palette.hint);
+            }
+        });
+
+        let marks = view.marks_cache.draw(renderer, bounds.size(), |frame| {
+            let forbidden_half_size = view.cell_size / 8.0;
+            for &index in view.forbidden.iter() {
+                let center = view.chess_center(board.index_to_pos(index));
+                let cross = Path::new(|b| {
+                    b.move_to(Point::new(center.x - forbidden_half_size, center.y - forbidden_half_size));
+                    b.line_to(Point::new(center.x + forbidden_half_size, center.y + forbidden_half_size));
+                    b.move_to(Point::new(center.x + forbidden_half_size, center.y - forbidden_half_size));
+                    b.line_to(Point::new(center.x - forbidden_half_size, center.y + forbidden_half_size));
+                });
+                frame.stroke(
+                    &cross,
+                    Stroke::default()
+                        .with_color(view.palette.forbidden)
+                        .with_width(view.line_width * view.palette.marker_width));
+            }
+
+            for &index in view.offers.iter() {
+                let center = view.chess_center(board.index_to_pos(index));
+                frame.fill(&Path::circle(center, view.chess_size / 2.0), Color { a: 0.45, ..view.palette.black });
+                frame.stroke(
+                    &Path::circle(center, view.chess_size / 2.0),
+                    Stroke::default()
+                        .with_color(view.palette.mark)
+                        .with_width(view.line_width * view.palette.marker_width));
+            }
+
+            let mark_half_size = view.cell_size / 5.0;
+            for &index in view.marks.iter() {
+                let center = view.chess_center(board.index_to_pos(index));
+                let triangle = Path::new(|b| {
+                    b.move_to(Point::new(center.x, center.y - mark_half_size));
+                    b.line_to(Point::new(center.x + mark_half_size, center.y + mark_half_size * 0.7));
+                    b.line_to(Point::new(center.x - mark_half_size, center.y + mark_half_size * 0.7));
+                    b.close();
+                });
+                frame.stroke(
+                    &triangle,
+                    Stroke::default()
+                        .with_color(view.palette.mark)
+                        .with_width(view.line_width * view.palette.marker_width));
+            }
+
+            if view.celebration.is_none() {
+                for &index in view.winning_line.iter() {
+                    let center = view.chess_center(board.index_to_pos(index));
+                    frame.stroke(
+                        &Path::circle(center, view.chess_size / 2.0 + view.line_width),
+                        Stroke::default()
+                            .with_color(view.palette.winning_line)
+                            .with_width(view.line_width * 1.5 * view.palette.marker_width));
+                }
+            }
+        });
+
+        let mut layers = vec![grid, chesses, uncached, last_move, analysis, marks];
+
+        if let Some(center) = view.hover_center(Instant::now()).filter(|_| view.context_menu.is_none()) {
+            let mut frame = Frame::new(renderer, bounds.size());
+            frame.fill(&Path::circle(center, view.chess_size / 2.0), Color { a: 0.15, ..view.palette.mark });
+            frame.stroke(
+                &Path::circle(center, view.chess_size / 2.0),
+                Stroke::default()
+                    .with_color(Color { a: 0.6, ..view.palette.mark })
+                    .with_width(view.line_width * view.palette.marker_width));
+            layers.push(frame.into_geometry());
+        }
+
+        if let Some(progress) = view.celebration {
+            let mut frame = Frame::new(renderer, bounds.size());
+            // dim quickly, hold, then fade back while the winning chesses keep pulsing
+            let dim = (progress * 4.0).min(1.0).min((1.0 - progress) * 4.0) * 0.5;
+            frame.fill_rectangle(bounds.position(), bounds.size(), Color::from_rgba8(0x00, 0x00, 0x00, dim));
+            let pulse = (progress * std::f32::consts::PI * 6.0).sin().abs();
+            for &index in view.winning_line.iter() {
+                let chess_center = view.chess_center(board.index_to_pos(index));
+                let color = board.cell(index).and_then(CellState::color).unwrap_or(ChessColor::White);
+                frame.fill(
+                    &Path::circle(chess_center, view.chess_size / 2.0 + view.line_width * (1.0 + 3.0 * pulse)),
+                    Color::from_rgba8(0xff, 0xd5, 0x4f, 0.4 + 0.5 * pulse));
+                view.draw_chess(&mut frame, chess_center, color);
+            }
+            layers.push(frame.into_geometry());
+        }
+
+        if let Some(menu_bounds) = view.menu_bounds(bounds.size()) {
+            let mut frame = Frame::new(renderer, bounds.size());
+            let hovered = cursor.position_in(bounds).and_then(|pos| view.menu_action_at(bounds.size(), pos));
+            frame.fill_rectangle(menu_bounds.position(), menu_bounds.size(), Color::from_rgb8(0xff, 0xff, 0xff));
+            frame.stroke(
+                &Path::rectangle(menu_bounds.position(), menu_bounds.size()),
+                Stroke::default()
+                    .with_color(Color::from_rgb8(0x60, 0x64, 0x6b))
+                    .with_width(1.0));
+            for (i, action) in MenuAction::ALL.iter().enumerate() {
+                let item_pos = Point::new(menu_bounds.x, menu_bounds.y + i as f32 * BoardView::MENU_ITEM_HEIGHT);
+                let enabled = view.menu_action_enabled(board, *action);
+                if enabled && hovered == Some(*action) {
+                    frame.fill_rectangle(
+                        item_pos,
+                        Size::new(BoardView::MENU_ITEM_WIDTH, BoardView::MENU_ITEM_HEIGHT),
+                        Color::from_rgb8(0xd6, 0xe4, 0xf0));
+                }
+                frame.fill_text(Text {
+                    content: view.menu_action_label(*action).to_string(),
+                    position: Point::new(item_pos.x + 10.0, item_pos.y + BoardView::MENU_ITEM_HEIGHT / 2.0),
+                    color: if enabled { Color::from_rgb8(0x20, 0x20, 0x20) } else { Color::from_rgb8(0xa0, 0xa0, 0xa0) },
+                    size: 16.0.into(),
+                    horizontal_alignment: Horizontal::Left,
+                    vertical_alignment: Vertical::Center,
+                    ..Text::default()
+                });
+            }
+            layers.push(frame.into_geometry());
+        }
+
+        layers
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &Self::State,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> Interaction {
+        let view = self.view;
+        match cursor.position_in(bounds) {
+            Some(pos) => {
+                if view.context_menu.is_some() {
+                    return match view.menu_action_at(bounds.size(), pos) {
+                        Some(action) if view.menu_action_enabled(self.board, action) => Interaction::Pointer,
+                        _ => Interaction::default(),
+                    };
+                }
+                match view.grid_pos(self.board, pos.x, pos.y) {
+                    Some(_) => Interaction::Pointer,
+                    None => Interaction::default(),
+                }
+            },
+            None => Interaction::default(),
+        }
+    }
+}
@@ -0,0 +1,303 @@
+//! User preferences edited on the settings screen, stored as `key = value` lines under the
+//! `config` storage key so they can also be edited by hand.
+
+use std::fmt;
+use std::str::FromStr;
+
+use gomoku_rs::board_view::{ClickSnap, MarkerStyle, StoneSkin};
+use gomoku_rs::engine::Style;
+use gomoku_rs::openings::OPENINGS;
+
+use crate::storage;
+
+/// Size of the whole window contents in percent of the normal size.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct UiScale(pub u16);
+
+impl UiScale {
+    pub const ALL: [UiScale; 7] = [UiScale(50), UiScale(75), UiScale(100), UiScale(125), UiScale(150), UiScale(175), UiScale(200)];
+
+    pub fn factor(self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+}
+
+impl Default for UiScale {
+    fn default() -> Self {
+        Self(100)
+    }
+}
+
+impl fmt::Display for UiScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
+
+impl FromStr for UiScale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().trim_end_matches('%').parse() {
+            Ok(percent @ 25..=400) => Ok(UiScale(percent)),
+            _ => Err(format!("scale must be between 25% and 400%, got \"{}\"", s)),
+        }
+    }
+}
+
+/// How long each move is shown in an exported animation.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct AnimationDelay(pub u32);
+
+impl AnimationDelay {
+    pub const ALL: [AnimationDelay; 5] = [AnimationDelay(300), AnimationDelay(500), AnimationDelay(700), AnimationDelay(1000), AnimationDelay(1500)];
+}
+
+impl Default for AnimationDelay {
+    fn default() -> Self {
+        Self(700)
+    }
+}
+
+impl fmt::Display for AnimationDelay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ms", self.0)
+    }
+}
+
+impl FromStr for AnimationDelay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().trim_end_matches("ms").trim().parse() {
+            Ok(millis @ 10..=60_000) => Ok(AnimationDelay(millis)),
+            _ => Err(format!("delay must be between 10 and 60000 ms, got \"{}\"", s)),
+        }
+    }
+}
+
+/// How long the engine may think about each of its moves.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct EngineTime(pub u32);
+
+impl EngineTime {
+    /// Slider range in milliseconds.
+    pub const MIN: u32 = 200;
+    pub const MAX: u32 = 10_000;
+}
+
+impl Default for EngineTime {
+    fn default() -> Self {
+        Self(2000)
+    }
+}
+
+impl fmt::Display for EngineTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ms", self.0)
+    }
+}
+
+impl FromStr for EngineTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().trim_end_matches("ms").trim().parse() {
+            Ok(millis) if (Self::MIN..=Self::MAX).contains(&millis) => Ok(EngineTime(millis)),
+            _ => Err(format!("engine time must be between {} and {} ms, got \"{}\"", Self::MIN, Self::MAX, s)),
+        }
+    }
+}
+
+/// How long the players may think about each move, the low time sound warns them before the
+/// time is up. No limit when 0.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct MoveTime(pub u32);
+
+impl MoveTime {
+    pub const ALL: [MoveTime; 5] = [MoveTime(0), MoveTime(30), MoveTime(60), MoveTime(120), MoveTime(300)];
+}
+
+impl fmt::Display for MoveTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            0 => write!(f, "No limit"),
+            seconds => write!(f, "{} s", seconds),
+        }
+    }
+}
+
+impl FromStr for MoveTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("no limit") {
+            return Ok(MoveTime(0));
+        }
+        match s.trim().trim_end_matches('s').trim().parse() {
+            Ok(seconds @ 0..=3600) => Ok(MoveTime(seconds)),
+            _ => Err(format!("time per move must be between 0 and 3600 s, got \"{}\"", s)),
+        }
+    }
+}
+
+/// What a quick start puts on the board before the first move.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum StartPosition {
+    #[default]
+    Empty,
+    /// One of the 26 openings drawn anew for each game.
+    RandomOpening,
+    /// The opening at this index of [`OPENINGS`].
+    Opening(usize),
+}
+
+impl StartPosition {
+    pub const ALL: [StartPosition; 2 + OPENINGS.len()] = {
+        let mut all = [StartPosition::Empty; 2 + OPENINGS.len()];
+        all[1] = StartPosition::RandomOpening;
+        let mut i = 0;
+        while i < OPENINGS.len() {
+            all[i + 2] = StartPosition::Opening(i);
+            i += 1;
+        }
+        all
+    };
+}
+
+impl fmt::Display for StartPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartPosition::Empty => write!(f, "Empty board"),
+            StartPosition::RandomOpening => write!(f, "Random opening"),
+            StartPosition::Opening(i) => write!(f, "{}", OPENINGS.get(*i).map_or("?", |opening| opening.name)),
+        }
+    }
+}
+
+impl FromStr for StartPosition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        StartPosition::ALL.iter()
+            .find(|position| position.to_string().eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("unknown start position \"{}\"", s))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub marker_style: MarkerStyle,
+    pub high_contrast: bool,
+    /// Highlight the threats of the side which just moved, as a help for beginners.
+    pub show_threats: bool,
+    /// Turn the board half around whenever white is to move, for two players facing each other.
+    pub auto_rotate: bool,
+    /// Flash the taskbar entry when the opponent moved while the window was in the background.
+    pub flash_taskbar: bool,
+    /// Append every finished game as a line of JSON to the `games.jsonl` file in the data directory.
+    pub json_game_log: bool,
+    /// Image file drawn under the board grid, none when empty.
+    pub background_image: String,
+    pub stone_skin: StoneSkin,
+    /// Folder with `black.png` and `white.png` for [`StoneSkin::Pictures`].
+    pub stone_pictures: String,
+    pub ui_scale: UiScale,
+    pub animation_delay: AnimationDelay,
+    pub click_snap: ClickSnap,
+    /// Outline where a click lands on each point, for developers tuning `click_snap`. Toggled with F12.
+    pub show_hit_regions: bool,
+    pub engine_time: EngineTime,
+    /// Address of the relay server for correspondence games, like "example.org:7070".
+    pub relay_server: String,
+    /// The name the player logs in to the relay server with.
+    pub relay_player: String,
+    pub engine_style: Style,
+    pub move_time: MoveTime,
+    /// Let a quick start draw who plays black, against the engine or between named players.
+    pub random_colors: bool,
+    pub start_position: StartPosition,
+}
+
+impl Config {
+    const KEY: &'static str = "config";
+
+    /// Reads the saved config, keeping defaults for missing or broken entries.
+    pub fn load() -> Self {
+        let mut config = Self::default();
+        for line in storage::load(Self::KEY).unwrap_or_default().lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let result = match line.split_once('=') {
+                Some((key, value)) => config.set(key.trim(), value.trim()),
+                None => Err(String::from("expected key = value")),
+            };
+            if let Err(e) = result {
+                println!("Ignore config line \"{}\": {}", line, e);
+            }
+        }
+        config
+    }
+
+    /// Replaces the saved config as a whole. In a sync folder the machine saving last wins, the
+    /// settings are not merged.
+    pub fn save(&self) {
+        let lines = [
+            format!("marker_style = {}", self.marker_style),
+            format!("high_contrast = {}", self.high_contrast),
+            format!("show_threats = {}", self.show_threats),
+            format!("auto_rotate = {}", self.auto_rotate),
+            format!("flash_taskbar = {}", self.flash_taskbar),
+            format!("json_game_log = {}", self.json_game_log),
+            format!("background_image = {}", self.background_image),
+            format!("stone_skin = {}", self.stone_skin),
+            format!("stone_pictures = {}", self.stone_pictures),
+            format!("ui_scale = {}", self.ui_scale),
+            format!("animation_delay = {}", self.animation_delay),
+            format!("click_snap = {}", self.click_snap),
+            format!("show_hit_regions = {}", self.show_hit_regions),
+            format!("engine_time = {}", self.engine_time),
+            format!("relay_server = {}", self.relay_server),
+            format!("relay_player = {}", self.relay_player),
+            format!("engine_style = {}", self.engine_style),
+            format!("move_time = {}", self.move_time),
+            format!("random_colors = {}", self.random_colors),
+            format!("start_position = {}", self.start_position),
+        ];
+        storage::save(Self::KEY, &(lines.join("\n") + "\n"));
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "marker_style" => { self.marker_style = value.parse()?; },
+            "high_contrast" => { self.high_contrast = parse_bool(value)?; },
+            "show_threats" => { self.show_threats = parse_bool(value)?; },
+            "auto_rotate" => { self.auto_rotate = parse_bool(value)?; },
+            "flash_taskbar" => { self.flash_taskbar = parse_bool(value)?; },
+            "json_game_log" => { self.json_game_log = parse_bool(value)?; },
+            "background_image" => { self.background_image = value.to_string(); },
+            "stone_skin" => { self.stone_skin = value.parse()?; },
+            "stone_pictures" => { self.stone_pictures = value.to_string(); },
+            "ui_scale" => { self.ui_scale = value.parse()?; },
+            "animation_delay" => { self.animation_delay = value.parse()?; },
+            "click_snap" => { self.click_snap = value.parse()?; },
+            "show_hit_regions" => { self.show_hit_regions = parse_bool(value)?; },
+            "engine_time" => { self.engine_time = value.parse()?; },
+            "relay_server" => { self.relay_server = value.to_string(); },
+            "relay_player" => { self.relay_player = value.to_string(); },
+            "engine_style" => { self.engine_style = value.parse()?; },
+            "move_time" => { self.move_time = value.parse()?; },
+            "random_colors" => { self.random_colors = parse_bool(value)?; },
+            "start_position" => { self.start_position = value.parse()?; },
+            _ => { return Err(format!("unknown key \"{}\"", key)); }
+        }
+        Ok(())
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    value.parse().map_err(|_| format!("expected true or false, got \"{}\"", value))
+}
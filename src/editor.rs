@@ -0,0 +1,112 @@
+//! Composing a position chess by chess, to start a game or an analysis from it.
+
+use std::fmt;
+use std::str::FromStr;
+
+use gomoku_rs::board::{Board, ChessColor};
+use gomoku_rs::game::Game;
+use gomoku_rs::rules::RuleSet;
+
+/// What clicking a point does in the editor.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum EditTool {
+    #[default]
+    Black,
+    White,
+    Erase,
+}
+
+impl EditTool {
+    pub const ALL: [EditTool; 3] = [EditTool::Black, EditTool::White, EditTool::Erase];
+}
+
+impl fmt::Display for EditTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EditTool::Black => "Black",
+            EditTool::White => "White",
+            EditTool::Erase => "Erase",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for EditTool {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EditTool::ALL.iter()
+            .find(|tool| tool.to_string().eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("unknown edit tool \"{}\"", s))
+    }
+}
+
+pub struct Editor {
+    pub tool: EditTool,
+    chesses: Vec<(usize, ChessColor)>,
+    next: ChessColor,
+    board: Board,
+}
+
+impl Editor {
+    /// Starts editing the chesses on `board`.
+    pub fn new(board: &Board) -> Self {
+        let chesses = board.stones().map(|chess| (board.pos_to_index(chess.pos), chess.color)).collect();
+        let next = if board.next_is_black() { ChessColor::Black } else { ChessColor::White };
+        Self { tool: EditTool::default(), chesses, next, board: board.clone() }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Puts a chess of the tool's color at `index`, replacing what was there, or erases it.
+    pub fn click(&mut self, index: usize) {
+        if !self.board.valid_index(index) {
+            return;
+        }
+        self.chesses.retain(|&(i, _)| i != index);
+        match self.tool {
+            EditTool::Black => self.chesses.push((index, ChessColor::Black)),
+            EditTool::White => self.chesses.push((index, ChessColor::White)),
+            EditTool::Erase => {},
+        }
+        self.rebuild();
+    }
+
+    pub fn clear(&mut self) {
+        self.chesses.clear();
+        self.rebuild();
+    }
+
+    pub fn next(&self) -> ChessColor {
+        self.next
+    }
+
+    pub fn set_next(&mut self, color: ChessColor) {
+        self.next = color;
+        self.board.set_next(color);
+    }
+
+    /// A game with `rule_set` starting from the edited position.
+    pub fn to_game(&self, rule_set: RuleSet) -> Game {
+        Game::new(self.board.clone(), rule_set)
+    }
+
+    fn rebuild(&mut self) {
+        self.board = position(self.board.cells_per_row(), &self.chesses, self.next);
+    }
+}
+
+/// A board of `size` with `chesses` put in order and `next` to move.
+pub fn position(size: usize, chesses: &[(usize, ChessColor)], next: ChessColor) -> Board {
+    let mut board = Board::new(size);
+    for &(index, color) in chesses {
+        if let Err(e) = board.try_put_chess(color, index) {
+            println!("Skip the chess at {} of the composed position: {}", index, e);
+        }
+    }
+    board.set_next(next);
+    board
+}
@@ -0,0 +1,447 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use iced::time::Instant;
+
+use crate::board::{Board, CellState, ChessColor, Pos};
+use crate::game::{Game, GameState};
+use crate::rules::RuleSet;
+use crate::symmetry::canonical_key;
+
+/// Score of a won position, reduced by the number of moves it takes so quicker wins are preferred.
+pub const WIN_SCORE: i32 = 1_000_000;
+
+/// Most positions [`Engine`] remembers the best move of, new positions are not remembered once
+/// it holds this many.
+pub const BEST_MOVES_CAPACITY: usize = 1 << 18;
+
+/// How far ahead an engine may see itself and still take a draw.
+pub const DRAW_MARGIN: i32 = 100;
+
+/// How an engine weighs its own lines against the opponent's, so games against it do not all
+/// feel the same.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Style {
+    #[default]
+    Balanced,
+    /// Prefers making fours and threes of its own.
+    Aggressive,
+    /// Prefers blocking the opponent's lines.
+    Defensive,
+}
+
+impl Style {
+    pub const ALL: [Style; 3] = [Style::Balanced, Style::Aggressive, Style::Defensive];
+
+    /// Weights in percent of the engine's own lines and of the opponent's lines.
+    fn weights(self) -> (i32, i32) {
+        match self {
+            Style::Balanced => (100, 100),
+            Style::Aggressive => (150, 75),
+            Style::Defensive => (75, 150),
+        }
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Style::Balanced => "Balanced",
+            Style::Aggressive => "Aggressive",
+            Style::Defensive => "Defensive",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Style::ALL.iter()
+            .find(|style| style.to_string().eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("unknown engine style \"{}\"", s))
+    }
+}
+
+/// What [`Engine::search`] found.
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub best_move: Option<usize>,
+    /// Score of `best_move` for the side to move.
+    pub score: i32,
+    /// Deepest fully searched depth.
+    pub depth: u32,
+    pub nodes: u64,
+    /// The moves both sides are expected to play, starting with `best_move`.
+    pub pv: Vec<usize>,
+}
+
+/// An iterative deepening search which can be advanced one depth at a time with
+/// [`Engine::deepen`], for showing its progress while it runs.
+pub struct Search {
+    game: Game,
+    moves: Vec<usize>,
+    result: SearchResult,
+    finished: bool,
+}
+
+impl Search {
+    /// What the deepest finished iteration found.
+    pub fn result(&self) -> &SearchResult {
+        &self.result
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Alpha-beta search over the moves near existing chesses.
+pub struct Engine {
+    max_depth: u32,
+    width: usize,
+    nodes: u64,
+    style: Style,
+    /// The side the engine searches for, which its style is about.
+    color: ChessColor,
+    /// Best move found in each position so far, searched first when the position comes up
+    /// again. Keyed by [`canonical_key`] so rotated and mirrored positions share an entry, the
+    /// move is stored as it is in the canonical image. Kept from one search to the next until
+    /// [`Engine::forget`] is called, or a game with other rules or another style is searched.
+    best_moves: HashMap<u64, usize>,
+    /// Rules of the game the best moves were found in.
+    rule_set: RuleSet,
+    /// When searches finish, see [`Engine::set_deadline`].
+    deadline: Option<Instant>,
+    /// The deadline of the iteration being searched, none for the first one.
+    give_up_at: Option<Instant>,
+    /// Set from elsewhere to finish searches, see [`Engine::set_stop`].
+    stop: Arc<AtomicBool>,
+    /// Whether the iteration being searched was given up.
+    aborted: bool,
+}
+
+impl Engine {
+    /// Nodes searched between two looks at the clock.
+    const CLOCK_NODES: u64 = 1024;
+
+    /// Creates an engine searching `max_depth` plies ahead, at least one.
+    pub fn new(max_depth: u32) -> Self {
+        Self {
+            max_depth: max_depth.max(1),
+            width: 12,
+            nodes: 0,
+            style: Style::default(),
+            color: ChessColor::Black,
+            best_moves: HashMap::new(),
+            rule_set: RuleSet::default(),
+            deadline: None,
+            give_up_at: None,
+            stop: Arc::new(AtomicBool::new(false)),
+            aborted: false,
+        }
+    }
+
+    /// Makes searches finish at `deadline` with what the deepest iteration through by then found.
+    /// The first iteration is always searched to the end, so there is a move to play.
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+
+    /// Makes searches finish as soon as `stop` is set, from another thread for example. What
+    /// they found then is only complete when an iteration was through.
+    pub fn set_stop(&mut self, stop: Arc<AtomicBool>) {
+        self.stop = stop;
+    }
+
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    /// Plays `style` from the next search on, the best moves found in the old style are forgotten.
+    pub fn set_style(&mut self, style: Style) {
+        if style != self.style {
+            self.forget();
+        }
+        self.style = style;
+    }
+
+    /// Forgets the best moves found so far, for searching another game.
+    pub fn forget(&mut self) {
+        self.best_moves.clear();
+    }
+
+    /// Number of positions whose best move is remembered, at most [`BEST_MOVES_CAPACITY`].
+    pub fn best_moves_len(&self) -> usize {
+        self.best_moves.len()
+    }
+
+    pub fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
+    /// Finds the best move for the side to move in `game`, `None` when the game is over.
+    pub fn search(&mut self, game: &Game) -> SearchResult {
+        let mut search = self.start(game);
+        while self.deepen(&mut search) {}
+        search.result
+    }
+
+    /// Prepares searching `game`, nothing is searched until [`Engine::deepen`] is called.
+    pub fn start(&mut self, game: &Game) -> Search {
+        self.prepare(game);
+        let result = SearchResult { best_move: None, score: 0, depth: 0, nodes: 0, pv: vec![] };
+        let moves = if game.state().is_over() { vec![] } else { self.legal_moves(game) };
+        Search { game: game.clone(), finished: moves.is_empty(), moves, result }
+    }
+
+    /// Searches one ply deeper than the last iteration, returns whether there is more to search.
+    pub fn deepen(&mut self, search: &mut Search) -> bool {
+        if search.finished {
+            return false;
+        }
+        let depth = search.result.depth + 1;
+        // search the best move of the last iteration first, it is most likely to stay the best
+        if let Some(best) = search.result.best_move {
+            if let Some(i) = search.moves.iter().position(|&m| m == best) {
+                search.moves[..=i].rotate_right(1);
+            }
+        }
+        let mut alpha = -WIN_SCORE - 1;
+        let mut pv = vec![];
+        self.give_up_at = self.deadline.filter(|_| search.result.best_move.is_some());
+        if self.give_up_at.is_some_and(|deadline| Instant::now() >= deadline) {
+            search.finished = true;
+            return false;
+        }
+        for &index in search.moves.iter() {
+            if search.game.put_chess(index).is_err() {
+                continue;
+            }
+            let mut line = vec![];
+            let score = -self.negamax(&mut search.game, depth - 1, 1, -WIN_SCORE - 1, -alpha, &mut line);
+            search.game.undo();
+            if self.aborted {
+                // the iteration given up is dropped, the one before stays the result
+                search.finished = true;
+                return false;
+            }
+            if score > alpha {
+                alpha = score;
+                pv = std::iter::once(index).chain(line).collect();
+            }
+        }
+        search.result = SearchResult { best_move: pv.first().copied(), score: alpha, depth, nodes: self.nodes, pv };
+        search.finished = depth >= self.max_depth || alpha.abs() >= WIN_SCORE - self.max_depth as i32;
+        !search.finished
+    }
+
+    /// Whether the engine, playing the side which is not to move, takes a draw the side to move
+    /// offers in `game`. It declines when its search sees it more than [`DRAW_MARGIN`] ahead.
+    pub fn accepts_draw(&mut self, game: &Game) -> bool {
+        !game.state().is_over() && -self.search(game).score <= DRAW_MARGIN
+    }
+
+    /// Scores every move worth considering for the side to move by searching `max_depth` plies
+    /// after it, for showing how good each point is rather than just picking the best one.
+    pub fn score_moves(&mut self, game: &Game) -> Vec<(usize, i32)> {
+        self.prepare(game);
+        if game.state().is_over() {
+            return vec![];
+        }
+        let mut game = game.clone();
+        let mut scores = Vec::new();
+        for index in self.ordered_moves(&game, usize::MAX) {
+            if game.put_chess(index).is_err() {
+                continue;
+            }
+            let score = -self.negamax(&mut game, self.max_depth - 1, 1, -WIN_SCORE - 1, WIN_SCORE + 1, &mut vec![]);
+            game.undo();
+            scores.push((index, score));
+        }
+        scores
+    }
+
+    /// Resets the counters for searching `game`, forgetting the best moves found under other rules.
+    fn prepare(&mut self, game: &Game) {
+        self.nodes = 0;
+        self.give_up_at = None;
+        self.aborted = false;
+        if game.rule_set() != self.rule_set {
+            self.forget();
+            self.rule_set = game.rule_set();
+        }
+        self.color = if game.state() == GameState::Wait(ChessColor::White) { ChessColor::White } else { ChessColor::Black };
+    }
+
+    /// Whether to give up the iteration being searched. The clock is read every
+    /// [`Engine::CLOCK_NODES`] nodes only, reading it takes longer than searching a node.
+    fn should_give_up(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+            || (self.nodes.is_multiple_of(Self::CLOCK_NODES) && self.give_up_at.is_some_and(|deadline| Instant::now() >= deadline))
+    }
+
+    /// Score of `game` for the side to move, with the expected moves from here written to `pv`.
+    /// Meaningless once the search was given up.
+    fn negamax(&mut self, game: &mut Game, depth: u32, ply: u32, mut alpha: i32, beta: i32, pv: &mut Vec<usize>) -> i32 {
+        self.nodes += 1;
+        if self.aborted || self.should_give_up() {
+            self.aborted = true;
+            return 0;
+        }
+        match game.state() {
+            // the previous move won, so the side to move has lost
+            GameState::Win(_) => return -(WIN_SCORE - ply as i32),
+            GameState::Draw => return 0,
+            _ => (),
+        }
+        if depth == 0 {
+            return self.evaluate(game);
+        }
+        let (key, symmetry) = canonical_key(game.board());
+        let mut moves = self.legal_moves(game);
+        if let Some(&stored) = self.best_moves.get(&key) {
+            let best_move = symmetry.inverse().apply_index(game.board(), stored);
+            if let Some(i) = moves.iter().position(|&m| m == best_move) {
+                moves[..=i].rotate_right(1);
+            }
+        }
+        let mut best = -WIN_SCORE - 1;
+        let mut best_move = None;
+        for index in moves {
+            if game.put_chess(index).is_err() {
+                continue;
+            }
+            let mut line = vec![];
+            let score = -self.negamax(game, depth - 1, ply + 1, -beta, -alpha, &mut line);
+            game.undo();
+            if self.aborted {
+                // nothing found after giving up is remembered
+                return 0;
+            }
+            if score > best {
+                best = score;
+                best_move = Some(index);
+            }
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(index);
+                pv.append(&mut line);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        if let Some(index) = best_move {
+            if self.best_moves.len() < BEST_MOVES_CAPACITY || self.best_moves.contains_key(&key) {
+                self.best_moves.insert(key, symmetry.apply_index(game.board(), index));
+            }
+        }
+        best
+    }
+
+    /// Static score of `game` for the side to move, with the engine's own lines and the
+    /// opponent's weighted by its style.
+    fn evaluate(&self, game: &Game) -> i32 {
+        let (black, white) = line_scores(game.board());
+        let (own, other) = if self.color == ChessColor::Black { (black, white) } else { (white, black) };
+        let (attack, defense) = self.style.weights();
+        let score = (own * attack - other * defense) / 100;
+        let engine_to_move = (game.state() == GameState::Wait(ChessColor::Black)) == (self.color == ChessColor::Black);
+        if engine_to_move { score } else { -score }
+    }
+
+    /// The most promising legal moves for the side to move, best first.
+    pub fn legal_moves(&self, game: &Game) -> Vec<usize> {
+        self.ordered_moves(game, self.width)
+    }
+
+    /// At most `limit` legal moves near existing chesses, best first.
+    fn ordered_moves(&self, game: &Game, limit: usize) -> Vec<usize> {
+        let board = game.board();
+        if !board.has_chess() {
+            let center = board.cells_per_row() / 2;
+            return vec![board.pos_to_index(Pos::new(center, center))];
+        }
+        let is_black = game.state() == GameState::Wait(ChessColor::Black);
+        let (own, other) = if is_black { (CellState::Black, CellState::White) } else { (CellState::White, CellState::Black) };
+        let mut moves: Vec<(u32, usize)> = candidates(board).into_iter()
+            .filter(|&index| game.rules().is_legal(board, index).is_ok())
+            .map(|index| (point_score(board, index, own) * 2 + point_score(board, index, other), index))
+            .collect();
+        moves.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        moves.into_iter().take(limit).map(|(_, index)| index).collect()
+    }
+}
+
+/// Empty cells at most two steps away from any chess.
+fn candidates(board: &Board) -> Vec<usize> {
+    let size = board.cells_per_row() as i32;
+    (0..board.cells().len()).filter(|&index| {
+        board.cell(index) == Some(CellState::Empty) && {
+            let pos = board.index_to_pos(index);
+            (-2..=2).any(|dy: i32| (-2..=2).any(|dx: i32| {
+                let col = pos.x as i32 + dx;
+                let row = pos.y as i32 + dy;
+                col >= 0 && row >= 0 && col < size && row < size
+                    && board.cell(board.pos_to_index(Pos::new(col as usize, row as usize))) != Some(CellState::Empty)
+            }))
+        }
+    }).collect()
+}
+
+/// Rough value of putting a chess of `state` at `index`, based on the lines it would extend.
+pub fn point_score(board: &Board, index: usize, state: CellState) -> u32 {
+    let pos = board.index_to_pos(index);
+    Board::DIRECTIONS.iter().map(|&(dx, dy)| {
+        let len = board.count_direction(pos, dx, dy, state) + board.count_direction(pos, -dx, -dy, state);
+        10u32.pow(len.min(4) as u32) - 1
+    }).sum()
+}
+
+/// Static score of `board` for the side to move, summing up every window of five cells
+/// which holds chesses of only one color and the chesses each side captured.
+pub fn evaluate(board: &Board, black_to_move: bool) -> i32 {
+    let (black, white) = line_scores(board);
+    if black_to_move { black - white } else { white - black }
+}
+
+/// Scores of black and white for [`evaluate`].
+fn line_scores(board: &Board) -> (i32, i32) {
+    const WEIGHTS: [i32; 6] = [0, 1, 10, 100, 1000, 100_000];
+    const CAPTURE_WEIGHT: i32 = 500;
+    let size = board.cells_per_row() as i32;
+    let mut black = board.captured_by(ChessColor::Black) as i32 * CAPTURE_WEIGHT;
+    let mut white = board.captured_by(ChessColor::White) as i32 * CAPTURE_WEIGHT;
+    for y in 0..size {
+        for x in 0..size {
+            for &(dx, dy) in Board::DIRECTIONS.iter() {
+                let (end_x, end_y) = (x + dx * 4, y + dy * 4);
+                if end_x < 0 || end_y < 0 || end_x >= size || end_y >= size {
+                    continue;
+                }
+                let (mut black_count, mut white_count) = (0, 0);
+                for step in 0..5 {
+                    let index = board.pos_to_index(Pos::new((x + dx * step) as usize, (y + dy * step) as usize));
+                    match board.cell(index) {
+                        Some(CellState::Black) => black_count += 1,
+                        Some(CellState::White) => white_count += 1,
+                        _ => (),
+                    }
+                }
+                if white_count == 0 {
+                    black += WEIGHTS[black_count];
+                } else if black_count == 0 {
+                    white += WEIGHTS[white_count];
+                }
+            }
+        }
+    }
+    (black, white)
+}
@@ -0,0 +1,389 @@
+//! Records every [`Message`] handled by the game with the time it arrived, and reads such a log
+//! back so a session can be replayed exactly with `--replay <file>`.
+//!
+//! A log starts with the session it began from, all its tabs written the way they are saved
+//! between runs, then an `events` line followed by one `<milliseconds> <message>` line per
+//! message. Logs written before tabs start with just the four lines of one saved game instead.
+
+use std::collections::VecDeque;
+#[cfg(feature = "native")]
+use std::io::Write;
+
+use iced::Point;
+use iced::time::{Duration, Instant};
+
+use gomoku_rs::board::ChessColor;
+use gomoku_rs::board_view::{ClickSnap, MarkerStyle, MenuAction, StoneSkin};
+use gomoku_rs::engine::Style;
+use gomoku_rs::opening_rule::OpeningRule;
+use gomoku_rs::openings::{Opening, OPENINGS};
+use gomoku_rs::rules::RuleSet;
+
+use crate::Message;
+use crate::config::{AnimationDelay, MoveTime, StartPosition, UiScale};
+use crate::editor::EditTool;
+use crate::opponent::EngineSide;
+use crate::scoreboard::ScoreColumn;
+use crate::session::{SavedTab, Session};
+
+pub struct EventLog {
+    start: Instant,
+    #[cfg(feature = "native")]
+    file: Option<std::fs::File>,
+}
+
+impl EventLog {
+    #[cfg(feature = "native")]
+    const FILE_NAME: &'static str = "events.log";
+
+    /// Starts a new log in the data directory, `session` is what the run begins with.
+    #[cfg(feature = "native")]
+    pub fn create(session: &Session) -> Self {
+        let header = format!("{}{}\n", session.text(), Replay::EVENTS_LINE);
+        let file = crate::storage::path(Self::FILE_NAME).and_then(|path| {
+            let file = path.parent().map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::File::create(&path))
+                .and_then(|mut file| file.write_all(header.as_bytes()).map(|_| file));
+            match file {
+                Ok(file) => {
+                    println!("Logging events to {}", path.display());
+                    Some(file)
+                },
+                Err(e) => {
+                    println!("Cannot log events to {}: {}", path.display(), e);
+                    None
+                },
+            }
+        });
+        Self { start: Instant::now(), file }
+    }
+
+    #[cfg(not(feature = "native"))]
+    pub fn create(_session: &Session) -> Self {
+        Self { start: Instant::now() }
+    }
+
+    pub fn record(&mut self, message: &Message) {
+        let Some(line) = to_log(message) else { return; };
+        let millis = self.start.elapsed().as_millis();
+        #[cfg(feature = "native")]
+        if let Some(file) = &mut self.file {
+            if let Err(e) = writeln!(file, "{} {}", millis, line) {
+                println!("Cannot log event, stop logging: {}", e);
+                self.file = None;
+            }
+        }
+        #[cfg(not(feature = "native"))]
+        let _ = (millis, line);
+    }
+}
+
+/// Messages of a recorded session waiting to be handled again.
+pub struct Replay {
+    /// The tabs and layout the session began with.
+    pub session: Session,
+    start: Instant,
+    events: VecDeque<(Duration, Message)>,
+}
+
+impl Replay {
+    /// Ends the session at the start of a log.
+    const EVENTS_LINE: &'static str = "events";
+    /// Lines of the saved game at the start of a log written before tabs.
+    const OLD_HEADER_LINES: usize = 4;
+
+    pub fn parse(log: &str) -> Result<Self, String> {
+        let lines: Vec<&str> = log.lines().collect();
+        let (session, first_event) = match lines.iter().position(|&line| line == Self::EVENTS_LINE) {
+            Some(end) => (Session::parse(&lines[..end].iter().map(|line| format!("{}\n", line)).collect::<String>()), end + 1),
+            None => {
+                let end = Self::OLD_HEADER_LINES.min(lines.len());
+                let game = lines[..end].iter().map(|line| format!("{}\n", line)).collect();
+                (Session { tabs: vec![SavedTab { game, ..SavedTab::default() }], ..Session::default() }, end)
+            },
+        };
+        let mut events = VecDeque::new();
+        for (i, line) in lines[first_event..].iter().enumerate() {
+            let number = i + first_event + 1;
+            let (millis, message) = line.split_once(' ').ok_or_else(|| format!("line {} has no time", number))?;
+            let millis: u64 = millis.parse().map_err(|_| format!("line {} has a bad time \"{}\"", number, millis))?;
+            let message = from_log(message).ok_or_else(|| format!("line {} has a bad message \"{}\"", number, message))?;
+            events.push_back((Duration::from_millis(millis), message));
+        }
+        Ok(Self { session, start: Instant::now(), events })
+    }
+
+    /// Starts the clock, messages are due relative to this call.
+    pub fn start(&mut self) {
+        self.start = Instant::now();
+    }
+
+    /// Takes the next message whose time has come at `now`.
+    pub fn next_due(&mut self, now: Instant) -> Option<Message> {
+        let elapsed = now.saturating_duration_since(self.start);
+        match self.events.front() {
+            Some(&(time, _)) if time <= elapsed => self.events.pop_front().map(|(_, message)| message),
+            _ => None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+fn menu_action_name(action: MenuAction) -> &'static str {
+    match action {
+        MenuAction::Undo => "Undo",
+        MenuAction::Hint => "Hint",
+        MenuAction::CopyPosition => "CopyPosition",
+        MenuAction::CopyTranscript => "CopyTranscript",
+        MenuAction::MarkPoint => "MarkPoint",
+        MenuAction::PasteGame => "PasteGame",
+        MenuAction::ExportAnimation => "ExportAnimation",
+        MenuAction::Rotate => "Rotate",
+        MenuAction::Mirror => "Mirror",
+    }
+}
+
+/// One line describing `message`, `None` for messages which only drive animations.
+fn to_log(message: &Message) -> Option<String> {
+    let line = match message {
+        Message::ClickBoard(index) => format!("ClickBoard {}", index),
+        Message::EngineMove(index) => format!("EngineMove {}", index),
+        Message::ToTab(id, message) => format!("ToTab {} {}", id, to_log(message)?),
+        Message::OpenContextMenu(position, index) => {
+            format!("OpenContextMenu {} {} {}", position.x, position.y, index.map_or(String::from("-"), |i| i.to_string()))
+        },
+        Message::CloseContextMenu => String::from("CloseContextMenu"),
+        Message::ContextMenu(action) => format!("ContextMenu {}", menu_action_name(*action)),
+        Message::ToggleMute(muted) => format!("ToggleMute {}", muted),
+        Message::SelectRules(rule_set) => format!("SelectRules {}", rule_set),
+        Message::SelectOpeningRule(rule) => format!("SelectOpeningRule {}", rule),
+        Message::AnswerSwap(swap) => format!("AnswerSwap {}", swap),
+        Message::DeclareOffers(count) => format!("DeclareOffers {}", count),
+        Message::ToggleSettings => String::from("ToggleSettings"),
+        Message::SelectMarkerStyle(style) => format!("SelectMarkerStyle {}", style),
+        Message::ToggleHighContrast(high_contrast) => format!("ToggleHighContrast {}", high_contrast),
+        Message::ToggleThreats(show) => format!("ToggleThreats {}", show),
+        Message::ToggleAutoRotate(auto_rotate) => format!("ToggleAutoRotate {}", auto_rotate),
+        Message::ToggleFlashTaskbar(flash) => format!("ToggleFlashTaskbar {}", flash),
+        Message::ToggleJsonGameLog(log) => format!("ToggleJsonGameLog {}", log),
+        Message::EditBackgroundPath(path) => format!("EditBackgroundPath {}", escape(path)),
+        Message::LoadBackground => String::from("LoadBackground"),
+        Message::EditSyncDir(path) => format!("EditSyncDir {}", escape(path)),
+        Message::ApplySyncDir => String::from("ApplySyncDir"),
+        Message::SelectStoneSkin(skin) => format!("SelectStoneSkin {}", skin),
+        Message::EditStonePictures(path) => format!("EditStonePictures {}", escape(path)),
+        Message::LoadStonePictures => String::from("LoadStonePictures"),
+        Message::SelectUiScale(scale) => format!("SelectUiScale {}", scale),
+        Message::SelectAnimationDelay(delay) => format!("SelectAnimationDelay {}", delay.0),
+        Message::SelectMoveTime(time) => format!("SelectMoveTime {}", time.0),
+        Message::SelectClickSnap(snap) => format!("SelectClickSnap {}", snap),
+        Message::ToggleHitRegions => String::from("ToggleHitRegions"),
+        Message::ToggleRandomColors(random) => format!("ToggleRandomColors {}", random),
+        Message::SelectStartPosition(position) => format!("SelectStartPosition {}", position),
+        Message::StartGame(swap, opening) => {
+            format!("StartGame {} {}", swap, opening.and_then(|i| OPENINGS.get(i)).map_or("-", |opening| opening.name))
+        },
+        Message::ToggleHeatmap => String::from("ToggleHeatmap"),
+        Message::ToggleOpenings => String::from("ToggleOpenings"),
+        Message::ToggleMoves => String::from("ToggleMoves"),
+        Message::ShowPly(ply) => format!("ShowPly {}", ply),
+        Message::ScrubMoves(steps) => format!("ScrubMoves {}", steps),
+        Message::EditComment(comment) => format!("EditComment {}", escape(comment)),
+        Message::ToggleEngine => String::from("ToggleEngine"),
+        Message::ToggleScoreboard => String::from("ToggleScoreboard"),
+        Message::SetupMulti => String::from("SetupMulti"),
+        Message::SelectMultiPlayers(count) => format!("SelectMultiPlayers {}", count),
+        Message::SelectMultiColor(player, color) => format!("SelectMultiColor {} {}", player, color),
+        Message::StartMulti => String::from("StartMulti"),
+        Message::ClickMulti(index) => format!("ClickMulti {}", index),
+        Message::UndoMulti => String::from("UndoMulti"),
+        Message::LeaveMulti => String::from("LeaveMulti"),
+        Message::SortScoreboard(column) => format!("SortScoreboard {}", column),
+        Message::ToggleCorrespondence => String::from("ToggleCorrespondence"),
+        Message::EditRelayServer(server) => format!("EditRelayServer {}", escape(server)),
+        Message::EditRelayPlayer(player) => format!("EditRelayPlayer {}", escape(player)),
+        Message::LogIn => String::from("LogIn"),
+        Message::OpenRemoteGame(id) => format!("OpenRemoteGame {}", escape(id)),
+        Message::SelectEngineSide(side) => format!("SelectEngineSide {}", side),
+        Message::SetEngineTime(millis) => format!("SetEngineTime {}", millis),
+        Message::SelectEngineStyle(style) => format!("SelectEngineStyle {}", style),
+        Message::Resign => String::from("Resign"),
+        Message::ConfirmResign(confirmed) => format!("ConfirmResign {}", confirmed),
+        Message::OfferDraw => String::from("OfferDraw"),
+        Message::AnswerDraw(accepted) => format!("AnswerDraw {}", accepted),
+        Message::TogglePause => String::from("TogglePause"),
+        Message::NewTab => String::from("NewTab"),
+        Message::SelectTab(id) => format!("SelectTab {}", id),
+        Message::CloseTab(id) => format!("CloseTab {}", id),
+        Message::NewGame => String::from("NewGame"),
+        Message::SetupPlayers => String::from("SetupPlayers"),
+        Message::EditPlayerName(i, name) => format!("EditPlayerName {} {}", i, escape(name)),
+        Message::FinishPlayerSetup(start) => format!("FinishPlayerSetup {}", start),
+        Message::ReviewGame => String::from("ReviewGame"),
+        Message::SaveGame => String::from("SaveGame"),
+        Message::ExportReport => String::from("ExportReport"),
+        Message::ToggleTrial => String::from("ToggleTrial"),
+        Message::EditPosition => String::from("EditPosition"),
+        Message::SelectEditTool(tool) => format!("SelectEditTool {}", tool),
+        Message::SetEditBlackToMove(black) => format!("SetEditBlackToMove {}", black),
+        Message::ClearEdit => String::from("ClearEdit"),
+        Message::FinishEdit(play) => format!("FinishEdit {}", play),
+        Message::ImportGame(text) => format!("ImportGame {}", escape(text)),
+        Message::LoadOpening(i) => format!("LoadOpening {}", OPENINGS.get(*i)?.name),
+        // a replay does not talk to the relay server, so its answers are not logged
+        Message::FetchRemoteGames | Message::RelayGames(_) | Message::MoveSent(..) => return None,
+        Message::Tick(_) | Message::HoverBoard(_) | Message::WindowResized(..) | Message::WindowFocused(_)
+            | Message::CloseRequested | Message::Rpc(_) => return None,
+        // what it draws is logged as the `StartGame` it sends
+        Message::QuickStart => return None,
+    };
+    Some(line)
+}
+
+fn from_log(line: &str) -> Option<Message> {
+    let mut words = line.split_whitespace();
+    let message = match words.next()? {
+        "ClickBoard" => Message::ClickBoard(words.next()?.parse().ok()?),
+        "EngineMove" => Message::EngineMove(words.next()?.parse().ok()?),
+        "ToTab" => {
+            let mut parts = line.splitn(3, ' ').skip(1);
+            let id = parts.next()?.parse().ok()?;
+            Message::ToTab(id, Box::new(from_log(parts.next()?)?))
+        },
+        "OpenContextMenu" => {
+            let x = words.next()?.parse().ok()?;
+            let y = words.next()?.parse().ok()?;
+            let index = match words.next()? {
+                "-" => None,
+                index => Some(index.parse().ok()?),
+            };
+            Message::OpenContextMenu(Point::new(x, y), index)
+        },
+        "CloseContextMenu" => Message::CloseContextMenu,
+        "ContextMenu" => {
+            let name = words.next()?;
+            let action = MenuAction::ALL.into_iter()
+                .find(|&action| menu_action_name(action) == name)?;
+            Message::ContextMenu(action)
+        },
+        "ToggleMute" => Message::ToggleMute(words.next()?.parse().ok()?),
+        "SelectRules" => Message::SelectRules(words.next()?.parse::<RuleSet>().ok()?),
+        "SelectOpeningRule" => Message::SelectOpeningRule(words.next()?.parse::<OpeningRule>().ok()?),
+        "AnswerSwap" => Message::AnswerSwap(words.next()?.parse().ok()?),
+        "DeclareOffers" => Message::DeclareOffers(words.next()?.parse().ok()?),
+        "ToggleSettings" => Message::ToggleSettings,
+        "SelectMarkerStyle" => Message::SelectMarkerStyle(words.next()?.parse::<MarkerStyle>().ok()?),
+        "ToggleHighContrast" => Message::ToggleHighContrast(words.next()?.parse().ok()?),
+        "ToggleThreats" => Message::ToggleThreats(words.next()?.parse().ok()?),
+        "ToggleAutoRotate" => Message::ToggleAutoRotate(words.next()?.parse().ok()?),
+        "ToggleFlashTaskbar" => Message::ToggleFlashTaskbar(words.next()?.parse().ok()?),
+        "ToggleJsonGameLog" => Message::ToggleJsonGameLog(words.next()?.parse().ok()?),
+        "EditBackgroundPath" => Message::EditBackgroundPath(unescape(line.split_once(' ').map_or("", |(_, path)| path))),
+        "LoadBackground" => Message::LoadBackground,
+        "EditSyncDir" => Message::EditSyncDir(unescape(line.split_once(' ').map_or("", |(_, path)| path))),
+        "ApplySyncDir" => Message::ApplySyncDir,
+        "SelectStoneSkin" => Message::SelectStoneSkin(line.split_once(' ')?.1.parse::<StoneSkin>().ok()?),
+        "EditStonePictures" => Message::EditStonePictures(unescape(line.split_once(' ').map_or("", |(_, path)| path))),
+        "LoadStonePictures" => Message::LoadStonePictures,
+        "SelectUiScale" => Message::SelectUiScale(words.next()?.parse::<UiScale>().ok()?),
+        "SelectAnimationDelay" => Message::SelectAnimationDelay(words.next()?.parse::<AnimationDelay>().ok()?),
+        "SelectMoveTime" => Message::SelectMoveTime(words.next()?.parse::<MoveTime>().ok()?),
+        "SelectClickSnap" => Message::SelectClickSnap(words.next()?.parse::<ClickSnap>().ok()?),
+        "ToggleHitRegions" => Message::ToggleHitRegions,
+        "ToggleRandomColors" => Message::ToggleRandomColors(words.next()?.parse().ok()?),
+        "SelectStartPosition" => Message::SelectStartPosition(line.split_once(' ')?.1.parse::<StartPosition>().ok()?),
+        "StartGame" => {
+            let swap = words.next()?.parse().ok()?;
+            let opening = match words.next()? {
+                "-" => None,
+                name => {
+                    let opening = Opening::by_name(name)?;
+                    Some(OPENINGS.iter().position(|o| o == opening)?)
+                },
+            };
+            Message::StartGame(swap, opening)
+        },
+        "ToggleHeatmap" => Message::ToggleHeatmap,
+        "ToggleOpenings" => Message::ToggleOpenings,
+        "ToggleMoves" => Message::ToggleMoves,
+        "ShowPly" => Message::ShowPly(words.next()?.parse().ok()?),
+        "ScrubMoves" => Message::ScrubMoves(words.next()?.parse().ok()?),
+        "EditComment" => Message::EditComment(unescape(line.split_once(' ').map_or("", |(_, comment)| comment))),
+        "ToggleEngine" => Message::ToggleEngine,
+        "ToggleScoreboard" => Message::ToggleScoreboard,
+        "SetupMulti" => Message::SetupMulti,
+        "SelectMultiPlayers" => Message::SelectMultiPlayers(words.next()?.parse().ok()?),
+        "SelectMultiColor" => Message::SelectMultiColor(words.next()?.parse().ok()?, words.next()?.parse::<ChessColor>().ok()?),
+        "StartMulti" => Message::StartMulti,
+        "ClickMulti" => Message::ClickMulti(words.next()?.parse().ok()?),
+        "UndoMulti" => Message::UndoMulti,
+        "LeaveMulti" => Message::LeaveMulti,
+        "SortScoreboard" => Message::SortScoreboard(words.next()?.parse::<ScoreColumn>().ok()?),
+        "ToggleCorrespondence" => Message::ToggleCorrespondence,
+        "EditRelayServer" => Message::EditRelayServer(unescape(line.split_once(' ').map_or("", |(_, server)| server))),
+        "EditRelayPlayer" => Message::EditRelayPlayer(unescape(line.split_once(' ').map_or("", |(_, player)| player))),
+        "LogIn" => Message::LogIn,
+        "OpenRemoteGame" => Message::OpenRemoteGame(unescape(line.split_once(' ').map_or("", |(_, id)| id))),
+        "SelectEngineSide" => Message::SelectEngineSide(words.next()?.parse::<EngineSide>().ok()?),
+        "SetEngineTime" => Message::SetEngineTime(words.next()?.parse().ok()?),
+        "SelectEngineStyle" => Message::SelectEngineStyle(words.next()?.parse::<Style>().ok()?),
+        "Resign" => Message::Resign,
+        "ConfirmResign" => Message::ConfirmResign(words.next()?.parse().ok()?),
+        "OfferDraw" => Message::OfferDraw,
+        "AnswerDraw" => Message::AnswerDraw(words.next()?.parse().ok()?),
+        "TogglePause" => Message::TogglePause,
+        "NewTab" => Message::NewTab,
+        "SelectTab" => Message::SelectTab(words.next()?.parse().ok()?),
+        "CloseTab" => Message::CloseTab(words.next()?.parse().ok()?),
+        "NewGame" => Message::NewGame,
+        "SetupPlayers" => Message::SetupPlayers,
+        "EditPlayerName" => {
+            let mut parts = line.splitn(3, ' ').skip(1);
+            let i = parts.next()?.parse().ok()?;
+            Message::EditPlayerName(i, unescape(parts.next().unwrap_or("")))
+        },
+        "FinishPlayerSetup" => Message::FinishPlayerSetup(words.next()?.parse().ok()?),
+        "ReviewGame" => Message::ReviewGame,
+        "SaveGame" => Message::SaveGame,
+        "ExportReport" => Message::ExportReport,
+        "ToggleTrial" => Message::ToggleTrial,
+        "EditPosition" => Message::EditPosition,
+        "SelectEditTool" => Message::SelectEditTool(words.next()?.parse::<EditTool>().ok()?),
+        "SetEditBlackToMove" => Message::SetEditBlackToMove(words.next()?.parse().ok()?),
+        "ClearEdit" => Message::ClearEdit,
+        "FinishEdit" => Message::FinishEdit(words.next()?.parse().ok()?),
+        "ImportGame" => Message::ImportGame(unescape(line.split_once(' ').map_or("", |(_, text)| text))),
+        "LoadOpening" => {
+            let opening = Opening::by_name(words.next()?)?;
+            Message::LoadOpening(OPENINGS.iter().position(|o| o == opening)?)
+        },
+        _ => return None,
+    };
+    Some(message)
+}
+
+/// Keeps `text` on one line: backslashes and line breaks become `\\`, `\n` and `\r`.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
@@ -0,0 +1,262 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::board::{Board, ChessColor, Move, PlaceError};
+use crate::rules::{Outcome, RuleSet, RuleViolation, Rules};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum GameState {
+    /// The player of this color is to move.
+    Wait(ChessColor),
+    Win(ChessColor),
+    Draw,
+}
+
+impl GameState {
+    pub fn is_over(&self) -> bool {
+        !matches!(self, GameState::Wait(_))
+    }
+}
+
+/// How a finished game came to its end.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Ending {
+    /// A winning line was made.
+    Line,
+    /// The board filled up without a winner.
+    BoardFull,
+    /// The side to move gave up.
+    Resignation,
+    /// Both sides agreed to a draw.
+    Agreement,
+    /// Enough pairs were captured, see [`crate::rules::Pente`].
+    Captures,
+}
+
+/// Why [`Game::put_chess`] refused a move.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum MoveError {
+    GameOver,
+    Illegal(RuleViolation),
+    /// Resigning with more than one opponent, nobody would win.
+    SeveralOpponents,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::GameOver => write!(f, "game is over"),
+            MoveError::Illegal(violation) => write!(f, "{}", violation),
+            MoveError::SeveralOpponents => write!(f, "there is more than one opponent to resign to"),
+        }
+    }
+}
+
+/// A board together with the rules it is played by, whose turn it is and whether somebody has won.
+#[derive(Clone, Debug)]
+pub struct Game {
+    board: Board,
+    rule_set: RuleSet,
+    state: GameState,
+    winning_line: Option<Vec<usize>>,
+    ending: Option<Ending>,
+    /// Time spent thinking on each move, `None` when it was not measured.
+    move_times: Vec<Option<Duration>>,
+    /// Number of chesses the game started with.
+    setup_plies: usize,
+    /// Rules played instead of those of `rule_set`, see [`Game::set_rules`].
+    custom_rules: Option<CustomRules>,
+}
+
+/// Rules given to [`Game::set_rules`].
+#[derive(Clone)]
+struct CustomRules(Arc<dyn Rules + Send + Sync>);
+
+impl fmt::Debug for CustomRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CustomRules")
+    }
+}
+
+impl Game {
+    /// Starts a game from `board`. Chesses already on it make the position the game starts
+    /// from and are not taken back by [`Game::undo`].
+    pub fn new(board: Board, rule_set: RuleSet) -> Self {
+        let state = GameState::Wait(board.next());
+        let move_times = vec![None; board.ply()];
+        let setup_plies = board.ply();
+        Self { board, rule_set, state, winning_line: None, ending: None, move_times, setup_plies, custom_rules: None }
+    }
+
+    /// Number of chesses on the board the game started from, see [`Game::new`].
+    pub fn setup_plies(&self) -> usize {
+        self.setup_plies
+    }
+
+    /// The side to move in the position the game started from.
+    pub fn first_player(&self) -> ChessColor {
+        match self.board.chesses().get(self.setup_plies) {
+            Some(chess) => chess.color,
+            None => self.board.next(),
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn rule_set(&self) -> RuleSet {
+        self.rule_set
+    }
+
+    /// The rules the game is played by, those of its rule set unless [`Game::set_rules`] replaced them.
+    pub fn rules(&self) -> &dyn Rules {
+        match &self.custom_rules {
+            Some(rules) => rules.0.as_ref(),
+            None => self.rule_set.rules(),
+        }
+    }
+
+    /// Plays by `rules` from the next move on, also after [`Game::restart`]. The rule set still
+    /// names the game in saved games and records.
+    pub fn set_rules(&mut self, rules: Arc<dyn Rules + Send + Sync>) {
+        self.custom_rules = Some(CustomRules(rules));
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    /// Puts a chess for the side to move, removes what it captures and returns the new state.
+    pub fn put_chess(&mut self, index: usize) -> Result<GameState, MoveError> {
+        let GameState::Wait(player) = self.state else { return Err(MoveError::GameOver); };
+        self.rules().is_legal(&self.board, index).map_err(MoveError::Illegal)?;
+        self.board.apply(Move::new(player, index, self.board.ply())).map_err(|e| MoveError::Illegal(match e {
+            PlaceError::OutOfRange => RuleViolation::OutOfBoard,
+            PlaceError::Occupied | PlaceError::OutOfOrder => RuleViolation::Occupied,
+        }))?;
+        let captured = self.rules().captures(&self.board, index);
+        self.board.remove_chesses(&captured);
+        self.move_times.push(None);
+        let outcome = match self.rules().check_result(&self.board, index) {
+            Ok(outcome) => outcome,
+            Err(violation) => {
+                // a move the rules cannot judge is not made
+                self.board.undo();
+                self.move_times.pop();
+                return Err(MoveError::Illegal(violation));
+            },
+        };
+        self.state = match outcome {
+            Some(Outcome::Win { color, line }) => {
+                self.winning_line = Some(line);
+                self.ending = Some(Ending::Line);
+                GameState::Win(color)
+            },
+            Some(Outcome::Captures { color }) => {
+                self.ending = Some(Ending::Captures);
+                GameState::Win(color)
+            },
+            Some(Outcome::Draw) => {
+                self.ending = Some(Ending::BoardFull);
+                GameState::Draw
+            },
+            None => GameState::Wait(self.board.next()),
+        };
+        Ok(self.state)
+    }
+
+    /// Like [`Game::put_chess`], remembering that the player thought for `time` about the move.
+    pub fn put_chess_timed(&mut self, index: usize, time: Duration) -> Result<GameState, MoveError> {
+        let state = self.put_chess(index)?;
+        if let Some(last) = self.move_times.last_mut() {
+            *last = Some(time);
+        }
+        Ok(state)
+    }
+
+    /// Thinking time of every move so far, in order.
+    pub fn move_times(&self) -> &[Option<Duration>] {
+        &self.move_times
+    }
+
+    /// Plays space separated coordinates like "h8 i9 j10" as written by [`Board::move_list`].
+    pub fn play_moves(&mut self, moves: &str) -> Result<(), String> {
+        for coord in moves.split_whitespace() {
+            let pos = self.board.coord_to_pos(coord).ok_or_else(|| format!("cannot read move \"{}\"", coord))?;
+            let index = self.board.pos_to_index(pos);
+            self.put_chess(index).map_err(|e| format!("cannot play {}: {}", coord, e))?;
+        }
+        Ok(())
+    }
+
+    /// Ends the game with a loss for the side to move, only between two players.
+    pub fn resign(&mut self) -> Result<GameState, MoveError> {
+        let GameState::Wait(player) = self.state else { return Err(MoveError::GameOver); };
+        if self.board.players().len() > 2 {
+            return Err(MoveError::SeveralOpponents);
+        }
+        self.state = GameState::Win(self.board.player_after(player));
+        self.ending = Some(Ending::Resignation);
+        Ok(self.state)
+    }
+
+    /// Ends the game as a draw all players agreed to.
+    pub fn agree_draw(&mut self) -> Result<GameState, MoveError> {
+        if self.state.is_over() {
+            return Err(MoveError::GameOver);
+        }
+        self.state = GameState::Draw;
+        self.ending = Some(Ending::Agreement);
+        Ok(self.state)
+    }
+
+    /// How the game ended, `None` while it is going on.
+    pub fn ending(&self) -> Option<Ending> {
+        self.ending
+    }
+
+    /// Takes back the last chess, returns whether one was played since the start. A resignation
+    /// or agreed draw is taken back on its own, leaving the chesses as they are.
+    pub fn undo(&mut self) -> bool {
+        if matches!(self.ending, Some(Ending::Resignation | Ending::Agreement)) {
+            self.state = GameState::Wait(self.board.next());
+            self.ending = None;
+            return true;
+        }
+        match self.board.last_move() {
+            Some(mv) if mv.ply >= self.setup_plies => {
+                self.board.undo();
+                self.move_times.pop();
+                self.state = GameState::Wait(self.board.next());
+                self.winning_line = None;
+                self.ending = None;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Clears the board to start over with `rule_set`.
+    pub fn restart(&mut self, rule_set: RuleSet) {
+        self.board.clear();
+        self.move_times.clear();
+        self.setup_plies = 0;
+        self.rule_set = rule_set;
+        self.state = GameState::Wait(self.board.next());
+        self.winning_line = None;
+        self.ending = None;
+    }
+
+    /// Indices of the winning chesses when the game is won.
+    pub fn winning_line(&self) -> Option<&[usize]> {
+        self.winning_line.as_deref()
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(Board::default(), RuleSet::default())
+    }
+}
@@ -0,0 +1,84 @@
+//! Finished games appended as lines of JSON to the `games.jsonl` storage key, one object per game
+//! with the settings it was played with, the players, the moves with their thinking times and the
+//! result, for analyzing one's play with other programs.
+
+use gomoku_rs::board::{Board, Chess, ChessColor};
+use gomoku_rs::game::{Ending, Game, GameState};
+use gomoku_rs::opening_rule::OpeningRule;
+use gomoku_rs::sgf;
+
+use crate::json::string;
+use crate::scoreboard::Player;
+use crate::storage;
+
+const KEY: &str = "games.jsonl";
+
+/// Appends `game` to the log. `players` are the black and the white one when known, `engine_time`
+/// the engine's milliseconds per move when it played.
+pub fn append(game: &Game, players: &[Option<Player>; 2], opening_rule: OpeningRule, engine_time: Option<u32>) {
+    storage::append(KEY, &(line(game, players, opening_rule, engine_time) + "\n"));
+}
+
+fn line(game: &Game, players: &[Option<Player>; 2], opening_rule: OpeningRule, engine_time: Option<u32>) -> String {
+    let board = game.board();
+    let (setup, _) = board.chesses().split_at(game.setup_plies());
+    let moves: Vec<String> = board.moves().zip(game.move_times()).skip(game.setup_plies()).map(|(mv, time)| {
+        format!(
+            "{{\"ply\":{},\"color\":{},\"move\":{},\"seconds\":{}}}",
+            mv.ply + 1,
+            string(color(mv.player)),
+            string(&board.pos_to_coord(board.index_to_pos(mv.index))),
+            time.map_or(String::from("null"), |time| format!("{:.3}", time.as_secs_f64())))
+    }).collect();
+    let winner = match game.state() {
+        GameState::Win(ChessColor::Black) => string("black"),
+        GameState::Win(ChessColor::White) => string("white"),
+        _ => String::from("null"),
+    };
+    let ending = match game.ending() {
+        Some(Ending::Line) => string("line"),
+        Some(Ending::BoardFull) => string("board full"),
+        Some(Ending::Resignation) => string("resignation"),
+        Some(Ending::Agreement) => string("agreement"),
+        Some(Ending::Captures) => string("captures"),
+        None => String::from("null"),
+    };
+    let player = |player: &Option<Player>| player.as_ref().map_or(String::from("null"), |player| string(&player.to_string()));
+    format!(
+        "{{\"finished\":{},\"rules\":{},\"opening_rule\":{},\"size\":{},\"engine_ms_per_move\":{},\
+        \"black\":{},\"white\":{},\"setup_black\":[{}],\"setup_white\":[{}],\"moves\":[{}],\
+        \"result\":{},\"winner\":{},\"ending\":{}}}",
+        unix_time().map_or(String::from("null"), |seconds| seconds.to_string()),
+        string(&game.rule_set().to_string()),
+        string(&opening_rule.to_string()),
+        board.cells_per_row(),
+        engine_time.map_or(String::from("null"), |millis| millis.to_string()),
+        player(&players[0]),
+        player(&players[1]),
+        coords(board, setup, ChessColor::Black),
+        coords(board, setup, ChessColor::White),
+        moves.join(","),
+        sgf::result(game).map_or(String::from("null"), string),
+        winner,
+        ending)
+}
+
+/// The coordinates of the `color` ones of `chesses` as JSON strings separated by commas.
+fn coords(board: &Board, chesses: &[Chess], color: ChessColor) -> String {
+    chesses.iter().filter(|c| c.color == color).map(|c| string(&board.pos_to_coord(c.pos))).collect::<Vec<_>>().join(",")
+}
+
+fn color(color: ChessColor) -> &'static str {
+    if color == ChessColor::Black { "black" } else { "white" }
+}
+
+/// Seconds since 1970, `None` in the browser where the system clock cannot be read this way.
+#[cfg(not(target_arch = "wasm32"))]
+fn unix_time() -> Option<u64> {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok().map(|time| time.as_secs())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn unix_time() -> Option<u64> {
+    None
+}
@@ -0,0 +1,79 @@
+//! Two named players taking turns at one board, with the score of the games they played since
+//! they sat down. The players change colors from one game to the next.
+
+use gomoku_rs::board::ChessColor;
+use gomoku_rs::game::GameState;
+
+pub struct Hotseat {
+    names: [String; 2],
+    /// Which of `names` started the current game holding black.
+    starts_black: usize,
+    wins: [u32; 2],
+    draws: u32,
+}
+
+impl Hotseat {
+    pub fn new(names: [String; 2]) -> Self {
+        Self { names, starts_black: 0, wins: [0; 2], draws: 0 }
+    }
+
+    pub fn names(&self) -> &[String; 2] {
+        &self.names
+    }
+
+    /// Starts the next game with the colors changed.
+    pub fn next_game(&mut self) {
+        self.starts_black = 1 - self.starts_black;
+    }
+
+    /// Which player holds black, `first_player_black` tells whether the opening rule swapped them.
+    fn black(&self, first_player_black: bool) -> usize {
+        if first_player_black { self.starts_black } else { 1 - self.starts_black }
+    }
+
+    /// Name of the player holding black and of the one holding white.
+    pub fn sides(&self, first_player_black: bool) -> (&str, &str) {
+        let black = self.black(first_player_black);
+        (&self.names[black], &self.names[1 - black])
+    }
+
+    /// Adds the result of the finished game to the score.
+    pub fn record(&mut self, state: GameState, first_player_black: bool) {
+        let black = self.black(first_player_black);
+        match state {
+            GameState::Win(ChessColor::Black) => { self.wins[black] += 1; },
+            GameState::Win(ChessColor::White) => { self.wins[1 - black] += 1; },
+            GameState::Draw => { self.draws += 1; },
+            GameState::Win(_) | GameState::Wait(_) => {},
+        }
+    }
+
+    /// The score like "Ann 2 : 1 Bob, 1 draw".
+    pub fn score_text(&self) -> String {
+        let draws = match self.draws {
+            0 => String::new(),
+            1 => String::from(", 1 draw"),
+            draws => format!(", {} draws", draws),
+        };
+        format!("{} {} : {} {}{}", self.names[0], self.wins[0], self.wins[1], self.names[1], draws)
+    }
+
+    /// The names and the score like "Ann\tBob; 1; 2 1; 1", in the form [`Self::restore`] reads.
+    /// The numbers are who started the current game holding black, the wins of both and the draws.
+    pub fn saved(&self) -> String {
+        let [first, second] = self.names.clone().map(|name| name.replace('\t', " "));
+        format!("{}\t{}; {}; {} {}; {}", first, second, self.starts_black, self.wins[0], self.wins[1], self.draws)
+    }
+
+    pub fn restore(saved: &str) -> Result<Self, String> {
+        let bad = || format!("cannot read players \"{}\"", saved);
+        let mut parts = saved.rsplitn(4, ';').map(str::trim);
+        let draws = parts.next().and_then(|draws| draws.parse().ok()).ok_or_else(bad)?;
+        let wins = parts.next().and_then(|wins| wins.split_once(' '))
+            .and_then(|(first, second)| Some([first.parse().ok()?, second.parse().ok()?]))
+            .ok_or_else(bad)?;
+        let starts_black = parts.next().and_then(|starts| starts.parse().ok()).filter(|&starts| starts < 2).ok_or_else(bad)?;
+        let names = parts.next().and_then(|names| names.split_once('\t')).ok_or_else(bad)?;
+        Ok(Self { names: [names.0.to_string(), names.1.to_string()], starts_black, wins, draws })
+    }
+}
@@ -0,0 +1,244 @@
+//! Reading games written by other programs: plain coordinate lists or transcripts like
+//! "1. h8 i9 2. j10" as read by [`crate::transcript::read`], Piskvork `.psq` files and SGF as written by [`crate::sgf::write`].
+
+use crate::board::{Board, Chess, ChessColor, Pos};
+use crate::game::{Game, GameState};
+use crate::transcript;
+
+/// A game read by [`read`] on the target board: the position it started from, its moves and how
+/// it ended.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    /// Chesses on the board before the first move, from the `AB` and `AW` of SGF.
+    pub setup: Vec<Chess>,
+    /// The side which moves first, black unless the record tells otherwise.
+    pub first_player: ChessColor,
+    pub moves: Vec<Chess>,
+    /// The result written in the record, which the moves alone may not reach, like a resignation.
+    pub result: Option<GameState>,
+}
+
+/// A game as written, on a board of its own size with points counted from the top left corner.
+struct Written {
+    size: usize,
+    setup: Vec<(ChessColor, (i32, i32))>,
+    first_player: ChessColor,
+    moves: Vec<(ChessColor, (i32, i32))>,
+    result: Option<GameState>,
+}
+
+impl Written {
+    /// Moves played in turn from black, by formats which do not tell the colors.
+    fn alternating(size: usize, points: Vec<(i32, i32)>) -> Self {
+        let moves = points.into_iter().enumerate()
+            .map(|(i, point)| (if i % 2 == 0 { ChessColor::Black } else { ChessColor::White }, point))
+            .collect();
+        Self { size, setup: vec![], first_player: ChessColor::Black, moves, result: None }
+    }
+}
+
+/// Replaces `game` with the game in `text`, keeping its rules.
+pub fn load(text: &str, game: &mut Game) -> Result<(), String> {
+    let record = read(text, game.board())?;
+    let mut board = Board::new(game.board().cells_per_row());
+    for chess in &record.setup {
+        board.try_put_chess(chess.color, board.pos_to_index(chess.pos))
+            .map_err(|e| format!("chess set up at {}: {}", board.pos_to_coord(chess.pos), e))?;
+    }
+    board.set_next(record.first_player);
+    *game = Game::new(board, game.rule_set());
+    for (i, chess) in record.moves.iter().enumerate() {
+        let coord = game.board().pos_to_coord(chess.pos);
+        let to_move = if game.state() == GameState::Wait(ChessColor::White) { ChessColor::White } else { ChessColor::Black };
+        if chess.color != to_move && !game.state().is_over() {
+            return Err(format!("move {} at {} is {}'s, but {} is to move", i + 1, coord, color_name(chess.color), color_name(to_move)));
+        }
+        let index = game.board().pos_to_index(chess.pos);
+        game.put_chess(index).map_err(|e| format!("move {} at {}: {}", i + 1, coord, e))?;
+    }
+    // a resignation is the side to move giving up, one out of turn cannot be told apart from a
+    // loss on time, so the game is left going on
+    match (record.result, game.state()) {
+        (Some(GameState::Draw), state) if !state.is_over() => { let _ = game.agree_draw(); },
+        (Some(GameState::Win(ChessColor::Black)), GameState::Wait(ChessColor::White)) | (Some(GameState::Win(ChessColor::White)), GameState::Wait(ChessColor::Black)) => { let _ = game.resign(); },
+        _ => {},
+    }
+    Ok(())
+}
+
+/// The game in `text`, moved onto a board of `board`'s size if it was played on another one.
+pub fn read(text: &str, board: &Board) -> Result<Record, String> {
+    let text = text.trim();
+    let written = if text.starts_with("Piskvorky") {
+        read_psq(text)?
+    } else if text.starts_with('(') {
+        read_sgf(text)?
+    } else {
+        let moves = transcript::read(text, board)?;
+        Written::alternating(board.cells_per_row(), moves.into_iter().map(|pos| (pos.x as i32, pos.y as i32)).collect())
+    };
+    if written.setup.is_empty() && written.moves.is_empty() {
+        return Err(String::from("no moves found"));
+    }
+    // the setup and the moves are moved together so they keep their places to each other
+    let points = written.setup.iter().chain(&written.moves).map(|&(_, point)| point).collect();
+    let mut positions = fit(written.size, points, board.cells_per_row())?.into_iter();
+    let mut chesses = |written: Vec<(ChessColor, (i32, i32))>| -> Vec<Chess> {
+        written.into_iter().zip(positions.by_ref()).map(|((color, _), pos)| Chess { pos, color }).collect()
+    };
+    let setup = chesses(written.setup);
+    let moves = chesses(written.moves);
+    Ok(Record { setup, first_player: written.first_player, moves, result: written.result })
+}
+
+fn color_name(color: ChessColor) -> String {
+    color.to_string().to_lowercase()
+}
+
+/// A Piskvork record: a "Piskvorky 20x20, ..." header, then one "x,y,milliseconds" line per move
+/// counted from 1 at the top left corner, then lines about the players which are ignored.
+fn read_psq(text: &str) -> Result<Written, String> {
+    let mut lines = text.lines();
+    let header = lines.next().unwrap_or_default();
+    let size = header.split_whitespace().nth(1)
+        .and_then(|size| size.trim_end_matches(',').split('x').next())
+        .and_then(|size| size.parse().ok())
+        .ok_or_else(|| format!("cannot read board size from \"{}\"", header))?;
+    let mut moves = vec![];
+    for line in lines {
+        let mut numbers = line.split(',').map(|n| n.trim().parse::<i32>());
+        match (numbers.next(), numbers.next()) {
+            (Some(Ok(x)), Some(Ok(y))) if x >= 1 && y >= 1 => moves.push((x - 1, y - 1)),
+            _ => break,
+        }
+    }
+    Ok(Written::alternating(size, moves))
+}
+
+/// The `B[..]` and `W[..]` moves of the main line with their colors. The root node holds the size
+/// in `SZ[..]`, the result in `RE[..]`, and the chesses of a composed position in `AB[..]` and
+/// `AW[..]` with the side to move in `PL[..]`.
+fn read_sgf(text: &str) -> Result<Written, String> {
+    let nodes = sgf_nodes(text)?;
+    let root = nodes.first().ok_or("no SGF nodes found")?;
+    let size = match property(root, "SZ") {
+        // a rectangular board is written "15:15"
+        Some(size) => size.split(':').next().and_then(|size| size.trim().parse().ok())
+            .ok_or_else(|| format!("cannot read board size \"{}\"", size))?,
+        None => 15,
+    };
+    let result = property(root, "RE").and_then(|result| match result.trim().chars().next() {
+        Some('B') => Some(GameState::Win(ChessColor::Black)),
+        Some('W') => Some(GameState::Win(ChessColor::White)),
+        Some('0') | Some('D') => Some(GameState::Draw),
+        _ => None,
+    });
+    let mut setup = vec![];
+    for (name, color) in [("AB", ChessColor::Black), ("AW", ChessColor::White)] {
+        for point in root.iter().filter(|(property, _)| property == name).flat_map(|(_, values)| values) {
+            setup.push((color, sgf_point(point)?));
+        }
+    }
+    let mut moves = vec![];
+    for node in &nodes {
+        for (name, values) in node {
+            let color = match name.as_str() {
+                "B" => ChessColor::Black,
+                "W" => ChessColor::White,
+                _ => continue,
+            };
+            let point = values.first().map_or("", String::as_str);
+            moves.push((color, sgf_point(point)?));
+        }
+    }
+    let first_player = match property(root, "PL").map(str::trim) {
+        Some("B") => ChessColor::Black,
+        Some("W") => ChessColor::White,
+        Some(player) => return Err(format!("cannot read SGF player \"{}\"", player)),
+        None => moves.first().map_or(ChessColor::Black, |&(color, _)| color),
+    };
+    Ok(Written { size, setup, first_player, moves, result })
+}
+
+/// Column and row of an SGF point like "hh".
+fn sgf_point(point: &str) -> Result<(i32, i32), String> {
+    let mut letters = point.bytes();
+    match (letters.next(), letters.next(), letters.next()) {
+        (Some(x @ b'a'..=b'z'), Some(y @ b'a'..=b'z'), None) => Ok(((x - b'a') as i32, (y - b'a') as i32)),
+        _ => Err(format!("cannot read SGF point \"{}\"", point)),
+    }
+}
+
+/// Properties of an SGF node with all their values, in the order they were written.
+type Node = Vec<(String, Vec<String>)>;
+
+/// The nodes of the main line of the first game in `text`, which ends where its first variation
+/// does. Values may hold any of `;()[` and an escaped `\]`.
+fn sgf_nodes(text: &str) -> Result<Vec<Node>, String> {
+    let mut nodes: Vec<Node> = vec![];
+    let mut name = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => {},
+            ')' => break,
+            ';' => nodes.push(vec![]),
+            '[' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        // a backslash before a line break joins the lines
+                        Some('\\') => match chars.next() {
+                            Some('\n') => {},
+                            Some(escaped) => value.push(escaped),
+                            None => break,
+                        },
+                        Some(']') => break,
+                        Some(c) => value.push(c),
+                        None => return Err(format!("SGF value \"{}\" is not closed", value)),
+                    }
+                }
+                let node = nodes.last_mut().ok_or("SGF property before the first node")?;
+                if !name.is_empty() {
+                    node.push((std::mem::take(&mut name), vec![value]));
+                } else if let Some((_, values)) = node.last_mut() {
+                    // more values of the same property, like AB[aa][bb]
+                    values.push(value);
+                } else {
+                    return Err(format!("SGF value \"{}\" without a property", value));
+                }
+            },
+            // property names of old SGF versions have lower case letters in them, which are skipped
+            c if c.is_ascii_uppercase() => name.push(c),
+            _ => {},
+        }
+    }
+    Ok(nodes)
+}
+
+/// The first value of the property called `name` in `node`.
+fn property<'a>(node: &'a Node, name: &str) -> Option<&'a str> {
+    node.iter().find(|(property, _)| property == name).and_then(|(_, values)| values.first()).map(String::as_str)
+}
+
+/// Moves the game from a `size` board onto a `target_size` one, centering it when the size differs.
+fn fit(size: usize, moves: Vec<(i32, i32)>, target_size: usize) -> Result<Vec<Pos>, String> {
+    let (dx, dy) = if size == target_size {
+        (0, 0)
+    } else {
+        let min_x = moves.iter().map(|m| m.0).min().unwrap_or(0);
+        let max_x = moves.iter().map(|m| m.0).max().unwrap_or(0);
+        let min_y = moves.iter().map(|m| m.1).min().unwrap_or(0);
+        let max_y = moves.iter().map(|m| m.1).max().unwrap_or(0);
+        let center = target_size as i32 / 2;
+        (center - (min_x + max_x) / 2, center - (min_y + max_y) / 2)
+    };
+    moves.into_iter().map(|(x, y)| {
+        let (x, y) = (x + dx, y + dy);
+        if x >= 0 && y >= 0 && (x as usize) < target_size && (y as usize) < target_size {
+            Ok(Pos::new(x as usize, y as usize))
+        } else {
+            Err(format!("the game does not fit on a {}x{} board", target_size, target_size))
+        }
+    }).collect()
+}
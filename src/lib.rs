@@ -0,0 +1,19 @@
+//! Gomoku game logic, independent of any user interface, and [`board_view`], an iced widget
+//! drawing a board for any application to embed.
+
+pub mod animation;
+pub mod board;
+pub mod board_view;
+pub mod engine;
+pub mod game;
+pub mod import;
+pub mod opening_rule;
+pub mod openings;
+pub mod report;
+pub mod rules;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod sgf;
+pub mod symmetry;
+pub mod threats;
+pub mod transcript;
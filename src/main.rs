@@ -1,321 +1,2656 @@
-use iced::{mouse, touch, Color, Size};
-use iced::mouse::{Cursor, Interaction};
-use iced::widget::canvas::{Cache, Canvas, Geometry, Path, Stroke, event};
-use iced::{Element, Rectangle, Renderer, Sandbox, Settings, Theme, Point, Length};
-use iced::widget::{canvas, column, container};
+use iced::{Application, Command, Element, Length, Settings, Subscription, Theme, Point, clipboard, executor, keyboard, window};
+use iced::widget::{button, checkbox, column, container, horizontal_space, pick_list, row, scrollable, slider, text, text_input, Column, Row};
+use iced::widget::scrollable::{Direction, Properties, RelativeOffset};
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use iced::time::{Duration, Instant};
+
+use gomoku_rs::animation;
+use gomoku_rs::board::{Board, CellState, Chess, ChessColor, Pos};
+use gomoku_rs::board_view::{Bitmap, BoardEvent, BoardView, ClickSnap, MarkerStyle, MenuAction, StoneSkin};
+use gomoku_rs::engine::{Engine, Style};
+use gomoku_rs::game::{Ending, Game, GameState};
+use gomoku_rs::import;
+use gomoku_rs::opening_rule::{OpeningProtocol, OpeningRule, Step};
+use gomoku_rs::openings::{Opening, OpeningKind, OPENINGS};
+use gomoku_rs::rules::{Pente, Renju, RuleSet};
+use gomoku_rs::report;
+use gomoku_rs::sgf;
+use gomoku_rs::threats::find_threats;
+use gomoku_rs::transcript;
+
+mod analysis;
+mod bitmap;
+mod config;
+mod editor;
+mod event_log;
+mod game_log;
+mod hotseat;
+mod json;
+mod multi;
+mod notification;
+mod opening_stats;
+mod opponent;
+mod relay;
+mod rpc;
+mod scoreboard;
+mod session;
+mod sound;
+mod storage;
+mod thinking;
+
+use analysis::Analysis;
+use config::{AnimationDelay, Config, EngineTime, MoveTime, StartPosition, UiScale};
+use editor::{EditTool, Editor};
+use event_log::{EventLog, Replay};
+use hotseat::Hotseat;
+use multi::{Multi, MAX_PLAYERS, MIN_PLAYERS};
+use opening_stats::OpeningStats;
+use opponent::{Decision, EngineSide, Opponent};
+use relay::RemoteGame;
+use scoreboard::{Player, ScoreColumn, ScoreRow, Scoreboard};
+use session::{SavedTab, Session};
+use sound::{SoundEffect, SoundPlayer};
 
 fn main() -> iced::Result {
-    GomokuGame::run(Settings::default())
+    let flags = match parse_args() {
+        Ok(flags) => flags,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        },
+    };
+    let scale = flags.config.ui_scale.factor() as f32;
+    let size = match flags.session.as_ref().and_then(|session| session.window_size) {
+        Some((width, height)) => iced::Size::new(width as f32, height as f32),
+        None => iced::Size::new(GomokuGame::WINDOW_SIZE.width * scale, GomokuGame::WINDOW_SIZE.height * scale),
+    };
+    let mut settings = Settings::with_flags(flags);
+    settings.window.size = size;
+    // the session is saved before the window closes, see `Message::CloseRequested`
+    settings.window.exit_on_close_request = false;
+    GomokuGame::run(settings)
+}
+
+struct Flags {
+    config: Config,
+    /// Session to play back instead of the saved game.
+    replay: Option<Replay>,
+    /// Game record to open instead of the saved game.
+    import: Option<String>,
+    /// Tabs and layout of the last run.
+    session: Option<Session>,
+    /// Port of the local control server, see [`rpc`].
+    serve: Option<u16>,
+}
+
+fn parse_args() -> Result<Flags, String> {
+    let mut flags = Flags { config: Config::load(), replay: None, import: None, session: Session::load(), serve: None };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--replay" => {
+                let path = args.next().ok_or_else(|| String::from("missing value for --replay"))?;
+                let log = std::fs::read_to_string(&path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+                flags.replay = Some(Replay::parse(&log).map_err(|e| format!("cannot replay {}: {}", path, e))?);
+            },
+            "--import" => {
+                let path = args.next().ok_or_else(|| String::from("missing value for --import"))?;
+                flags.import = Some(std::fs::read_to_string(&path).map_err(|e| format!("cannot read {}: {}", path, e))?);
+            },
+            "--serve" => {
+                let port = args.next().ok_or_else(|| String::from("missing value for --serve"))?;
+                flags.serve = Some(port.parse().map_err(|_| format!("port must be a number, got \"{}\"", port))?);
+            },
+            "--help" | "-h" => {
+                return Err(String::from("usage: gomoku-rs [--replay <file>] [--import <file>] [--serve <port>]"));
+            },
+            _ => { return Err(format!("unknown argument \"{}\"", arg)); }
+        }
+    }
+    Ok(flags)
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Message {
+#[derive(Debug, Clone)]
+pub enum Message {
     ClickBoard(usize),
+    /// A move of the engine, the same as a click on the board but only while the engine is to move.
+    EngineMove(usize),
+    OpenContextMenu(Point, Option<usize>),
+    CloseContextMenu,
+    ContextMenu(MenuAction),
+    ToggleMute(bool),
+    SelectRules(RuleSet),
+    /// Starts a new game whose first moves follow `OpeningRule`.
+    SelectOpeningRule(OpeningRule),
+    /// Answers [`Step::Swap`] of the opening, swapping colors when `true`.
+    AnswerSwap(bool),
+    /// Answers [`Step::Declare`] of the opening with the number of fifth moves.
+    DeclareOffers(usize),
+    ToggleSettings,
+    SelectMarkerStyle(MarkerStyle),
+    ToggleHighContrast(bool),
+    ToggleThreats(bool),
+    ToggleAutoRotate(bool),
+    ToggleFlashTaskbar(bool),
+    ToggleJsonGameLog(bool),
+    EditBackgroundPath(String),
+    /// Draws the image at the entered path under the grid, or no image when the path is empty.
+    LoadBackground,
+    SelectStoneSkin(StoneSkin),
+    EditStonePictures(String),
+    /// Loads the chess pictures from the entered folder, or none when it is empty.
+    LoadStonePictures,
+    EditSyncDir(String),
+    /// Keeps the settings, the scores and the finished games in the entered folder from now on, in
+    /// the data directory when it is empty.
+    ApplySyncDir,
+    SelectUiScale(UiScale),
+    SelectAnimationDelay(AnimationDelay),
+    SelectMoveTime(MoveTime),
+    SelectClickSnap(ClickSnap),
+    /// Shows or hides where clicks land on each point, a developer's help bound to F12.
+    ToggleHitRegions,
+    ToggleRandomColors(bool),
+    SelectStartPosition(StartPosition),
+    /// Starts a new game as set up in the quick start settings, drawing what they leave to chance.
+    QuickStart,
+    /// Starts a new game with the sides swapped when `true`, then puts the opening at this index of
+    /// [`OPENINGS`]. Sent by [`Message::QuickStart`] with what it drew, so a replay draws the same.
+    StartGame(bool, Option<usize>),
+    ToggleHeatmap,
+    ToggleOpenings,
+    ToggleMoves,
+    /// Shows the position after this many moves, the game's own position from its current ply on.
+    ShowPly(usize),
+    /// Shows the position this many moves later, or earlier when negative, like [`Message::ShowPly`].
+    /// Sent by the mouse wheel and by Page Up and Page Down over the board.
+    ScrubMoves(i32),
+    /// Sets the comment on the move of the shown position, an empty one removes it.
+    EditComment(String),
+    ToggleEngine,
+    ToggleScoreboard,
+    SortScoreboard(ScoreColumn),
+    /// Shows the correspondence games of the relay server next to the board.
+    ToggleCorrespondence,
+    EditRelayServer(String),
+    EditRelayPlayer(String),
+    /// Fetches the games of the relay player which are going on.
+    LogIn,
+    /// Fetches the relay games again, sent every [`relay::REFRESH_PERIOD`] while logged in.
+    FetchRemoteGames,
+    /// The games fetched from the relay server, or why they could not be fetched.
+    RelayGames(Result<Vec<RemoteGame>, String>),
+    /// Opens the relay game with this id in a new tab, or shows the tab it is open in.
+    OpenRemoteGame(String),
+    /// The relay server's answer to the move sent from the tab with this id.
+    MoveSent(u32, Result<(), String>),
+    /// Puts the game aside for three or more players to pick their colors.
+    SetupMulti,
+    SelectMultiPlayers(usize),
+    SelectMultiColor(usize, ChessColor),
+    /// Starts a game of the players on an empty board, also after one ended.
+    StartMulti,
+    ClickMulti(usize),
+    UndoMulti,
+    /// Goes back to the game put aside.
+    LeaveMulti,
+    SelectEngineSide(EngineSide),
+    /// Changes the time per engine move in milliseconds, from its next move on.
+    SetEngineTime(u32),
+    /// Changes how the engine weighs attack against defense, from its next move on.
+    SelectEngineStyle(Style),
+    /// Asks whether the side to move really wants to resign.
+    Resign,
+    ConfirmResign(bool),
+    /// The side to move offers a draw, the other side answers with [`Message::AnswerDraw`].
+    OfferDraw,
+    AnswerDraw(bool),
+    /// Stops or continues the thinking time, hiding the board while it is stopped.
+    TogglePause,
+    /// A message for the game in the tab with this id, handled there even when another tab is
+    /// shown. Sent by the engine, the control server and commands answering later, like reading
+    /// the clipboard.
+    ToTab(u32, Box<Message>),
+    /// Opens a new game in another tab.
+    NewTab,
+    SelectTab(u32),
+    CloseTab(u32),
+    /// Starts a new game with the same rules.
+    NewGame,
+    /// Asks for the names of two players sharing the board.
+    SetupPlayers,
+    EditPlayerName(usize, String),
+    /// Starts keeping score for the named players with a new game when `true`.
+    FinishPlayerSetup(bool),
+    /// Hides the result of a finished game to look at the board.
+    ReviewGame,
+    /// Exports the game as SGF.
+    SaveGame,
+    /// Exports the finished game with the engine's evaluations and the comments as an HTML report.
+    ExportReport,
+    /// Starts or ends trying moves on a copy of the game, which is thrown away at the end.
+    ToggleTrial,
+    /// Starts composing a position from the one on the board.
+    EditPosition,
+    SelectEditTool(EditTool),
+    SetEditBlackToMove(bool),
+    ClearEdit,
+    /// Leaves the editor, starting a game from the composed position when `true`.
+    FinishEdit(bool),
+    /// Loads the opening at this index of [`OPENINGS`].
+    LoadOpening(usize),
+    /// Replaces the game with one written by another program, see [`import`].
+    ImportGame(String),
+    Tick(Instant),
+    /// The mouse moved over the point at this index, `None` when it left the board.
+    HoverBoard(Option<usize>),
+    WindowResized(u32, u32),
+    /// The window gained focus when `true` or lost it.
+    WindowFocused(bool),
+    CloseRequested,
+    /// A request of the control server waiting for its answer.
+    Rpc(rpc::Call),
+}
+
+impl Message {
+    fn from_board(event: BoardEvent) -> Self {
+        match event {
+            BoardEvent::Click(index) => Message::ClickBoard(index),
+            BoardEvent::OpenContextMenu(position, index) => Message::OpenContextMenu(position, index),
+            BoardEvent::CloseContextMenu => Message::CloseContextMenu,
+            BoardEvent::ContextMenu(action) => Message::ContextMenu(action),
+            BoardEvent::Hover(index) => Message::HoverBoard(index),
+            BoardEvent::Scrub(steps) => Message::ScrubMoves(steps),
+        }
+    }
+}
+
+/// What is shown next to the board.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum SidePanel {
+    Openings,
+    Moves,
+    /// How the engine's search of the position goes.
+    Engine,
+    /// Results of all games between named players and against the engine.
+    Scoreboard,
+    /// Games on the relay server, waiting for the player's move or the opponent's.
+    Correspondence,
+}
+
+impl SidePanel {
+    const ALL: [SidePanel; 5] = [SidePanel::Openings, SidePanel::Moves, SidePanel::Engine, SidePanel::Scoreboard, SidePanel::Correspondence];
+
+    fn name(self) -> &'static str {
+        match self {
+            SidePanel::Openings => "openings",
+            SidePanel::Moves => "moves",
+            SidePanel::Engine => "engine",
+            SidePanel::Scoreboard => "scoreboard",
+            SidePanel::Correspondence => "correspondence",
+        }
+    }
+}
+
+/// A question waiting for an answer below the board.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Prompt {
+    ConfirmResign,
+    DrawOffer,
+}
+
+/// Pictures named in the config, loaded once and shared by the boards of all tabs.
+#[derive(Clone, Default)]
+struct Pictures {
+    background: Option<Arc<Bitmap>>,
+    /// A black and a white chess.
+    stones: Option<Arc<[Bitmap; 2]>>,
+}
+
+impl Pictures {
+    const BACKGROUND_SIZE: u32 = 96;
+    const STONE_SIZE: u32 = 20;
+
+    /// The pictures named in `config`, those which cannot be loaded are left out.
+    fn from_config(config: &Config) -> Self {
+        Self {
+            background: Self::load_background(&config.background_image)
+                .unwrap_or_else(|e| { println!("No board background: {}", e); None }),
+            stones: Self::load_stones(&config.stone_pictures)
+                .unwrap_or_else(|e| { println!("No chess pictures: {}", e); None }),
+        }
+    }
+
+    /// The board background in the image file at `path`, `None` when the path is empty.
+    fn load_background(path: &str) -> Result<Option<Arc<Bitmap>>, String> {
+        if path.is_empty() {
+            return Ok(None);
+        }
+        bitmap::load(path, Self::BACKGROUND_SIZE).map(|background| Some(Arc::new(background)))
+    }
+
+    /// The chesses in `black.png` and `white.png` in the folder `dir`, `None` when it is empty.
+    fn load_stones(dir: &str) -> Result<Option<Arc<[Bitmap; 2]>>, String> {
+        if dir.is_empty() {
+            return Ok(None);
+        }
+        let load = |name: &str| bitmap::load(&std::path::Path::new(dir).join(name).to_string_lossy(), Self::STONE_SIZE);
+        Ok(Some(Arc::new([load("black.png")?, load("white.png")?])))
+    }
+}
+
+/// One open game with everything shown about it.
+struct Tab {
+    /// Tells tabs apart in messages, tabs are shown in the order of their ids.
+    id: u32,
+    game: Game,
+    board: BoardView,
+    celebration_start: Option<Instant>,
+    notice: Option<String>,
+    prompt: Option<Prompt>,
+    /// When the side to move started thinking, moved forward by the time spent paused.
+    turn_start: Instant,
+    /// Whether the low time sound already played for the side to move.
+    low_time_warned: bool,
+    /// When the game was paused, `None` while it is going on.
+    paused_at: Option<Instant>,
+    /// Whether the result of the finished game was put aside to look at the board.
+    reviewing: bool,
+    /// Search of the current position, running while the engine panel is shown.
+    analysis: Option<Analysis>,
+    /// An earlier position of the game picked in the move list, shown instead of the game's.
+    browsed: Option<Board>,
+    /// Comments written on the moves, keyed by the ply of the move.
+    comments: BTreeMap<usize, String>,
+    /// The real game, put aside while moves are tried on a copy of it.
+    trial_of: Option<Game>,
+    /// The position being composed, `None` while playing.
+    editor: Option<Editor>,
+    /// Progress through the opening rule of the game.
+    opening: OpeningProtocol,
+    engine_side: EngineSide,
+    /// The engine playing against the players, thinking while it is its turn.
+    opponent: Opponent,
+    /// The players sharing the board and their score, `None` until they give their names.
+    hotseat: Option<Hotseat>,
+    /// Names being entered for a hotseat game.
+    player_setup: Option<[String; 2]>,
+    /// Whether the result of the game was counted, so finishing it again after an undo does not
+    /// count it twice.
+    result_counted: bool,
+    /// The relay game played in this tab as the server last sent it, `None` for a game played here.
+    remote: Option<RemoteGame>,
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
-enum GameState {
-    WaitBlack,
-    WaitWhite,
-    CheckBlack,
-    CheckWhite,
+impl Tab {
+    fn new(id: u32, game: Game, config: &Config, pictures: &Pictures) -> Self {
+        let mut board = BoardView::default();
+        GomokuGame::configure_board(&mut board, config, pictures);
+        board.set_forbidden(GomokuGame::forbidden_points(&game));
+        if let Some(line) = game.winning_line() {
+            board.set_winning_line(line.to_vec());
+        }
+        Self {
+            id,
+            game,
+            board,
+            celebration_start: None,
+            notice: None,
+            prompt: None,
+            turn_start: Instant::now(),
+            low_time_warned: false,
+            paused_at: None,
+            reviewing: false,
+            analysis: None,
+            browsed: None,
+            comments: BTreeMap::new(),
+            trial_of: None,
+            editor: None,
+            opening: OpeningProtocol::new(OpeningRule::Free),
+            engine_side: EngineSide::Off,
+            opponent: Opponent::new(),
+            hotseat: None,
+            player_setup: None,
+            result_counted: false,
+            remote: None,
+        }
+    }
+
+    fn title(&self) -> String {
+        match &self.remote {
+            Some(remote) => format!("vs {}", remote.opponent),
+            None => format!("Game {}", self.id + 1),
+        }
+    }
 }
 
 struct GomokuGame {
-    board: Board,
-    state: GameState,
+    /// The tab shown, the others wait in `background_tabs`.
+    tab: Tab,
+    background_tabs: Vec<Tab>,
+    next_tab_id: u32,
+    /// The shown tab while a message for a background tab is handled in it, see [`Self::in_tab`].
+    routed_from: Option<u32>,
+    sound: SoundPlayer,
+    config: Config,
+    show_settings: bool,
+    show_heatmap: bool,
+    side_panel: Option<SidePanel>,
+    /// Size of the window in logical pixels once it was resized.
+    window_size: Option<(u32, u32)>,
+    /// Whether the window has focus, the player is told about the opponent's moves otherwise.
+    focused: bool,
+    pictures: Pictures,
+    /// Path being entered in the settings, loaded with [`Message::LoadBackground`].
+    background_path: String,
+    /// Folder being entered in the settings, loaded with [`Message::LoadStonePictures`].
+    stone_pictures_path: String,
+    /// Folder being entered in the settings, used with [`Message::ApplySyncDir`].
+    sync_dir_path: String,
+    opening_stats: OpeningStats,
+    scoreboard: Scoreboard,
+    score_sort: ScoreColumn,
+    /// Games of the relay player, `None` until logged in.
+    remote_games: Option<Vec<RemoteGame>>,
+    /// Why the relay server could not be reached the last time it was asked.
+    relay_error: Option<String>,
+    /// Where handled messages are recorded, `None` for a replayed session.
+    event_log: Option<EventLog>,
+    /// The session played back, `None` once it finished.
+    replay: Option<Replay>,
+    /// Port the control server listens on, `None` when it is not started.
+    serve: Option<u16>,
+    /// The game of three or more players shown instead of the tabs, `None` when there is none.
+    multi: Option<Multi>,
 }
 
-impl Sandbox for GomokuGame {
+impl Application for GomokuGame {
+    type Executor = executor::Default;
     type Message = Message;
+    type Theme = Theme;
+    type Flags = Flags;
 
-    fn new() -> Self {
-        Self {
-            board: Board::default(),
-            state: GameState::WaitBlack,
+    fn new(flags: Self::Flags) -> (Self, Command<Self::Message>) {
+        // a replay starts from its own tabs and layout, not those of the last run
+        let mut session = match &flags.replay {
+            Some(replay) => replay.session.clone(),
+            None => flags.session.unwrap_or_default(),
+        };
+        // versions before tabs saved a single game under their own key
+        if session.tabs.is_empty() && flags.replay.is_none() {
+            session.tabs = storage::load(Self::SAVE_KEY).into_iter().map(|game| SavedTab { game, ..SavedTab::default() }).collect();
+        }
+        if session.tabs.is_empty() {
+            session.tabs.push(SavedTab::default());
         }
+        let shown_tab = session.shown_tab.min(session.tabs.len() - 1);
+        let config = flags.config;
+        let pictures = Pictures::from_config(&config);
+        let background_path = config.background_image.clone();
+        let stone_pictures_path = config.stone_pictures.clone();
+        let next_tab_id = session.tabs.len() as u32;
+        let mut background_tabs: Vec<Tab> = session.tabs.iter().enumerate()
+            .map(|(id, saved)| Self::restore_tab(id as u32, saved, &config, &pictures))
+            .collect();
+        let mut tab = background_tabs.remove(shown_tab);
+        if let (Some(text), None) = (&flags.import, &flags.replay) {
+            let mut game = tab.game.clone();
+            match import::load(text, &mut game) {
+                Ok(()) => {
+                    tab = Tab::new(tab.id, game, &config, &pictures);
+                },
+                Err(e) => {
+                    println!("Cannot import game: {}", e);
+                    tab.notice = Some(format!("Cannot import game: {}", e));
+                },
+            }
+        }
+        let side_panel = SidePanel::ALL.into_iter().find(|panel| panel.name() == session.side_panel);
+        let analysis = (side_panel == Some(SidePanel::Engine)).then(|| Analysis::new(&tab.game));
+        tab.analysis = analysis;
+        if session.show_heatmap {
+            tab.board.set_heatmap(&Engine::new(Self::HEATMAP_DEPTH).score_moves(&tab.game));
+        }
+        let mut gomoku = Self {
+            tab,
+            background_tabs,
+            next_tab_id,
+            routed_from: None,
+            sound: SoundPlayer::default(),
+            config,
+            show_settings: session.show_settings,
+            show_heatmap: session.show_heatmap,
+            side_panel,
+            window_size: session.window_size,
+            focused: true,
+            pictures,
+            background_path,
+            stone_pictures_path,
+            sync_dir_path: storage::sync_dir().map_or(String::new(), |dir| dir.to_string_lossy().into_owned()),
+            opening_stats: OpeningStats::load(),
+            scoreboard: Scoreboard::load(),
+            score_sort: ScoreColumn::default(),
+            remote_games: None,
+            relay_error: None,
+            event_log: None,
+            replay: flags.replay,
+            serve: flags.serve,
+            multi: None,
+        };
+        match &mut gomoku.replay {
+            Some(replay) => replay.start(),
+            None => { gomoku.event_log = Some(EventLog::create(&gomoku.session())); },
+        }
+        // a player who logged in before is logged in again
+        let logged_in = !gomoku.config.relay_server.is_empty() && !gomoku.config.relay_player.is_empty();
+        let command = if logged_in && gomoku.replay.is_none() { gomoku.fetch_remote_games() } else { Command::none() };
+        (gomoku, command)
     }
 
     fn title(&self) -> String {
-        String::from("Gomoku")
+        let mode = if self.replay.is_some() { "Replay — " } else { "" };
+        format!("Gomoku — {}{} ({})", mode, self.state_text(), self.tab.game.rule_set())
     }
 
-    fn update(&mut self, message: Self::Message) {
-        let mut next_state = None;
+    fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
         match message {
-            Self::Message::ClickBoard(index) => {
-                println!("Message ClickBoard at {}, current state {:?}", index, self.state);
-                if self.board.is_empty_at(index) {
-                    match self.state {
-                        GameState::WaitBlack => {
-                            println!("Put black chess at {}", index);
-                            self.board.put_chess(index, true);
-                            next_state = Some(GameState::CheckBlack);
-                        },
-                        GameState::WaitWhite => {
-                            println!("Put white chess at {}", index);
-                            self.board.put_chess(index, false);
-                            next_state = Some(GameState::CheckWhite);
-                        },
-                        _ => ()
+            Self::Message::Tick(now) => { return self.tick(now); },
+            Self::Message::HoverBoard(index) => {
+                match &mut self.multi {
+                    Some(multi) => multi.board_mut().set_hover(index),
+                    None => self.tab.board.set_hover(index),
+                }
+                return Command::none();
+            },
+            // the moves it makes go through update as messages of their own, so they are logged
+            Self::Message::Rpc(call) => { return self.answer_rpc(call); },
+            Self::Message::WindowResized(width, height) => {
+                self.window_size = Some((width, height));
+                return Command::none();
+            },
+            Self::Message::WindowFocused(focused) => {
+                self.focused = focused;
+                return Command::none();
+            },
+            Self::Message::FetchRemoteGames => { return self.fetch_remote_games(); },
+            Self::Message::RelayGames(games) => { return self.receive_remote_games(games); },
+            Self::Message::MoveSent(id, result) => {
+                self.move_sent(id, result);
+                return Command::none();
+            },
+            Self::Message::CloseRequested => {
+                println!("Message CloseRequested, save the session");
+                self.save_session();
+                return window::close(window::Id::MAIN);
+            },
+            _ => {},
+        }
+        if self.replay.is_some() {
+            println!("Ignore {:?} while replaying", message);
+            return Command::none();
+        }
+        if let Some(event_log) = &mut self.event_log {
+            event_log.record(&message);
+        }
+        let shown_ply = self.shown_ply();
+        let moves_shown = self.side_panel == Some(SidePanel::Moves);
+        let command = self.handle(message);
+        // the engine's turn comes and goes with moves, pauses, tabs and the side it plays
+        self.update_clickable();
+        if self.side_panel == Some(SidePanel::Moves) && self.routed_from.is_none() && (self.shown_ply() != shown_ply || !moves_shown) {
+            return Command::batch([command, self.scroll_moves()]);
+        }
+        command
+    }
+
+    fn subscription(&self) -> Subscription<Self::Message> {
+        let keys = keyboard::on_key_press(|key, _modifiers| match key.as_ref() {
+            keyboard::Key::Character("h") => Some(Message::ToggleHeatmap),
+            keyboard::Key::Character("m") => Some(Message::ContextMenu(MenuAction::Mirror)),
+            keyboard::Key::Character("p") => Some(Message::TogglePause),
+            keyboard::Key::Character("r") => Some(Message::ContextMenu(MenuAction::Rotate)),
+            keyboard::Key::Character("t") => Some(Message::ToggleTrial),
+            keyboard::Key::Named(keyboard::key::Named::F12) => Some(Message::ToggleHitRegions),
+            _ => None,
+        });
+        let window_events = iced::event::listen_with(|event, _status| match event {
+            iced::Event::Window(_, window::Event::Resized { width, height }) => Some(Message::WindowResized(width, height)),
+            iced::Event::Window(_, window::Event::CloseRequested) => Some(Message::CloseRequested),
+            iced::Event::Window(_, window::Event::Focused) => Some(Message::WindowFocused(true)),
+            iced::Event::Window(_, window::Event::Unfocused) => Some(Message::WindowFocused(false)),
+            _ => None,
+        });
+        let refresh = if self.remote_games.is_some() { relay::refresh(Message::FetchRemoteGames) } else { Subscription::none() };
+        let rpc = self.serve.map_or(Subscription::none(), |port| rpc::subscription(port).map(Message::Rpc));
+        let keys = Subscription::batch([keys, window_events, refresh, rpc]);
+        let replaying = self.replay.is_some();
+        let analyzing = self.tab.analysis.as_ref().is_some_and(|analysis| !analysis.is_finished());
+        let engine_thinking = self.multi.is_none()
+            && std::iter::once(&self.tab).chain(&self.background_tabs).any(Self::engine_to_move_in);
+        if self.tab.celebration_start.is_some() || replaying || analyzing || engine_thinking
+            || (self.move_timed() && !self.tab.low_time_warned)
+            || self.tab.board.is_hover_gliding() || self.multi.as_ref().is_some_and(Multi::is_hover_gliding) {
+            Subscription::batch([keys, window::frames().map(Message::Tick)])
+        } else {
+            keys
+        }
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.config.ui_scale.factor()
+    }
+
+    fn view(&self) -> Element<'_, Self::Message> {
+       if let Some(multi) = &self.multi {
+           return self.multi_view(multi);
+       }
+       let opening = Opening::of(self.tab.game.board())
+           .filter(|_| self.tab.game.setup_plies() == 0 && self.tab.game.board().ply() <= Self::OPENING_NAME_PLIES);
+       let paused = self.tab.paused_at.is_some();
+       let playing = !paused && !self.tab.game.state().is_over();
+       let engine_thinking = self.engine_to_move();
+       let in_opening = !self.tab.opening.is_done();
+       let portrait = self.is_portrait();
+       let status = row![
+           text(self.status_text()),
+           text(self.captures_text()),
+           text(opening.map_or(String::new(), |opening| format!("{} opening", opening.name))),
+       ].spacing(10);
+       let actions = row![
+           text("Rules"),
+           pick_list(RuleSet::ALL, Some(self.tab.game.rule_set()), Message::SelectRules),
+           text("Opening"),
+           pick_list(OpeningRule::ALL, Some(self.tab.opening.rule()), Message::SelectOpeningRule),
+           button(if paused { "Resume (P)" } else { "Pause (P)" }).on_press_maybe((paused || playing).then_some(Message::TogglePause)),
+           button(if self.tab.trial_of.is_some() { "Back to game (T)" } else { "Try moves (T)" })
+               .on_press_maybe((!paused && !in_opening).then_some(Message::ToggleTrial)),
+           button("Edit").on_press_maybe((!paused && !in_opening).then_some(Message::EditPosition)),
+           button("Offer draw").on_press_maybe((playing && !engine_thinking).then_some(Message::OfferDraw)),
+           button("Resign").on_press_maybe((playing && !engine_thinking).then_some(Message::Resign)),
+           button("Players").on_press_maybe((!paused).then_some(Message::SetupPlayers)),
+           button("Quick start").on_press_maybe((!paused).then_some(Message::QuickStart)),
+           button("Moves").on_press(Message::ToggleMoves),
+           button("Engine").on_press(Message::ToggleEngine),
+           button("Openings").on_press(Message::ToggleOpenings),
+           button("Scores").on_press(Message::ToggleScoreboard),
+           button("Online").on_press(Message::ToggleCorrespondence),
+           button("3+ players").on_press_maybe((!paused).then_some(Message::SetupMulti)),
+           button("Settings").on_press(Message::ToggleSettings),
+       ].spacing(10).align_items(iced::Alignment::Center);
+       let controls: Element<'_, Message> = if portrait {
+           column![status, Self::sideways(actions)].spacing(10).padding(10).into()
+       } else {
+           row![status, horizontal_space(), actions].spacing(10).padding(10).align_items(iced::Alignment::Center).into()
+       };
+       let shown = match (&self.tab.editor, &self.tab.browsed) {
+           (Some(editor), _) => editor.board(),
+           (None, Some(browsed)) => browsed,
+           (None, None) => self.tab.game.board(),
+       };
+       let board: Element<'_, Message> = match self.side_panel {
+           // the position stays hidden during a pause so the break cannot be used to think
+           _ if paused => container(text("Paused"))
+               .width(Length::Fill).height(Length::Fill).center_x().center_y().into(),
+           _ if self.tab.editor.is_some() => self.tab.board.view(shown, Message::from_board),
+            Some(SidePanel::Openings) => self.beside_board(shown, self.openings_view()),
+            Some(SidePanel::Moves) => self.beside_board(shown, self.moves_view()),
+            Some(SidePanel::Scoreboard) => self.beside_board(shown, self.scoreboard_view()),
+            Some(SidePanel::Correspondence) => self.beside_board(shown, self.correspondence_view()),
+           Some(SidePanel::Engine) => match &self.tab.analysis {
+               Some(analysis) if portrait => self.beside_board(shown, row![analysis.view(&self.tab.game), self.opponent_view()].into()),
+               Some(analysis) => self.beside_board(shown, column![analysis.view(&self.tab.game), self.opponent_view()].into()),
+               None => self.tab.board.view(shown, Message::from_board),
+           },
+           None => self.tab.board.view(shown, Message::from_board),
+       };
+       let mut content = match &self.tab.editor {
+           Some(editor) => column![board, row![
+               text("Edit position"),
+               horizontal_space(),
+               text("Put"),
+               pick_list(EditTool::ALL, Some(editor.tool), Message::SelectEditTool),
+               checkbox("Black to move", editor.next() == ChessColor::Black).on_toggle(Message::SetEditBlackToMove),
+               button("Clear").on_press(Message::ClearEdit),
+               button("Play").on_press(Message::FinishEdit(true)),
+               button("Cancel").on_press(Message::FinishEdit(false)),
+           ].spacing(10).padding(10).align_items(iced::Alignment::Center)],
+           None => column![board, controls],
+       };
+       if let Some(names) = &self.tab.player_setup {
+           let ready = names.iter().all(|name| !name.trim().is_empty());
+           content = content.push(row![
+               text("Players"),
+               text_input("First player", &names[0]).on_input(|name| Message::EditPlayerName(0, name)).width(180),
+               text_input("Second player", &names[1]).on_input(|name| Message::EditPlayerName(1, name)).width(180),
+               button("Start").on_press_maybe(ready.then_some(Message::FinishPlayerSetup(true))),
+               button("Cancel").on_press(Message::FinishPlayerSetup(false)),
+           ].spacing(10).padding(10).align_items(iced::Alignment::Center));
+       }
+       if playing && self.tab.editor.is_none() {
+           let side = if self.tab.game.state() == GameState::Wait(ChessColor::Black) { "Black" } else { "White" };
+           match self.tab.opening.step() {
+               Some(Step::Swap) => {
+                   content = content.push(row![
+                       text(format!("{} may swap colors", side)),
+                       button("Swap").on_press(Message::AnswerSwap(true)),
+                       button("Keep").on_press(Message::AnswerSwap(false)),
+                   ].spacing(10).padding(10).align_items(iced::Alignment::Center));
+               },
+               Some(Step::Declare { max }) => {
+                   let counts = (1..=max).fold(Row::new().spacing(6), |counts, count| {
+                       counts.push(button(text(count)).on_press(Message::DeclareOffers(count)))
+                   });
+                   content = content.push(row![
+                       text(format!("{} declares the fifth moves black offers", side)),
+                       counts,
+                   ].spacing(10).padding(10).align_items(iced::Alignment::Center));
+               },
+               _ => {},
+           }
+       }
+       if let Some(prompt) = self.tab.prompt {
+           let (side, other) = if self.tab.game.state() == GameState::Wait(ChessColor::Black) { ("Black", "White") } else { ("White", "Black") };
+           let question = match prompt {
+               Prompt::ConfirmResign => row![
+                   text(format!("Resign as {}?", side)),
+                   button("Resign").on_press(Message::ConfirmResign(true)),
+                   button("Cancel").on_press(Message::ConfirmResign(false)),
+               ],
+               Prompt::DrawOffer => row![
+                   text(format!("{} offers a draw, {} accepts?", side, other)),
+                   button("Accept").on_press(Message::AnswerDraw(true)),
+                   button("Decline").on_press(Message::AnswerDraw(false)),
+               ],
+           };
+           content = content.push(question.spacing(10).padding(10).align_items(iced::Alignment::Center));
+       }
+       if self.tab.game.state().is_over() && !self.tab.reviewing && self.tab.editor.is_none() {
+           content = content.push(container(row![
+               text(self.state_text()).size(20),
+               horizontal_space(),
+               button("New game").on_press(Message::NewGame),
+               button("Quick start").on_press(Message::QuickStart),
+               button("Review").on_press(Message::ReviewGame),
+               button("Save").on_press(Message::SaveGame),
+               button("Report").on_press(Message::ExportReport),
+           ].spacing(10).align_items(iced::Alignment::Center)).padding(10).style(iced::theme::Container::Box));
+       }
+       if self.show_settings {
+           let can_log_in = !self.config.relay_server.trim().is_empty() && !self.config.relay_player.trim().is_empty();
+           let settings = column![
+               row![
+                   text("Last move marker"),
+                   pick_list(MarkerStyle::ALL, Some(self.config.marker_style), Message::SelectMarkerStyle),
+                   checkbox("High contrast", self.config.high_contrast).on_toggle(Message::ToggleHighContrast),
+                   checkbox("Heatmap (H)", self.show_heatmap).on_toggle(|_| Message::ToggleHeatmap),
+                   checkbox("Show threats", self.config.show_threats).on_toggle(Message::ToggleThreats),
+               ].spacing(10).align_items(iced::Alignment::Center),
+               row![
+                   text("Scale"),
+                   pick_list(UiScale::ALL, Some(self.config.ui_scale), Message::SelectUiScale),
+                   checkbox("Mute sounds", self.sound.is_muted()).on_toggle(Message::ToggleMute),
+                   checkbox("Face to face", self.config.auto_rotate).on_toggle(Message::ToggleAutoRotate),
+                   checkbox("Flash taskbar on my turn", self.config.flash_taskbar).on_toggle(Message::ToggleFlashTaskbar),
+                   checkbox("Log games as JSON", self.config.json_game_log).on_toggle(Message::ToggleJsonGameLog),
+                   text("Animation delay"),
+                   pick_list(AnimationDelay::ALL, Some(self.config.animation_delay), Message::SelectAnimationDelay),
+                   text("Click snap"),
+                   pick_list(ClickSnap::ALL, Some(self.config.click_snap), Message::SelectClickSnap),
+               ].spacing(10).align_items(iced::Alignment::Center),
+               row![
+                   text("Quick start"),
+                   checkbox("Draw who plays black", self.config.random_colors).on_toggle(Message::ToggleRandomColors),
+                   text("Start from"),
+                   pick_list(StartPosition::ALL, Some(self.config.start_position), Message::SelectStartPosition),
+                   text("Time per move"),
+                   pick_list(MoveTime::ALL, Some(self.config.move_time), Message::SelectMoveTime),
+               ].spacing(10).align_items(iced::Alignment::Center),
+               row![
+                   text("Relay server"),
+                   text_input("host:port", &self.config.relay_server).on_input(Message::EditRelayServer).width(200),
+                   text("Name"),
+                   text_input("Player", &self.config.relay_player).on_input(Message::EditRelayPlayer).width(150),
+                   button("Log in").on_press_maybe(can_log_in.then_some(Message::LogIn)),
+               ].spacing(10).align_items(iced::Alignment::Center),
+               row![
+                   text("Board background"),
+                   text_input("Image file, empty for none", &self.background_path)
+                       .on_input(Message::EditBackgroundPath)
+                       .on_submit(Message::LoadBackground)
+                       .width(360),
+                   button("Apply").on_press(Message::LoadBackground),
+               ].spacing(10).align_items(iced::Alignment::Center),
+               row![
+                   text("Stones"),
+                   pick_list(StoneSkin::ALL, Some(self.config.stone_skin), Message::SelectStoneSkin),
+                   text_input("Folder with black.png and white.png", &self.stone_pictures_path)
+                       .on_input(Message::EditStonePictures)
+                       .on_submit(Message::LoadStonePictures)
+                       .width(360),
+                   button("Apply").on_press(Message::LoadStonePictures),
+               ].spacing(10).align_items(iced::Alignment::Center),
+               row![
+                   text("Sync folder"),
+                   text_input("Folder shared with other machines, empty for none", &self.sync_dir_path)
+                       .on_input(Message::EditSyncDir)
+                       .on_submit(Message::ApplySyncDir)
+                       .width(360),
+                   button("Apply").on_press(Message::ApplySyncDir),
+               ].spacing(10).align_items(iced::Alignment::Center),
+           ].spacing(10).padding(10);
+           content = content.push(if portrait { Self::sideways(settings) } else { settings.into() });
+       }
+       let mut page = column![self.tab_bar()];
+       if let Some(hotseat) = &self.tab.hotseat {
+           let (black, white) = hotseat.sides(self.tab.opening.first_player_black());
+           page = page.push(row![
+               text(hotseat.score_text()).size(20),
+               horizontal_space(),
+               text(format!("{} plays black, {} plays white", black, white)),
+           ].spacing(10).padding([0, 10]).align_items(iced::Alignment::Center));
+       }
+       container(page.push(content)).into()
+    }
+}
+
+impl GomokuGame {
+    const CELEBRATION_DURATION: Duration = Duration::from_millis(1500);
+    const HINT_DEPTH: u32 = 2;
+    /// Depth the engine searches to answer a draw offer, the window waits for the answer.
+    const DRAW_OFFER_DEPTH: u32 = 3;
+    /// Time left to move when the low time sound plays.
+    const LOW_TIME: Duration = Duration::from_secs(10);
+    const HEATMAP_DEPTH: u32 = 2;
+    /// Depth the report searches after every move which could have been played, the whole game
+    /// is reviewed at once so it stays shallow.
+    const REPORT_DEPTH: u32 = 2;
+    /// Deepest search the control server runs, the window does not respond meanwhile.
+    const RPC_MAX_DEPTH: u32 = 6;
+    const SAVE_KEY: &'static str = "current_game";
+    /// The opening is named until this many chesses are on the board.
+    const OPENING_NAME_PLIES: usize = 10;
+    /// Window size at 100% scale, fitting the board and the controls below it.
+    const WINDOW_SIZE: iced::Size = iced::Size::new(860.0, 900.0);
+
+    /// Handles a message from the user or from a replayed session.
+    fn handle(&mut self, message: Message) -> Command<Message> {
+        let mut command = Command::none();
+        if let Message::ToTab(id, message) = message {
+            if matches!(*message, Message::ToTab(..)) || Self::is_about_tabs(&message) {
+                println!("Ignore {:?} sent to tab {}", message, id);
+                return command;
+            }
+            return self.in_tab(id, |this| this.handle(*message)).unwrap_or_else(|| {
+                println!("Ignore a message to tab {}, which was closed", id);
+                Command::none()
+            });
+        }
+        if self.tab.paused_at.is_some() && !Self::allowed_while_paused(&message) {
+            println!("Ignore {:?} while paused", message);
+            return command;
+        }
+        if self.multi.is_some() && !Self::is_about_multi(&message) && !Self::is_setting(&message) {
+            println!("Ignore {:?} during the game of three or more", message);
+            return command;
+        }
+        if self.tab.editor.is_some() && !Self::allowed_while_editing(&message) {
+            println!("Ignore {:?} while editing", message);
+            return command;
+        }
+        if !self.tab.opening.is_done() && !Self::allowed_during_opening(&message) {
+            println!("Ignore {:?} during the opening", message);
+            return command;
+        }
+        if self.tab.remote.is_some() && self.tab.trial_of.is_none() && !Self::allowed_in_remote_game(&message) {
+            println!("Ignore {:?} in a relay game", message);
+            self.tab.notice = Some(String::from("Not possible in a game on the relay server"));
+            return command;
+        }
+        match message {
+            Message::ClickBoard(index) if self.tab.editor.is_some() => {
+                if let Some(editor) = &mut self.tab.editor {
+                    editor.click(index);
+                }
+                self.tab.board.chesses_changed();
+            },
+            Message::ClickBoard(index) if self.tab.game.state().is_over() => {
+                println!("Ignore ClickBoard at {}, the game is over", index);
+            },
+            Message::ClickBoard(index) if self.engine_to_move() => {
+                println!("Ignore ClickBoard at {}, the engine is to move", index);
+                self.tab.notice = Some(String::from("Wait for the engine to move"));
+            },
+            Message::EngineMove(index) if !self.engine_to_move() => {
+                println!("Ignore EngineMove at {}, the engine is not to move", index);
+            },
+            Message::ClickBoard(index) if !self.tab.opening.is_done() => {
+                println!("Message ClickBoard at {} during the opening, step {:?}", index, self.tab.opening.step());
+                let is_black = self.tab.game.state() == GameState::Wait(ChessColor::Black);
+                let ply = self.tab.game.board().ply();
+                match self.tab.opening.click(&mut self.tab.game, index) {
+                    Ok(()) => {
+                        self.tab.notice = None;
+                        self.tab.board.set_offers(self.tab.opening.offers().to_vec());
+                        if self.tab.game.board().ply() > ply {
+                            self.chesses_changed();
+                            self.autosave();
+                            self.sound.play(if is_black { SoundEffect::PutBlack } else { SoundEffect::PutWhite });
+                        }
+                    },
+                    Err(e) => {
+                        println!("Cannot click at {} during the opening: {}", index, e);
+                        self.tab.notice = Some(format!("Not there: {}", e));
+                    },
+                }
+            },
+            Message::ClickBoard(index) if self.remote_opponent_to_move().is_some() => {
+                println!("Ignore ClickBoard at {}, the relay opponent is to move", index);
+                self.tab.notice = self.remote_opponent_to_move().map(|opponent| format!("Waiting for the move of {}", opponent));
+            },
+            Message::ClickBoard(index) | Message::EngineMove(index) => {
+                println!("Message {:?}, current state {:?}", message, self.tab.game.state());
+                let is_black = self.tab.game.state() == GameState::Wait(ChessColor::Black);
+                match self.tab.game.put_chess_timed(index, self.tab.turn_start.elapsed()) {
+                    Ok(state) => {
+                        println!("Put {} chess at {}", if is_black { "black" } else { "white" }, index);
+                        self.tab.notice = None;
+                        self.chesses_changed();
+                        self.autosave();
+                        self.sound.play(if is_black { SoundEffect::PutBlack } else { SoundEffect::PutWhite });
+                        self.on_state_changed(state);
+                        command = self.send_move(index);
+                    },
+                    Err(e) => {
+                        println!("Cannot put chess at {}: {}", index, e);
+                        self.tab.notice = Some(format!("Cannot put chess there: {}", e));
+                    },
+                }
+            },
+            Message::OpenContextMenu(position, index) => {
+                println!("Message OpenContextMenu at {}, index {:?}", position, index);
+                self.tab.board.open_context_menu(position, index);
+            },
+            Message::CloseContextMenu => {
+                self.tab.board.close_context_menu();
+            },
+            Message::ContextMenu(action) => {
+                println!("Message ContextMenu {:?}, current state {:?}", action, self.tab.game.state());
+                let index = self.tab.board.close_context_menu();
+                match action {
+                    MenuAction::Undo if !self.tab.opening.is_done() => {
+                        self.tab.notice = Some(String::from("Moves of the opening cannot be taken back"));
+                    },
+                    MenuAction::Undo => {
+                        if self.tab.game.undo() {
+                            // take back the engine's reply as well so it is the player's turn again
+                            if self.engine_to_move() {
+                                self.tab.game.undo();
+                            }
+                            self.tab.notice = None;
+                            self.chesses_changed();
+                            self.autosave();
+                            self.tab.celebration_start = None;
+                        }
+                    },
+                    MenuAction::Hint => {
+                        let hint = Engine::new(Self::HINT_DEPTH).search(&self.tab.game).best_move;
+                        println!("Hint for {:?} is {:?}", self.tab.game.state(), hint);
+                        self.tab.board.set_hint(hint);
+                    },
+                    MenuAction::CopyPosition => {
+                        command = clipboard::write(self.tab.game.board().move_list());
+                    },
+                    MenuAction::CopyTranscript => {
+                        command = clipboard::write(transcript::write(&self.tab.game));
+                    },
+                    MenuAction::MarkPoint => {
+                        if let Some(index) = index {
+                            self.tab.board.toggle_mark(index);
+                        }
+                    },
+                    MenuAction::ExportAnimation => {
+                        let mut gif = Vec::new();
+                        let result = animation::write_gif(&self.tab.game, self.config.animation_delay.0, &mut gif)
+                            .and_then(|_| storage::export(&format!("gomoku-{}-moves.gif", self.tab.game.board().ply()), &gif));
+                        self.tab.notice = Some(match result {
+                            Ok(path) => format!("Saved animation to {}", path.display()),
+                            Err(e) => format!("Cannot export animation: {}", e),
+                        });
+                    },
+                    MenuAction::Rotate => {
+                        self.tab.board.set_orientation(self.tab.board.orientation().rotated());
+                    },
+                    MenuAction::Mirror => {
+                        self.tab.board.set_orientation(self.tab.board.orientation().flipped());
+                    },
+                    MenuAction::PasteGame => {
+                        let id = self.tab.id;
+                        command = clipboard::read(move |text| Message::ToTab(id, Box::new(Message::ImportGame(text.unwrap_or_default()))));
+                    },
+                };
+            },
+            Message::ToggleMute(muted) => {
+                self.sound.set_muted(muted);
+            },
+            Message::SelectRules(rule_set) => {
+                println!("Message SelectRules {}, start a new game", rule_set);
+                self.new_game(rule_set);
+            },
+            Message::SelectOpeningRule(rule) => {
+                println!("Message SelectOpeningRule {}, start a new game", rule);
+                self.tab.opening = OpeningProtocol::new(rule);
+                self.new_game(self.tab.game.rule_set());
+            },
+            Message::AnswerSwap(swap) => {
+                println!("Message AnswerSwap {}, step {:?}", swap, self.tab.opening.step());
+                if let Err(e) = self.tab.opening.swap(swap) {
+                    println!("Cannot answer the swap: {}", e);
+                }
+            },
+            Message::DeclareOffers(count) => {
+                println!("Message DeclareOffers {}, step {:?}", count, self.tab.opening.step());
+                if let Err(e) = self.tab.opening.declare(count) {
+                    println!("Cannot declare {} offers: {}", count, e);
+                }
+            },
+            Message::NewTab => {
+                let id = self.next_tab_id;
+                self.next_tab_id += 1;
+                println!("Message NewTab, open tab {}", id);
+                let game = Game::new(Board::default(), self.tab.game.rule_set());
+                let mut tab = Tab::new(id, game, &self.config, &self.pictures);
+                tab.opening = OpeningProtocol::new(self.tab.opening.rule());
+                let tab = std::mem::replace(&mut self.tab, tab);
+                self.background_tabs.push(tab);
+                self.update_analysis();
+            },
+            Message::SelectTab(id) => {
+                println!("Message SelectTab {}", id);
+                self.select_tab(id);
+            },
+            Message::CloseTab(id) => {
+                if self.background_tabs.is_empty() {
+                    return command;
+                }
+                println!("Message CloseTab {}", id);
+                if id == self.tab.id {
+                    // show the neighbour after the closed tab, or before it when it was the last
+                    let titles = self.tab_titles();
+                    let i = titles.iter().position(|&(tab_id, _, _)| tab_id == id).unwrap_or(0);
+                    let neighbour = titles.get(i + 1).or(titles.get(i.wrapping_sub(1))).map(|&(tab_id, _, _)| tab_id);
+                    if let Some(neighbour) = neighbour {
+                        self.select_tab(neighbour);
+                    }
+                }
+                self.background_tabs.retain(|tab| tab.id != id);
+                self.autosave();
+            },
+            Message::NewGame => {
+                println!("Message NewGame");
+                self.new_game(self.tab.game.rule_set());
+            },
+            Message::SetupPlayers => {
+                let names = match &self.tab.hotseat {
+                    Some(hotseat) => hotseat.names().clone(),
+                    None => [String::from("Player 1"), String::from("Player 2")],
+                };
+                self.tab.player_setup = Some(names);
+            },
+            Message::EditPlayerName(i, name) => {
+                if let Some(names) = self.tab.player_setup.as_mut() {
+                    names[i.min(1)] = name;
+                }
+            },
+            Message::FinishPlayerSetup(start) => {
+                println!("Message FinishPlayerSetup {}", start);
+                let Some(names) = self.tab.player_setup.take() else { return command; };
+                if start {
+                    self.tab.engine_side = EngineSide::Off;
+                    self.tab.hotseat = None;
+                    self.new_game(self.tab.game.rule_set());
+                    self.tab.hotseat = Some(Hotseat::new(names.map(|name| name.trim().to_string())));
+                }
+            },
+            Message::ReviewGame => {
+                self.tab.reviewing = true;
+                self.side_panel = Some(SidePanel::Moves);
+                self.update_analysis();
+            },
+            Message::SaveGame => {
+                let result = storage::export(&format!("gomoku-{}-moves.sgf", self.tab.game.board().ply()), sgf::write(&self.tab.game).as_bytes());
+                self.tab.notice = Some(match result {
+                    Ok(path) => format!("Saved game to {}", path.display()),
+                    Err(e) => format!("Cannot save game: {}", e),
+                });
+            },
+            Message::ExportReport => {
+                println!("Message ExportReport, review {} moves", self.tab.game.board().ply());
+                let reviews = report::review(&self.tab.game, Self::REPORT_DEPTH);
+                let html = report::write(&self.tab.game, &reviews, &self.tab.comments);
+                let result = storage::export(&format!("gomoku-{}-moves.html", self.tab.game.board().ply()), html.as_bytes());
+                self.tab.notice = Some(match result {
+                    Ok(path) => format!("Saved report to {}", path.display()),
+                    Err(e) => format!("Cannot save report: {}", e),
+                });
+            },
+            Message::ToggleSettings => {
+                self.show_settings = !self.show_settings;
+            },
+            Message::SelectMarkerStyle(style) => {
+                self.config.marker_style = style;
+                self.tab.board.set_marker_style(style);
+                self.save_config();
+            },
+            Message::ToggleHighContrast(high_contrast) => {
+                self.config.high_contrast = high_contrast;
+                self.tab.board.set_high_contrast(high_contrast);
+                self.save_config();
+            },
+            Message::ToggleThreats(show) => {
+                println!("Message ToggleThreats {}", show);
+                self.config.show_threats = show;
+                self.update_threats();
+                self.save_config();
+            },
+            Message::ToggleAutoRotate(auto_rotate) => {
+                println!("Message ToggleAutoRotate {}", auto_rotate);
+                self.config.auto_rotate = auto_rotate;
+                self.update_half_turn();
+                self.save_config();
+            },
+            Message::ToggleFlashTaskbar(flash) => {
+                self.config.flash_taskbar = flash;
+                self.save_config();
+            },
+            Message::ToggleJsonGameLog(log) => {
+                self.config.json_game_log = log;
+                self.save_config();
+            },
+            Message::EditBackgroundPath(path) => {
+                self.background_path = path;
+            },
+            Message::LoadBackground => {
+                let path = self.background_path.trim().to_string();
+                println!("Message LoadBackground \"{}\"", path);
+                match Pictures::load_background(&path) {
+                    Ok(background) => {
+                        self.pictures.background = background;
+                        self.tab.board.set_background(self.pictures.background.clone());
+                        self.config.background_image = path;
+                        self.save_config();
+                    },
+                    Err(e) => { self.tab.notice = Some(format!("Cannot load background: {}", e)); },
+                }
+            },
+            Message::SelectStoneSkin(skin) => {
+                println!("Message SelectStoneSkin {}", skin);
+                self.config.stone_skin = skin;
+                self.tab.board.set_stone_skin(skin);
+                self.save_config();
+            },
+            Message::EditStonePictures(path) => {
+                self.stone_pictures_path = path;
+            },
+            Message::LoadStonePictures => {
+                let path = self.stone_pictures_path.trim().to_string();
+                println!("Message LoadStonePictures \"{}\"", path);
+                match Pictures::load_stones(&path) {
+                    Ok(stones) => {
+                        self.pictures.stones = stones;
+                        self.tab.board.set_stone_pictures(self.pictures.stones.clone());
+                        self.config.stone_pictures = path;
+                        self.save_config();
+                    },
+                    Err(e) => { self.tab.notice = Some(format!("Cannot load chess pictures: {}", e)); },
+                }
+            },
+            Message::EditSyncDir(path) => {
+                self.sync_dir_path = path;
+            },
+            Message::ApplySyncDir => {
+                let path = self.sync_dir_path.trim().to_string();
+                println!("Message ApplySyncDir \"{}\"", path);
+                if self.replay.is_some() {
+                    return Command::none();
+                }
+                if let Err(e) = storage::set_sync_dir(&path) {
+                    self.tab.notice = Some(format!("Cannot use the sync folder: {}", e));
+                    return Command::none();
+                }
+                // what another machine already put in the folder wins over what was used here
+                self.config = Config::load();
+                self.pictures = Pictures::from_config(&self.config);
+                self.background_path = self.config.background_image.clone();
+                self.stone_pictures_path = self.config.stone_pictures.clone();
+                self.scoreboard = Scoreboard::load();
+                self.opening_stats = OpeningStats::load();
+                Self::configure_board(&mut self.tab.board, &self.config, &self.pictures);
+                self.update_threats();
+                self.update_half_turn();
+                self.tab.notice = Some(if path.is_empty() {
+                    String::from("Settings and scores are kept on this machine")
+                } else {
+                    format!("Settings and scores are kept in {}", path)
+                });
+            },
+            Message::SelectMoveTime(time) => {
+                println!("Message SelectMoveTime {}", time);
+                self.config.move_time = time;
+                self.save_config();
+            },
+            Message::SelectUiScale(scale) => {
+                println!("Message SelectUiScale {}", scale);
+                self.config.ui_scale = scale;
+                self.save_config();
+            },
+            Message::SelectAnimationDelay(delay) => {
+                self.config.animation_delay = delay;
+                self.save_config();
+            },
+            Message::SelectClickSnap(snap) => {
+                self.config.click_snap = snap;
+                self.tab.board.set_click_snap(snap);
+                self.save_config();
+            },
+            Message::ToggleRandomColors(random) => {
+                self.config.random_colors = random;
+                self.save_config();
+            },
+            Message::SelectStartPosition(position) => {
+                self.config.start_position = position;
+                self.save_config();
+            },
+            Message::QuickStart => {
+                let random = split_mix(self.tab.turn_start.elapsed().as_nanos() as u64);
+                let swap = self.config.random_colors && random & 1 == 1;
+                let opening = match self.config.start_position {
+                    StartPosition::Empty => None,
+                    StartPosition::RandomOpening => Some((random >> 1) as usize % OPENINGS.len()),
+                    StartPosition::Opening(i) => Some(i),
+                };
+                return self.update(Message::StartGame(swap, opening));
+            },
+            Message::StartGame(swap, opening) => {
+                println!("Message StartGame, swap sides {}, opening {:?}", swap, opening);
+                self.new_game(self.tab.game.rule_set());
+                if swap {
+                    self.tab.engine_side = match self.tab.engine_side {
+                        EngineSide::Black => EngineSide::White,
+                        EngineSide::White => EngineSide::Black,
+                        EngineSide::Off => EngineSide::Off,
                     };
+                    if let Some(hotseat) = self.tab.hotseat.as_mut() {
+                        hotseat.next_game();
+                    }
                 }
+                match opening.and_then(|i| OPENINGS.get(i)) {
+                    // the opening rule has the players put the first chesses themselves
+                    Some(opening) if !self.tab.opening.is_done() => {
+                        self.tab.notice = Some(format!("{} is not put, the opening rule puts the first chesses", opening.name));
+                    },
+                    Some(opening) => { self.put_opening(opening); },
+                    None => {},
+                }
+            },
+            Message::ToggleHitRegions => {
+                self.config.show_hit_regions = !self.config.show_hit_regions;
+                println!("Message ToggleHitRegions, show hit regions {}", self.config.show_hit_regions);
+                self.tab.board.set_show_hit_regions(self.config.show_hit_regions);
+                self.save_config();
+            },
+            Message::ToggleHeatmap => {
+                self.show_heatmap = !self.show_heatmap;
+                println!("Message ToggleHeatmap, show heatmap {}", self.show_heatmap);
+                self.update_heatmap();
             },
+            Message::ToggleOpenings => {
+                self.toggle_side_panel(SidePanel::Openings);
+            },
+            Message::ToggleScoreboard => {
+                self.toggle_side_panel(SidePanel::Scoreboard);
+            },
+            Message::SortScoreboard(column) => {
+                self.score_sort = column;
+            },
+            Message::ToggleCorrespondence => {
+                self.toggle_side_panel(SidePanel::Correspondence);
+            },
+            Message::EditRelayServer(server) => {
+                self.config.relay_server = server;
+            },
+            Message::EditRelayPlayer(player) => {
+                self.config.relay_player = player;
+            },
+            Message::LogIn => {
+                if self.replay.is_some() {
+                    println!("Ignore LogIn while replaying");
+                    return command;
+                }
+                self.config.relay_server = self.config.relay_server.trim().to_string();
+                self.config.relay_player = self.config.relay_player.trim().to_string();
+                println!("Message LogIn as {} to {}", self.config.relay_player, self.config.relay_server);
+                self.save_config();
+                command = self.fetch_remote_games();
+            },
+            Message::OpenRemoteGame(id) => {
+                let Some(remote) = self.remote_games.iter().flatten().find(|game| game.id == id).cloned() else {
+                    println!("Ignore OpenRemoteGame {}, there is no such game", id);
+                    return command;
+                };
+                println!("Message OpenRemoteGame {} against {}", id, remote.opponent);
+                let open = std::iter::once(&self.tab).chain(&self.background_tabs)
+                    .find(|tab| tab.remote.as_ref().is_some_and(|open| open.id == id))
+                    .map(|tab| tab.id);
+                if let Some(tab_id) = open {
+                    self.select_tab(tab_id);
+                    return command;
+                }
+                let mut game = Game::new(Board::default(), remote.rule_set);
+                if let Err(e) = game.play_moves(&remote.moves) {
+                    self.tab.notice = Some(format!("Cannot open the game against {}: {}", remote.opponent, e));
+                    return command;
+                }
+                let mut tab = Tab::new(self.next_tab_id, game, &self.config, &self.pictures);
+                self.next_tab_id += 1;
+                tab.remote = Some(remote);
+                let tab = std::mem::replace(&mut self.tab, tab);
+                self.background_tabs.push(tab);
+                self.update_analysis();
+            },
+            Message::SetupMulti => {
+                println!("Message SetupMulti");
+                self.multi = Some(Multi::new());
+            },
+            Message::SelectMultiPlayers(count) => {
+                if let Some(multi) = &mut self.multi {
+                    multi.set_players(count.clamp(MIN_PLAYERS, MAX_PLAYERS));
+                }
+            },
+            Message::SelectMultiColor(player, color) => {
+                if let Some(multi) = &mut self.multi {
+                    multi.set_color(player, color);
+                }
+            },
+            Message::StartMulti => {
+                if let Some(multi) = self.multi.as_mut().filter(|multi| multi.colors_differ()) {
+                    println!("Message StartMulti with {:?}", multi.colors());
+                    multi.start();
+                    Self::configure_board(multi.board_mut(), &self.config, &self.pictures);
+                }
+            },
+            Message::ClickMulti(index) => {
+                let Some(multi) = &mut self.multi else { return command; };
+                match multi.put_chess(index) {
+                    Ok(state) => {
+                        println!("Message ClickMulti at {}, {}", index, multi.state_text());
+                        self.sound.play(match state {
+                            GameState::Win(_) => SoundEffect::Win,
+                            GameState::Draw => SoundEffect::Draw,
+                            GameState::Wait(_) => SoundEffect::PutBlack,
+                        });
+                    },
+                    Err(e) => { println!("Cannot put chess at {}: {}", index, e); },
+                }
+            },
+            Message::UndoMulti => {
+                if let Some(multi) = &mut self.multi {
+                    multi.undo();
+                }
+            },
+            Message::LeaveMulti => {
+                println!("Message LeaveMulti");
+                self.multi = None;
+            },
+            Message::SelectEngineSide(side) => {
+                println!("Message SelectEngineSide {}", side);
+                self.tab.engine_side = side;
+                self.tab.opponent.stop();
+            },
+            Message::SetEngineTime(millis) => {
+                self.config.engine_time = EngineTime(millis.clamp(EngineTime::MIN, EngineTime::MAX));
+                self.save_config();
+            },
+            Message::SelectEngineStyle(style) => {
+                println!("Message SelectEngineStyle {}", style);
+                self.config.engine_style = style;
+                self.save_config();
+            },
+            Message::Resign | Message::OfferDraw if self.engine_to_move() => {
+                println!("Ignore {:?}, the engine is to move", message);
+            },
+            Message::Resign => {
+                self.tab.prompt = (!self.tab.game.state().is_over()).then_some(Prompt::ConfirmResign);
+            },
+            Message::ConfirmResign(confirmed) => {
+                if self.tab.prompt.take() == Some(Prompt::ConfirmResign) && confirmed {
+                    println!("Message ConfirmResign, current state {:?}", self.tab.game.state());
+                    if let Ok(state) = self.tab.game.resign() {
+                        self.tab.notice = None;
+                        self.autosave();
+                        self.on_state_changed(state);
+                    }
+                }
+            },
+            Message::OfferDraw if self.engine_answers_draw() => {
+                // the search is deterministic, so a replayed offer gets the same answer
+                let accepted = Engine::new(Self::DRAW_OFFER_DEPTH).accepts_draw(&self.tab.game);
+                println!("Message OfferDraw, the engine answers {}", accepted);
+                self.answer_draw(accepted);
+            },
+            Message::OfferDraw => {
+                self.tab.prompt = (!self.tab.game.state().is_over()).then_some(Prompt::DrawOffer);
+            },
+            Message::AnswerDraw(accepted) => {
+                if self.tab.prompt.take() != Some(Prompt::DrawOffer) {
+                    return command;
+                }
+                println!("Message AnswerDraw {}, current state {:?}", accepted, self.tab.game.state());
+                self.answer_draw(accepted);
+            },
+            Message::TogglePause => {
+                match self.tab.paused_at.take() {
+                    Some(paused_at) => {
+                        println!("Message TogglePause, resume after {:?}", paused_at.elapsed());
+                        self.tab.turn_start += paused_at.elapsed();
+                    },
+                    None if !self.tab.game.state().is_over() => {
+                        println!("Message TogglePause, pause");
+                        self.tab.paused_at = Some(Instant::now());
+                        self.tab.board.close_context_menu();
+                    },
+                    None => {},
+                }
+            },
+            Message::ToggleTrial => {
+                match self.tab.trial_of.take() {
+                    Some(game) => {
+                        println!("Message ToggleTrial, back to the game");
+                        self.tab.game = game;
+                    },
+                    None => {
+                        println!("Message ToggleTrial, try moves from {}", self.tab.game.board().move_list());
+                        self.tab.trial_of = Some(self.tab.game.clone());
+                    },
+                }
+                self.tab.notice = None;
+                self.tab.celebration_start = None;
+                self.chesses_changed();
+                if let Some(line) = self.tab.game.winning_line() {
+                    self.tab.board.set_winning_line(line.to_vec());
+                }
+            },
+            Message::EditPosition => {
+                println!("Message EditPosition");
+                self.tab.editor = Some(Editor::new(self.tab.game.board()));
+                self.tab.prompt = None;
+                self.tab.celebration_start = None;
+                self.tab.board.close_context_menu();
+                self.tab.board.chesses_changed();
+                self.tab.board.set_forbidden(vec![]);
+            },
+            Message::SelectEditTool(tool) => {
+                if let Some(editor) = &mut self.tab.editor {
+                    editor.tool = tool;
+                }
+            },
+            Message::SetEditBlackToMove(black) => {
+                if let Some(editor) = &mut self.tab.editor {
+                    editor.set_next(if black { ChessColor::Black } else { ChessColor::White });
+                }
+            },
+            Message::ClearEdit => {
+                if let Some(editor) = &mut self.tab.editor {
+                    editor.clear();
+                }
+                self.tab.board.chesses_changed();
+            },
+            Message::FinishEdit(play) => {
+                let Some(editor) = self.tab.editor.take() else { return command; };
+                println!("Message FinishEdit, play {}", play);
+                if play {
+                    self.tab.game = editor.to_game(self.tab.game.rule_set());
+                    self.forget_positions();
+                    self.tab.notice = None;
+                    self.autosave();
+                }
+                self.chesses_changed();
+                if let Some(line) = self.tab.game.winning_line() {
+                    self.tab.board.set_winning_line(line.to_vec());
+                }
+            },
+            Message::ToggleMoves => {
+                self.toggle_side_panel(SidePanel::Moves);
+            },
+            Message::ShowPly(ply) => {
+                println!("Message ShowPly {}", ply);
+                self.show_ply(ply);
+            },
+            Message::ScrubMoves(steps) => {
+                let ply = (self.shown_ply() as i64 + steps as i64).clamp(0, self.tab.game.board().ply() as i64) as usize;
+                if ply != self.shown_ply() {
+                    println!("Message ScrubMoves {}, show ply {}", steps, ply);
+                    self.show_ply(ply);
+                }
+            },
+            Message::EditComment(comment) => {
+                let Some(ply) = self.shown_ply().checked_sub(1).filter(|&ply| ply >= self.tab.game.setup_plies()) else { return command; };
+                if comment.is_empty() {
+                    self.tab.comments.remove(&ply);
+                } else {
+                    self.tab.comments.insert(ply, comment);
+                }
+            },
+            Message::ToggleEngine => {
+                self.toggle_side_panel(SidePanel::Engine);
+            },
+            Message::LoadOpening(i) => {
+                let Some(opening) = OPENINGS.get(i) else { return command; };
+                println!("Message LoadOpening {}", opening.name);
+                self.put_opening(opening);
+            },
+            Message::ImportGame(text) => {
+                println!("Message ImportGame, {} bytes", text.len());
+                let mut game = self.tab.game.clone();
+                match import::load(&text, &mut game) {
+                    Ok(()) => {
+                        self.tab.game = game;
+                        self.forget_positions();
+                        self.tab.notice = None;
+                        self.chesses_changed();
+                        self.autosave();
+                        self.tab.celebration_start = None;
+                        if let Some(line) = self.tab.game.winning_line() {
+                            self.tab.board.set_winning_line(line.to_vec());
+                        }
+                    },
+                    Err(e) => {
+                        println!("Cannot import game: {}", e);
+                        self.tab.notice = Some(format!("Cannot import game: {}", e));
+                    },
+                }
+            },
+            // routed before the match
+            Message::ToTab(..) => (),
+            Message::Tick(_) | Message::HoverBoard(_) | Message::WindowResized(..) | Message::WindowFocused(_)
+                | Message::CloseRequested | Message::FetchRemoteGames | Message::RelayGames(_) | Message::MoveSent(..)
+                | Message::Rpc(_) => (),
         };
 
-        match next_state {
-            Some(GameState::CheckBlack) => { self.state = GameState::WaitWhite; }
-            Some(GameState::CheckWhite) => { self.state = GameState::WaitBlack; }
-            _ => ()
+        command
+    }
+
+    /// Advances the celebration and feeds the replayed messages which are due.
+    fn tick(&mut self, now: Instant) -> Command<Message> {
+        if let Some(start) = self.tab.celebration_start {
+            let progress = now.saturating_duration_since(start).as_secs_f32() / Self::CELEBRATION_DURATION.as_secs_f32();
+            if progress < 1.0 {
+                self.tab.board.set_celebration(Some(progress));
+            } else {
+                self.tab.board.set_celebration(None);
+                self.tab.celebration_start = None;
+            }
+        }
+        // one depth per frame, the board is hidden during a pause so the engine waits as well
+        if let Some(analysis) = self.tab.analysis.as_mut().filter(|_| self.tab.paused_at.is_none()) {
+            if !analysis.is_finished() {
+                analysis.step();
+            }
+        }
+        if self.move_timed() && !self.tab.low_time_warned
+            && self.tab.turn_start.elapsed() + Self::LOW_TIME >= Duration::from_secs(self.config.move_time.0 as u64) {
+            self.tab.low_time_warned = true;
+            self.sound.play(SoundEffect::LowTime);
+        }
+        let mut commands = Vec::new();
+        if self.replay.is_none() && self.multi.is_none() {
+            // engines keep thinking in background tabs, their moves are routed back by the tab id
+            let thinking: Vec<u32> = std::iter::once(&self.tab).chain(&self.background_tabs)
+                .filter(|tab| Self::engine_to_move_in(tab))
+                .map(|tab| tab.id)
+                .collect();
+            let shown = self.tab.id;
+            for id in thinking {
+                commands.extend(self.in_tab(id, |this| this.think(id != shown)).into_iter().flatten());
+            }
+        }
+        while let Some(message) = self.replay.as_mut().and_then(|replay| replay.next_due(now)) {
+            println!("Replay {:?}", message);
+            commands.push(self.handle(message));
+        }
+        // the player takes over a finished replay, which then saves, thinks and times like any run
+        if self.replay.as_ref().is_some_and(Replay::is_finished) {
+            println!("Replay finished");
+            self.replay = None;
+        }
+        Command::batch(commands)
+    }
+
+    /// Lets the engine think one depth further in the tab handled, playing its move once it is
+    /// through. Players hear of the move when it was made in the `background` or the window is.
+    fn think(&mut self, background: bool) -> Option<Command<Message>> {
+        let id = self.tab.id;
+        let time = Duration::from_millis(self.config.engine_time.0 as u64);
+        self.tab.opponent.set_style(self.config.engine_style);
+        match self.tab.opponent.step(&self.tab.game, time) {
+            Decision::Thinking => None,
+            Decision::Play(index) => {
+                // the engine's move goes through update like a click, so it is logged and replayed
+                let command = self.update(Message::ToTab(id, Box::new(Message::EngineMove(index))));
+                if background || !self.focused {
+                    Some(Command::batch([command, self.announce_turn(index)]))
+                } else {
+                    Some(command)
+                }
+            },
+            Decision::NoMove => {
+                // without a move the engine would think on every frame, the player takes over its side
+                println!("The engine found no move for {:?}", self.tab.game.state());
+                let command = self.update(Message::ToTab(id, Box::new(Message::SelectEngineSide(EngineSide::Off))));
+                self.tab.notice = Some(String::from("The engine found no move to play and stopped"));
+                Some(command)
+            },
+        }
+    }
+
+    /// Handles `f` in the tab `id` as if it were shown, so messages for a game in a background
+    /// tab reach it. `None` when there is no such tab, as it was closed meanwhile.
+    fn in_tab<T>(&mut self, id: u32, f: impl FnOnce(&mut Self) -> T) -> Option<T> {
+        if self.tab.id == id {
+            return Some(f(self));
+        }
+        let i = self.background_tabs.iter().position(|tab| tab.id == id)?;
+        std::mem::swap(&mut self.tab, &mut self.background_tabs[i]);
+        self.routed_from = Some(self.background_tabs[i].id);
+        let result = f(self);
+        self.routed_from = None;
+        std::mem::swap(&mut self.tab, &mut self.background_tabs[i]);
+        Some(result)
+    }
+
+    /// The rules, the moves, whether the game was resigned or agreed drawn and the position it
+    /// started from, in the form [`Self::restore`] reads. The position is written like
+    /// "h8 i9 | j10 | white" with the black and the white chesses and the side to move.
+    fn saved_game(game: &Game) -> String {
+        let board = game.board();
+        let (setup, moves) = board.chesses().split_at(game.setup_plies());
+        let coords = |chesses: &[Chess], color| {
+            chesses.iter().filter(|c| c.color == color).map(|c| board.pos_to_coord(c.pos)).collect::<Vec<_>>().join(" ")
         };
+        let ending = match game.ending() {
+            Some(Ending::Resignation) => "Resign",
+            Some(Ending::Agreement) => "Draw",
+            _ => "",
+        };
+        let setup = if setup.is_empty() {
+            String::new()
+        } else {
+            let next = if game.first_player() == ChessColor::Black { "black" } else { "white" };
+            format!("{} | {} | {}", coords(setup, ChessColor::Black), coords(setup, ChessColor::White), next)
+        };
+        let moves = moves.iter().map(|c| board.pos_to_coord(c.pos)).collect::<Vec<_>>().join(" ");
+        format!("{}\n{}\n{}\n{}\n", game.rule_set(), moves, ending, setup)
     }
 
-    fn view(&self) -> Element<'_, Self::Message> {
-       let content = column![self.board.view()];
-       container(content).into()
+    /// Saves the games of all tabs, so they can be continued after a restart.
+    fn autosave(&self) {
+        self.save_session();
     }
-}
 
-#[derive(PartialEq)]
-enum ChessColor {
-    Black,
-    White,
-}
+    /// Saves the tabs and the layout. A replayed session never overwrites the real one.
+    fn save_session(&self) {
+        if self.replay.is_none() {
+            self.session().save();
+        }
+    }
 
-struct Chess {
-    pos: Point<usize>,
-    color: ChessColor,
-}
+    /// The tabs and the layout as they are saved, moves tried in a tab are saved as the real game
+    /// they were tried on. Games on the relay server are left out, they are fetched again after
+    /// logging in.
+    fn session(&self) -> Session {
+        let mut tabs: Vec<&Tab> = std::iter::once(&self.tab).chain(&self.background_tabs)
+            .filter(|tab| tab.remote.is_none())
+            .collect();
+        tabs.sort_by_key(|tab| tab.id);
+        Session {
+            tabs: tabs.iter().map(|tab| SavedTab {
+                game: Self::saved_game(tab.trial_of.as_ref().unwrap_or(&tab.game)),
+                opening: if tab.opening.rule() == OpeningRule::Free { String::new() } else { tab.opening.saved() },
+                players: tab.hotseat.as_ref().map_or(String::new(), Hotseat::saved),
+                engine_side: if tab.engine_side == EngineSide::Off { String::new() } else { tab.engine_side.to_string() },
+            }).collect(),
+            shown_tab: tabs.iter().position(|tab| tab.id == self.routed_from.unwrap_or(self.tab.id)).unwrap_or(0),
+            side_panel: self.side_panel.map_or(String::new(), |panel| panel.name().to_string()),
+            show_settings: self.show_settings,
+            show_heatmap: self.show_heatmap,
+            window_size: self.window_size,
+        }
+    }
 
-#[derive(PartialEq, Copy, Clone)]
-enum CellState {
-    Empty,
-    Black,
-    White,
-}
+    fn save_config(&self) {
+        if self.replay.is_none() {
+            self.config.save();
+        }
+    }
 
-struct Board {
-    padding: f32,
-    cell_size: f32,
-    chess_size: f32,
-    line_width: f32,
-    grid_size: f32,
-    cells_per_row: usize,
-    cells: Vec<CellState>,
-    chesses: Vec<Chess>,
-    chesses_cache: Cache,
-    grid_cache: Cache,
-    overlay_cache: Cache,
-}
+    /// The tab `id` as [`Self::session`] saved it. A game which cannot be restored starts over
+    /// as a free game, the players and the engine staying at the board.
+    fn restore_tab(id: u32, saved: &SavedTab, config: &Config, pictures: &Pictures) -> Tab {
+        let mut game = Game::default();
+        let mut opening = OpeningProtocol::new(OpeningRule::Free);
+        match Self::restore(&mut game, &saved.game) {
+            Ok(()) if !saved.opening.is_empty() => {
+                match OpeningProtocol::restore(&saved.opening, game.board()) {
+                    Ok(restored) => { opening = restored; },
+                    Err(e) => println!("Cannot restore the opening of a saved game: {}", e),
+                }
+            },
+            Ok(()) => {},
+            Err(e) => {
+                println!("Cannot restore a saved game: {}", e);
+                game = Game::default();
+            },
+        }
+        let mut tab = Tab::new(id, game, config, pictures);
+        tab.opening = opening;
+        if !saved.players.is_empty() {
+            match Hotseat::restore(&saved.players) {
+                Ok(hotseat) => { tab.hotseat = Some(hotseat); },
+                Err(e) => println!("Cannot restore the players of a saved game: {}", e),
+            }
+        }
+        if !saved.engine_side.is_empty() {
+            match saved.engine_side.parse() {
+                Ok(side) => { tab.engine_side = side; },
+                Err(e) => println!("Cannot restore the engine side of a saved game: {}", e),
+            }
+        }
+        tab
+    }
 
-impl Board {
-    fn new(padding: f32, cell_size: f32, chess_size: f32, line_width: f32) -> Self {
-        let cells_per_row = 15;
-        let grid_size = (cells_per_row - 1) as f32 * cell_size + line_width;
-        let mut cells = Vec::with_capacity(cells_per_row * cells_per_row);
-        cells.resize(cells_per_row * cells_per_row, CellState::Empty);
+    fn restore(game: &mut Game, saved: &str) -> Result<(), String> {
+        let mut lines = saved.lines();
+        let rule_set = lines.next().unwrap_or_default().parse()?;
+        let moves = lines.next().unwrap_or_default();
+        let ending = lines.next();
+        match lines.next().filter(|setup| !setup.is_empty()) {
+            Some(setup) => *game = Game::new(Self::setup_board(game.board().cells_per_row(), setup)?, rule_set),
+            None => game.restart(rule_set),
+        }
+        game.play_moves(moves)?;
+        match ending {
+            Some("Resign") => { game.resign().map_err(|e| format!("cannot resign: {}", e))?; },
+            Some("Draw") => { game.agree_draw().map_err(|e| format!("cannot agree to a draw: {}", e))?; },
+            _ => {},
+        }
+        Ok(())
+    }
 
-        Self {
-            padding,
-            cell_size,
-            chess_size,
-            line_width,
-            grid_size,
-            cells_per_row,
-            cells,
-            chesses: vec![],
-            chesses_cache: Cache::default(),
-            grid_cache: Cache::default(),
-            overlay_cache: Cache::default(),
+    /// Whether `message` may be handled while the game is paused, only settings which do not help
+    /// thinking about the position may be changed.
+    fn allowed_while_paused(message: &Message) -> bool {
+        matches!(message, Message::TogglePause) || Self::is_setting(message) || Self::is_about_tabs(message)
+    }
+
+    /// Whether `message` may be handled while composing a position, messages about the game are not.
+    fn allowed_while_editing(message: &Message) -> bool {
+        matches!(message, Message::ClickBoard(_) | Message::SelectEditTool(_) | Message::SetEditBlackToMove(_)
+            | Message::ClearEdit | Message::FinishEdit(_) | Message::ContextMenu(MenuAction::Rotate | MenuAction::Mirror))
+            || Self::is_setting(message) || Self::is_about_tabs(message)
+    }
+
+    /// Whether `message` may be handled before the opening rule is through, messages which
+    /// replace the moves it is about are not.
+    fn allowed_during_opening(message: &Message) -> bool {
+        !matches!(message, Message::ToggleTrial | Message::EditPosition | Message::LoadOpening(_) | Message::ImportGame(_))
+    }
+
+    /// Whether `message` may be handled in a game on the relay server, messages which change the
+    /// game other than by a move are not, as the server would not know of it.
+    fn allowed_in_remote_game(message: &Message) -> bool {
+        !matches!(message, Message::SelectRules(_) | Message::SelectOpeningRule(_) | Message::NewGame
+            | Message::QuickStart | Message::StartGame(..)
+            | Message::Resign | Message::ConfirmResign(_) | Message::OfferDraw | Message::AnswerDraw(_)
+            | Message::SetupPlayers | Message::FinishPlayerSetup(_) | Message::SelectEngineSide(_)
+            | Message::EditPosition | Message::LoadOpening(_) | Message::ImportGame(_)
+            | Message::ContextMenu(MenuAction::Undo | MenuAction::PasteGame))
+    }
+
+    /// Whether `message` is about the game of three or more players, the only ones handled while
+    /// it is shown besides settings.
+    fn is_about_multi(message: &Message) -> bool {
+        matches!(message, Message::SelectMultiPlayers(_) | Message::SelectMultiColor(..) | Message::StartMulti
+            | Message::ClickMulti(_) | Message::UndoMulti | Message::LeaveMulti)
+    }
+
+    /// Whether `message` opens, closes or switches tabs, which every tab allows.
+    fn is_about_tabs(message: &Message) -> bool {
+        matches!(message, Message::NewTab | Message::SelectTab(_) | Message::CloseTab(_) | Message::OpenRemoteGame(_))
+    }
+
+    fn is_setting(message: &Message) -> bool {
+        matches!(message, Message::ToggleMute(_) | Message::ToggleSettings | Message::SelectMarkerStyle(_)
+            | Message::ToggleHighContrast(_) | Message::ToggleThreats(_) | Message::ToggleAutoRotate(_)
+            | Message::ToggleFlashTaskbar(_) | Message::ToggleJsonGameLog(_) | Message::EditBackgroundPath(_) | Message::LoadBackground
+            | Message::SelectStoneSkin(_) | Message::EditStonePictures(_) | Message::LoadStonePictures
+            | Message::EditSyncDir(_) | Message::ApplySyncDir | Message::SelectUiScale(_) | Message::SelectAnimationDelay(_)
+            | Message::SelectMoveTime(_)
+            | Message::SelectClickSnap(_) | Message::ToggleHitRegions
+            | Message::ToggleRandomColors(_) | Message::SelectStartPosition(_) | Message::SetEngineTime(_)
+            | Message::EditRelayServer(_) | Message::EditRelayPlayer(_) | Message::LogIn
+            | Message::SelectEngineStyle(_))
+    }
+
+    /// The board of a position written by [`Self::saved_game`].
+    fn setup_board(size: usize, setup: &str) -> Result<Board, String> {
+        let parts: Vec<&str> = setup.split('|').map(str::trim).collect();
+        let &[black, white, next] = parts.as_slice() else { return Err(format!("cannot read position \"{}\"", setup)); };
+        let board = Board::new(size);
+        let mut chesses = vec![];
+        for (coords, color) in [(black, ChessColor::Black), (white, ChessColor::White)] {
+            for coord in coords.split_whitespace() {
+                let pos = board.coord_to_pos(coord).ok_or_else(|| format!("cannot read chess \"{}\"", coord))?;
+                chesses.push((board.pos_to_index(pos), color));
+            }
+        }
+        let next = match next {
+            "black" => ChessColor::Black,
+            "white" => ChessColor::White,
+            _ => return Err(format!("cannot read side to move \"{}\"", next)),
+        };
+        Ok(editor::position(size, &chesses, next))
+    }
+
+    fn status_text(&self) -> String {
+        if self.tab.paused_at.is_some() {
+            return String::from("Paused");
+        }
+        let text = match &self.tab.notice {
+            Some(notice) => notice.clone(),
+            None => self.state_text(),
+        };
+        if self.tab.trial_of.is_some() { format!("Trying moves — {}", text) } else { text }
+    }
+
+    fn state_text(&self) -> String {
+        match self.tab.game.state() {
+            GameState::Wait(_) if !self.tab.opening.is_done() => self.opening_text(),
+            GameState::Wait(color) => format!("{} to move", color),
+            GameState::Win(color) if self.tab.game.ending() == Some(Ending::Resignation) => {
+                format!("{} wins by resignation ({})", color, sgf::result(&self.tab.game).unwrap_or_default())
+            },
+            GameState::Win(color) if self.tab.game.ending() == Some(Ending::Captures) => format!("{} wins by captures", color),
+            GameState::Win(color) => format!("{} wins", color),
+            GameState::Draw if self.tab.game.ending() == Some(Ending::Agreement) => String::from("Draw by agreement"),
+            GameState::Draw => String::from("Draw"),
         }
     }
 
-    fn valid_index(&self, index: usize) -> bool {
-        index < self.cells_per_row * self.cells_per_row
+    /// What the opening rule waits for and which player holds black.
+    fn opening_text(&self) -> String {
+        let opening = &self.tab.opening;
+        let side = if self.tab.game.state() == GameState::Wait(ChessColor::Black) { "Black" } else { "White" };
+        let step = match opening.step() {
+            Some(Step::Put { reach: Some(0) }) => format!("{} puts a chess in the center", side),
+            Some(Step::Put { reach: Some(reach) }) => format!("{} puts a chess within {} lines of the center", side, reach),
+            Some(Step::Put { reach: None }) => format!("{} puts a chess", side),
+            Some(Step::Swap) => format!("{} may swap colors", side),
+            Some(Step::Declare { .. }) => format!("{} declares the fifth moves", side),
+            Some(Step::Offer { .. }) => format!("Black offers fifth moves, {} of {}", opening.offers().len(), opening.offer_count()),
+            Some(Step::Pick) => String::from("White picks the fifth move"),
+            None => String::new(),
+        };
+        let black = if opening.first_player_black() { 1 } else { 2 };
+        format!("{}: {} (player {} plays black)", opening.rule(), step, black)
     }
 
-    fn valid_pos(&self, col: usize, row: usize) -> bool {
-        col <= self.cells_per_row && row <= self.cells_per_row
+    /// Pairs each side captured, empty unless the rules capture.
+    fn captures_text(&self) -> String {
+        if !self.tab.game.rule_set().has_captures() {
+            return String::new();
+        }
+        let board = self.tab.game.board();
+        format!("Captured pairs: black {}, white {} of {}",
+            board.captured_by(ChessColor::Black) / 2, board.captured_by(ChessColor::White) / 2, Pente::PAIRS_TO_WIN)
     }
 
-    fn index_to_pos(&self, index: usize) -> Point<usize> {
-        Point::new(index % self.cells_per_row, index / self.cells_per_row)
+    fn toggle_side_panel(&mut self, panel: SidePanel) {
+        self.side_panel = if self.side_panel == Some(panel) { None } else { Some(panel) };
+        self.update_analysis();
     }
 
-    fn pos_to_index(&self, pos: Point<usize>) -> usize {
-        pos.x + pos.y * self.cells_per_row
+    /// Whether the engine should be thinking about its move in the shown tab.
+    fn engine_to_move(&self) -> bool {
+        self.multi.is_none() && Self::engine_to_move_in(&self.tab)
     }
 
-    fn is_empty_at(&self, index: usize) -> bool {
-        self.valid_index(index) && self.cells[index] == CellState::Empty
+    /// Whether the engine should be thinking about its move in `tab`, shown or not.
+    fn engine_to_move_in(tab: &Tab) -> bool {
+        let color = match tab.game.state() {
+            GameState::Wait(ChessColor::Black) => ChessColor::Black,
+            GameState::Wait(ChessColor::White) => ChessColor::White,
+            _ => return false,
+        };
+        tab.engine_side.plays(color) && tab.paused_at.is_none() && tab.editor.is_none()
+            && tab.trial_of.is_none() && tab.opening.is_done()
     }
 
-    fn put_chess(&mut self, index: usize, is_black: bool) {
-        if self.valid_index(index) {
-            let grid_pos = self.index_to_pos(index);
-            self.chesses.push(Chess {pos: grid_pos, color: if is_black { ChessColor::Black } else { ChessColor::White } });
-            self.cells[index] = if is_black { CellState::Black } else { CellState::White };
-            self.chesses_cache.clear();
-            self.overlay_cache.clear();
-        } else {
-            panic!("Index out of range when putting chess, max is {}, but got {}.", self.cells_per_row * self.cells_per_row, index);
+    /// Whether a player is thinking about a move against the time per move.
+    fn move_timed(&self) -> bool {
+        let tab = &self.tab;
+        self.config.move_time.0 > 0 && !tab.game.state().is_over() && !self.engine_to_move() && self.replay.is_none()
+            && tab.paused_at.is_none() && tab.editor.is_none() && tab.trial_of.is_none() && self.multi.is_none()
+    }
+
+    /// Whether the engine plays the side a draw offer of the side to move goes to.
+    fn engine_answers_draw(&self) -> bool {
+        let other = match self.tab.game.state() {
+            GameState::Wait(ChessColor::Black) => ChessColor::White,
+            GameState::Wait(ChessColor::White) => ChessColor::Black,
+            _ => return false,
+        };
+        self.tab.engine_side.plays(other)
+    }
+
+    fn answer_draw(&mut self, accepted: bool) {
+        if !accepted {
+            self.tab.notice = Some(String::from("Draw declined"));
+        } else if let Ok(state) = self.tab.game.agree_draw() {
+            self.tab.notice = None;
+            self.autosave();
+            self.on_state_changed(state);
         }
     }
 
-    fn remove_last_chess(&mut self) {
-        match self.chesses.pop() {
-            Some(chess) => {
-                let index = self.pos_to_index(chess.pos);
-                self.cells[index] = CellState::Empty;
-                self.chesses_cache.clear();
-                self.overlay_cache.clear();
+    /// Answers a request of the control server, see [`rpc`] for the methods.
+    fn answer_rpc(&mut self, call: rpc::Call) -> Command<Message> {
+        println!("Message Rpc {} {}", call.method, call.params);
+        let id = match call.params.get("tab").map(json::Value::as_f64) {
+            None => self.tab.id,
+            Some(Some(id)) if id.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(&id) => id as u32,
+            Some(_) => {
+                call.answer(Err((rpc::INVALID_PARAMS, String::from("tab must be the id of a tab"))));
+                return Command::none();
+            },
+        };
+        match self.in_tab(id, |this| this.answer_call(&call)) {
+            Some((result, command)) => {
+                call.answer(result);
+                command
+            },
+            None => {
+                call.answer(Err((rpc::INVALID_PARAMS, format!("there is no tab {}", id))));
+                Command::none()
             },
-            None => { panic!("Cannot remove last chess because chesses is empty."); }
         }
     }
 
-    fn clear(&mut self) {
-        *self = Self::new(self.padding, self.cell_size, self.chess_size, self.line_width);
+    /// Answers `call` about the game in the tab handled, the messages it sends are routed to it.
+    fn answer_call(&mut self, call: &rpc::Call) -> (Result<String, (i32, String)>, Command<Message>) {
+        let id = self.tab.id;
+        let mut command = Command::none();
+        let result = match call.method.as_str() {
+            "position" => Ok(self.position_json()),
+            "play" => match call.param(0, "move").and_then(json::Value::as_str) {
+                Some(coord) => match self.tab.game.board().coord_to_pos(coord) {
+                    Some(pos) => {
+                        let ply = self.tab.game.board().ply();
+                        self.tab.notice = None;
+                        command = self.update(Message::ToTab(id, Box::new(Message::ClickBoard(self.tab.game.board().pos_to_index(pos)))));
+                        if self.tab.game.board().ply() > ply {
+                            Ok(self.position_json())
+                        } else {
+                            Err((rpc::REFUSED, self.tab.notice.clone().unwrap_or_else(|| String::from("the move was not played"))))
+                        }
+                    },
+                    None => Err((rpc::INVALID_PARAMS, format!("no point \"{}\" on the board", coord))),
+                },
+                None => Err((rpc::INVALID_PARAMS, String::from("expected a move like \"h8\""))),
+            },
+            "new_game" => {
+                command = self.update(Message::ToTab(id, Box::new(Message::NewGame)));
+                Ok(self.position_json())
+            },
+            "analyze" => {
+                let depth = call.param(0, "depth").and_then(json::Value::as_f64).unwrap_or(4.0);
+                if depth.fract() == 0.0 && (1.0..=Self::RPC_MAX_DEPTH as f64).contains(&depth) {
+                    let result = Engine::new(depth as u32).search(&self.tab.game);
+                    let board = self.tab.game.board();
+                    let coord = |index: usize| json::string(&board.pos_to_coord(board.index_to_pos(index)));
+                    let pv: Vec<String> = result.pv.iter().map(|&index| coord(index)).collect();
+                    Ok(format!("{{\"best_move\":{},\"score\":{},\"depth\":{},\"pv\":[{}]}}",
+                        result.best_move.map_or(String::from("null"), coord), result.score, result.depth, pv.join(",")))
+                } else {
+                    Err((rpc::INVALID_PARAMS, format!("depth must be a whole number from 1 to {}", Self::RPC_MAX_DEPTH)))
+                }
+            },
+            method => Err((rpc::METHOD_NOT_FOUND, format!("unknown method \"{}\"", method))),
+        };
+        (result, command)
+    }
+
+    /// The game of the tab handled for the control server: the tab id, the rules, the status line,
+    /// whose turn it is, the moves and the board as rows of `x` for black, `o` for white and `.`
+    /// for empty points.
+    fn position_json(&self) -> String {
+        let game = &self.tab.game;
+        let board = game.board();
+        let size = board.cells_per_row();
+        let rows: Vec<String> = (0..size).map(|y| {
+            let row: String = (0..size).map(|x| match board.cell(board.pos_to_index(Pos::new(x, y))) {
+                Some(CellState::Black) => 'x',
+                Some(CellState::White) => 'o',
+                _ => '.',
+            }).collect();
+            json::string(&row)
+        }).collect();
+        let moves: Vec<String> = board.moves()
+            .map(|mv| json::string(&board.pos_to_coord(board.index_to_pos(mv.index))))
+            .collect();
+        let to_move = match game.state() {
+            GameState::Wait(ChessColor::Black) => json::string("black"),
+            GameState::Wait(ChessColor::White) => json::string("white"),
+            _ => String::from("null"),
+        };
+        format!(
+            "{{\"tab\":{},\"size\":{},\"rules\":{},\"status\":{},\"to_move\":{},\"result\":{},\"moves\":[{}],\"rows\":[{}]}}",
+            self.tab.id,
+            size,
+            json::string(&game.rule_set().to_string()),
+            json::string(&self.state_text()),
+            to_move,
+            sgf::result(game).map_or(String::from("null"), json::string),
+            moves.join(","),
+            rows.join(","))
     }
 
-    fn view(&self) -> Element<Message> {
-        Canvas::new(self).width(Length::Fill).height(Length::Fill).into()
+    /// Tells the player the opponent moved at `index` while the window was in the background.
+    fn announce_turn(&self, index: usize) -> Command<Message> {
+        let board = self.tab.game.board();
+        self.announce_move(&self.tab.title(), &board.pos_to_coord(board.index_to_pos(index)))
     }
 
-    fn grid_pos(&self, x: f32, y: f32, dis_scale: f32) -> Option<Point<usize>> {
-        let pos_from_grid = Point::new(x - self.padding, y - self.padding);
-        let col = (pos_from_grid.x / self.cell_size).round() as i32;
-        let row = (pos_from_grid.y / self.cell_size).round() as i32;
-        if col > 0 && row > 0 && self.valid_pos(col as usize, row as usize) {
-            let dis = pos_from_grid.distance(Point::new(col as f32 * self.cell_size, row as f32 * self.cell_size));
-            // println!("board pos {}, grid pos {}, col {}, row {}, dis {}", Point::new(x, y), pos_from_grid, col, row, dis);
-            if dis * 2.0 > self.cell_size * dis_scale { None } else { Some(Point::new(col as usize, row as usize)) }
+    /// Tells the player the opponent played `coord` in the game titled `title`.
+    fn announce_move(&self, title: &str, coord: &str) -> Command<Message> {
+        notification::notify(&format!("Your move in {}", title), &format!("The opponent played {}", coord));
+        if self.config.flash_taskbar {
+            window::request_user_attention(window::Id::MAIN, Some(window::UserAttention::Informational))
         } else {
-            None
+            Command::none()
         }
     }
-}
 
-impl Default for Board {
-    fn default() -> Self {
-        Self::new(45.0, 48.0, 42.0, 2.0)
+    /// The board of the game of three or more players with its controls, or the players picking
+    /// their colors before it starts.
+    fn multi_view<'a>(&'a self, multi: &'a Multi) -> Element<'a, Message> {
+        let controls = match multi.game() {
+            None => {
+                let counts: Vec<usize> = (MIN_PLAYERS..=MAX_PLAYERS).collect();
+                let colors = multi.colors().iter().enumerate().fold(Row::new().spacing(6), |colors, (player, &color)| {
+                    colors.push(pick_list(ChessColor::ALL, Some(color), move |color| Message::SelectMultiColor(player, color)))
+                });
+                row![
+                    text("Players"),
+                    pick_list(counts, Some(multi.colors().len()), Message::SelectMultiPlayers),
+                    colors,
+                    horizontal_space(),
+                    button("Start").on_press_maybe(multi.colors_differ().then_some(Message::StartMulti)),
+                    button("Cancel").on_press(Message::LeaveMulti),
+                ]
+            },
+            Some(game) => row![
+                text(multi.state_text()).size(20),
+                horizontal_space(),
+                button("Undo").on_press_maybe(game.board().has_chess().then_some(Message::UndoMulti)),
+                button("New game").on_press(Message::StartMulti),
+                button("Leave").on_press(Message::LeaveMulti),
+            ],
+        };
+        let controls = controls.spacing(10).padding(10).align_items(iced::Alignment::Center);
+        let controls = if self.is_portrait() { Self::sideways(controls) } else { controls.into() };
+        column![multi.view(), controls].into()
     }
-}
 
-impl canvas::Program<Message> for Board {
-    type State = ();
-    fn update(
-        &self,
-        _state: &mut Self::State,
-        event: canvas::Event,
-        bounds: Rectangle,
-        cursor: mouse::Cursor,
-    ) -> (canvas::event::Status, Option<Message>) {
-        let on_click = || {
-            match cursor.position_in(bounds) {
-                Some(pos) => {
-                    match self.grid_pos(pos.x, pos.y, 0.6) {
-                        Some(grid_pos) => {
-                            println!("Press at board {}, try to put chess at index {}", grid_pos, self.pos_to_index(grid_pos));
-                            (event::Status::Captured, Some(Message::ClickBoard(self.pos_to_index(grid_pos))))
-                        },
-                        None => (canvas::event::Status::Captured, None),
-                    }
-                },
-                None => (canvas::event::Status::Captured, None),
-            }
+    /// Whether the window is taller than wide, like on a phone or tiled to half a screen. The
+    /// panels then go below the board and the rows too wide for the window scroll sideways.
+    fn is_portrait(&self) -> bool {
+        self.window_size.is_some_and(|(width, height)| height > width)
+    }
+
+    /// The board with `panel` on its right, or below it in a portrait window so the board keeps
+    /// the full width.
+    fn beside_board<'a>(&'a self, shown: &'a Board, panel: Element<'a, Message>) -> Element<'a, Message> {
+        let board = self.tab.board.view(shown, Message::from_board);
+        if self.is_portrait() {
+            column![
+                container(board).height(Length::FillPortion(3)),
+                container(panel).width(Length::Fill).height(Length::FillPortion(2)).center_x(),
+            ].into()
+        } else {
+            row![board, panel].into()
+        }
+    }
+
+    /// `content` scrolling sideways when it is wider than the window.
+    fn sideways<'a>(content: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+        scrollable(content).direction(Direction::Horizontal(Properties::default())).into()
+    }
+
+    /// Who the engine plays, in which style and how long it may think, next to the analysis.
+    fn opponent_view(&self) -> Element<'_, Message> {
+        let time = self.config.engine_time;
+        column![
+            text("Engine plays"),
+            pick_list(EngineSide::ALL, Some(self.tab.engine_side), Message::SelectEngineSide),
+            text("Style"),
+            pick_list(Style::ALL, Some(self.config.engine_style), Message::SelectEngineStyle),
+            text(format!("Time per move {:.1} s", time.0 as f64 / 1000.0)),
+            slider(EngineTime::MIN..=EngineTime::MAX, time.0, Message::SetEngineTime).step(100u32),
+        ].spacing(6).padding(10).width(220).into()
+    }
+
+    /// Starts searching the current position when the engine panel is shown, stops otherwise.
+    fn update_analysis(&mut self) {
+        match (&mut self.tab.analysis, self.side_panel == Some(SidePanel::Engine)) {
+            (Some(analysis), true) => analysis.restart(&self.tab.game),
+            (analysis, shown) => { *analysis = shown.then(|| Analysis::new(&self.tab.game)); },
+        }
+    }
+
+    /// Makes the engines of the tab forget the positions of the game it had, which was replaced
+    /// by a new one.
+    fn forget_positions(&mut self) {
+        self.tab.opponent.forget();
+        if let Some(analysis) = self.tab.analysis.as_mut() {
+            analysis.forget(&self.tab.game);
+        }
+    }
+
+    /// Every move so far with how long it was thought about. Clicking a move shows the position
+    /// after it, the move of the shown position is highlighted.
+    fn moves_view(&self) -> Element<'_, Message> {
+        let board = self.tab.game.board();
+        let shown_ply = self.shown_ply();
+        let mut list = Column::new().spacing(2).padding(10).width(240);
+        list = list.push(text("Moves").size(18));
+        for (mv, time) in board.moves().zip(self.tab.game.move_times()).skip(self.tab.game.setup_plies()) {
+            let color = if mv.player == ChessColor::Black { "Black" } else { "White" };
+            let style = if mv.ply + 1 == shown_ply { iced::theme::Button::Primary } else { iced::theme::Button::Text };
+            list = list.push(button(row![
+                text(format!("{}.", mv.ply + 1)).width(36),
+                text(color).width(50),
+                text(board.pos_to_coord(board.index_to_pos(mv.index))).width(40),
+                text(time.map_or(String::new(), format_time)).size(14),
+            ].spacing(6)).style(style).padding([2, 4]).width(Length::Fill).on_press(Message::ShowPly(mv.ply + 1)));
+        }
+        let comment_box: Element<'_, Message> = match shown_ply.checked_sub(1).filter(|&ply| ply >= self.tab.game.setup_plies()) {
+            Some(ply) => text_input(&format!("Comment on move {}", ply + 1), self.tab.comments.get(&ply).map_or("", String::as_str))
+                .on_input(Message::EditComment)
+                .width(240)
+                .into(),
+            None => horizontal_space().height(0).into(),
         };
+        column![scrollable(list).id(Self::moves_scroll_id()).height(Length::Fill), comment_box].into()
+    }
 
-        match event {
-            canvas::Event::Touch(touch::Event::FingerPressed { .. }) => { on_click() },
-            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => { on_click() },
-            _ => (canvas::event::Status::Captured, None),
-        }
-    }
-
-    fn draw(
-        &self,
-        _interaction: &Self::State,
-        renderer: &Renderer,
-        _theme: &Theme,
-        bounds: Rectangle,
-        _cursor: mouse::Cursor
-    ) -> Vec<Geometry> {
-        // println!("board draw called, already have {} chesses", self.chesses.len());
-
-        let grid = self.grid_cache.draw(renderer, bounds.size(), |frame| {
-            let bg_color = Color::from_rgb8(0xf0, 0xf0, 0xf0);
-            let grid_color = Color::from_rgb8(0x60, 0x64, 0x6b);
-            frame.fill_rectangle(bounds.position(), bounds.size(), bg_color);
-            for row in 0..self.cells_per_row {
-                frame.fill_rectangle(
-                    Point::new(self.padding, self.padding + row as f32 * self.cell_size),
-                    Size::new(self.grid_size, self.line_width as f32),
-                    grid_color,
-                );
-                frame.fill_rectangle(
-                    Point::new(self.padding + row as f32 * self.cell_size, self.padding),
-                    Size::new(self.line_width as f32, self.grid_size),
-                    grid_color,
-                );
+    fn moves_scroll_id() -> scrollable::Id {
+        scrollable::Id::new("moves")
+    }
+
+    /// Scrolls the move list so the move of the shown position is in sight.
+    fn scroll_moves(&self) -> Command<Message> {
+        let first = self.tab.game.setup_plies();
+        let count = self.tab.game.board().ply() - first;
+        let y = if count > 1 { self.shown_ply().saturating_sub(first + 1) as f32 / (count - 1) as f32 } else { 0.0 };
+        scrollable::snap_to(Self::moves_scroll_id(), RelativeOffset { x: 0.0, y })
+    }
+
+    /// Number of moves in the shown position, which is the game's own unless an earlier one is browsed.
+    fn shown_ply(&self) -> usize {
+        self.tab.browsed.as_ref().unwrap_or(self.tab.game.board()).ply()
+    }
+
+    /// The named openings with how games after them went, to load one onto the board.
+    fn openings_view(&self) -> Element<'_, Message> {
+        let mut list = Column::new().spacing(6).padding(10).width(300);
+        for (kind, title) in [(OpeningKind::Direct, "Direct openings"), (OpeningKind::Indirect, "Indirect openings")] {
+            list = list.push(text(title).size(18));
+            for (i, opening) in OPENINGS.iter().enumerate().filter(|(_, opening)| opening.kind == kind) {
+                let record = self.opening_stats.record_of(opening);
+                let stats = match record.games() {
+                    0 => String::from("no games"),
+                    games => format!("{} games, black {}%", games, record.black_wins * 100 / games),
+                };
+                list = list.push(row![
+                    text(opening.name).width(90),
+                    text(stats).size(14),
+                    horizontal_space(),
+                    button("Load").on_press(Message::LoadOpening(i)),
+                ].spacing(10).align_items(iced::Alignment::Center));
             }
+        }
+        scrollable(list).into()
+    }
+
+    /// Games between named players and against the engine, sorted by the clicked column header.
+    fn scoreboard_view(&self) -> Element<'_, Message> {
+        let header = || ScoreColumn::ALL.iter().fold(Row::new().spacing(4), |header, &column| {
+            let label = if column == self.score_sort { format!("{} ▾", column) } else { column.to_string() };
+            let width = if column == ScoreColumn::Name { 150 } else { 64 };
+            header.push(button(text(label).size(14)).on_press(Message::SortScoreboard(column)).padding(4).width(width))
         });
+        let table = |title: &'static str, rows: Vec<ScoreRow>| {
+            let mut table = column![text(title).size(18), header()].spacing(4);
+            if rows.is_empty() {
+                table = table.push(text("No games yet").size(14));
+            }
+            for row in rows {
+                table = table.push(row![
+                    text(row.name.clone()).width(150),
+                    text(row.games()).width(64),
+                    text(row.wins).width(64),
+                    text(row.losses).width(64),
+                    text(row.draws).width(64),
+                ].spacing(4));
+            }
+            table
+        };
+        scrollable(column![
+            table("Players", self.scoreboard.pairings(self.score_sort)),
+            table("Against the engine", self.scoreboard.engine_levels(self.score_sort)),
+        ].spacing(16).padding(10).width(480)).into()
+    }
+
+    /// Who plays `color` in the shown tab for the scoreboard, `None` for an unnamed player when
+    /// nobody plays against the engine.
+    fn player(&self, color: ChessColor) -> Option<Player> {
+        if let Some(remote) = &self.tab.remote {
+            let name = if color == remote.color { &self.config.relay_player } else { &remote.opponent };
+            return Some(Player::Named(name.clone()));
+        }
+        if self.tab.engine_side.plays(color) {
+            return Some(Player::Engine(self.config.engine_time.0));
+        }
+        match &self.tab.hotseat {
+            Some(hotseat) => {
+                let (black, white) = hotseat.sides(self.tab.opening.first_player_black());
+                Some(Player::Named(String::from(if color == ChessColor::Black { black } else { white })))
+            },
+            None if self.tab.engine_side != EngineSide::Off => Some(Player::Named(String::from("Player"))),
+            None => None,
+        }
+    }
 
-        let chesses = self.chesses_cache.draw(renderer, bounds.size(), |frame| {
-            // TODO: read from config
-            let outer_color = Color::from_rgb8(0x60, 0x60, 0x60);
-            let black_chess_color = Color::from_rgb8(0x20, 0x20, 0x20);
-            let white_chess_color = Color::from_rgb8(0xf0, 0xf0, 0xf0);
-            for c in self.chesses.iter() {
-                let chess_center = Point::new(
-                    self.padding + c.pos.x as f32 * self.cell_size,
-                    self.padding + c.pos.y as f32 * self.cell_size);
-                let chess_color = if c.color == ChessColor::Black { black_chess_color } else { white_chess_color };
-                frame.fill(&Path::circle(chess_center, self.chess_size / 2.0), outer_color);
-                frame.fill(&Path::circle(chess_center, self.chess_size / 2.0 - self.line_width), chess_color);
+    /// A win sounds sad when the engine or a relay opponent beat the player at the board, and
+    /// happy otherwise, so a hot-seat game always ends with a win.
+    fn result_sound(&self, state: GameState) -> SoundEffect {
+        let (winner, loser) = if state == GameState::Win(ChessColor::Black) {
+            (ChessColor::Black, ChessColor::White)
+        } else {
+            (ChessColor::White, ChessColor::Black)
+        };
+        let is_engine = |color| matches!(self.player(color), Some(Player::Engine(_)));
+        let engine_won = is_engine(winner) && !is_engine(loser);
+        let opponent_won = self.tab.remote.as_ref().is_some_and(|remote| remote.color == loser);
+        if engine_won || opponent_won { SoundEffect::Loss } else { SoundEffect::Win }
+    }
+
+    /// The relay games of the player, those waiting for their move first.
+    fn correspondence_view(&self) -> Element<'_, Message> {
+        let mut list = Column::new().spacing(6).padding(10).width(300);
+        if let Some(e) = &self.relay_error {
+            list = list.push(text(format!("Cannot reach the relay server: {}", e)).size(14));
+        }
+        let Some(games) = &self.remote_games else {
+            return list.push(text("Log in to a relay server in the settings to play correspondence games").size(14)).into();
+        };
+        let heading = match games.iter().filter(|game| game.my_turn()).count() {
+            0 => String::from("No game waits for your move"),
+            1 => String::from("Your move in 1 game"),
+            waiting => format!("Your move in {} games", waiting),
+        };
+        list = list.push(text(heading).size(18));
+        let mut games: Vec<&RemoteGame> = games.iter().collect();
+        games.sort_by_key(|game| !game.my_turn());
+        for game in games {
+            list = list.push(row![
+                text(format!("vs {}", game.opponent)).width(120),
+                text(if game.my_turn() { "your move" } else { "their move" }).size(14),
+                horizontal_space(),
+                button("Open").on_press(Message::OpenRemoteGame(game.id.clone())),
+            ].spacing(10).align_items(iced::Alignment::Center));
+        }
+        scrollable(list).into()
+    }
+
+    /// The name of the relay opponent while it is their turn in the shown tab. Moves tried on a
+    /// copy of the game may be made for both sides.
+    fn remote_opponent_to_move(&self) -> Option<&str> {
+        let remote = self.tab.remote.as_ref().filter(|_| self.tab.trial_of.is_none())?;
+        let color = match self.tab.game.state() {
+            GameState::Wait(ChessColor::Black) => ChessColor::Black,
+            GameState::Wait(ChessColor::White) => ChessColor::White,
+            _ => return None,
+        };
+        (color != remote.color).then_some(remote.opponent.as_str())
+    }
+
+    fn fetch_remote_games(&self) -> Command<Message> {
+        let (server, player) = (self.config.relay_server.clone(), self.config.relay_player.clone());
+        Command::perform(relay::pending(server, player), Message::RelayGames)
+    }
+
+    /// Keeps the games fetched from the relay server and plays the opponents' new moves in the
+    /// tabs the games are open in.
+    fn receive_remote_games(&mut self, games: Result<Vec<RemoteGame>, String>) -> Command<Message> {
+        let games = match games {
+            Ok(games) => games,
+            Err(e) => {
+                println!("Cannot fetch the relay games: {}", e);
+                self.relay_error = Some(e);
+                return Command::none();
+            },
+        };
+        println!("The relay server has {} games, {} wait for a move", games.len(), games.iter().filter(|game| game.my_turn()).count());
+        self.relay_error = None;
+        let commands: Vec<_> = games.iter().filter_map(|game| self.receive_remote_moves(game)).collect();
+        self.remote_games = Some(games);
+        Command::batch(commands)
+    }
+
+    /// Plays the moves the relay opponent made in `remote` since the tab it is open in last
+    /// heard of it, `None` when there are none or the game is not open.
+    fn receive_remote_moves(&mut self, remote: &RemoteGame) -> Option<Command<Message>> {
+        let shown = self.tab.id;
+        let tab = std::iter::once(&mut self.tab).chain(&mut self.background_tabs)
+            .find(|tab| tab.remote.as_ref().is_some_and(|open| open.id == remote.id))?;
+        let known: Vec<&str> = tab.remote.as_ref()?.moves.split_whitespace().collect();
+        let moves: Vec<&str> = remote.moves.split_whitespace().collect();
+        if moves.len() <= known.len() || moves[..known.len()] != known[..] {
+            return None;
+        }
+        let new_moves = moves[known.len()..].join(" ");
+        let game = tab.trial_of.as_mut().unwrap_or(&mut tab.game);
+        if let Err(e) = game.play_moves(&new_moves) {
+            println!("Cannot play the relay moves {} against {}: {}", new_moves, remote.opponent, e);
+            return None;
+        }
+        println!("The relay opponent {} played {}", remote.opponent, new_moves);
+        let state = game.state();
+        let coord = moves.last()?.to_string();
+        tab.remote = Some(remote.clone());
+        let (id, title) = (tab.id, tab.title());
+        if id == shown {
+            self.chesses_changed();
+            self.sound.play(if remote.color == ChessColor::White { SoundEffect::PutBlack } else { SoundEffect::PutWhite });
+            self.on_state_changed(state);
+        } else {
+            tab.board.chesses_changed();
+            tab.turn_start = Instant::now();
+            if let Some(line) = tab.game.winning_line() {
+                tab.board.set_winning_line(line.to_vec());
             }
-        });
+        }
+        (id != shown || !self.focused).then(|| self.announce_move(&title, &coord))
+    }
 
-        let overlay = self.overlay_cache.draw(renderer, bounds.size(), |frame| {
-            match self.chesses.last() {
-                Some(last_chess) => {
-                    let cross_half_size = self.cell_size / 7.0;
-                    let chess_center = Point::new(
-                        self.padding + last_chess.pos.x as f32 * self.cell_size,
-                        self.padding + last_chess.pos.y as f32 * self.cell_size);
-                    let cross = Path::new(|b| {
-                        b.move_to(Point::new(chess_center.x - cross_half_size, chess_center.y));
-                        b.line_to(Point::new(chess_center.x + cross_half_size, chess_center.y));
-                        b.move_to(Point::new(chess_center.x, chess_center.y - cross_half_size));
-                        b.line_to(Point::new(chess_center.x, chess_center.y + cross_half_size));
-                    });
-                    frame.stroke(
-                        &cross,
-                        Stroke::default()
-                            .with_color(Color::from_rgb8(0xff, 0x00, 0x00))
-                            .with_width(self.line_width as f32));
-                },
-                None => ()
+    /// Sends the move just made at `index` in the shown tab when it is a relay game.
+    fn send_move(&mut self, index: usize) -> Command<Message> {
+        let Some(remote) = self.tab.remote.as_mut().filter(|_| self.replay.is_none()) else { return Command::none(); };
+        let board = self.tab.game.board();
+        let coord = board.pos_to_coord(board.index_to_pos(index));
+        remote.moves = board.move_list();
+        if let Some(game) = self.remote_games.iter_mut().flatten().find(|game| game.id == remote.id) {
+            game.moves = remote.moves.clone();
+        }
+        let id = self.tab.id;
+        let send = relay::send_move(self.config.relay_server.clone(), self.config.relay_player.clone(), remote.id.clone(), coord);
+        Command::perform(send, move |result| Message::MoveSent(id, result))
+    }
+
+    /// Takes the move sent from the tab `id` back when the relay server refused it or could not
+    /// be reached, so the player can make it again.
+    fn move_sent(&mut self, id: u32, result: Result<(), String>) {
+        let Err(e) = result else {
+            println!("The relay server took the move of tab {}", id);
+            return;
+        };
+        println!("Cannot send the move of tab {}: {}", id, e);
+        let shown = self.tab.id == id;
+        let Some(tab) = std::iter::once(&mut self.tab).chain(&mut self.background_tabs).find(|tab| tab.id == id) else { return; };
+        let Some(remote) = tab.remote.as_mut() else { return; };
+        let game = tab.trial_of.as_mut().unwrap_or(&mut tab.game);
+        game.undo();
+        remote.moves = game.board().move_list();
+        tab.notice = Some(format!("Move not sent, make it again: {}", e));
+        tab.result_counted = false;
+        if shown {
+            self.chesses_changed();
+            self.tab.celebration_start = None;
+        } else {
+            tab.board.chesses_changed();
+        }
+    }
+
+    /// Applies the settings about drawing the board to `board`.
+    fn configure_board(board: &mut BoardView, config: &Config, pictures: &Pictures) {
+        board.set_background(pictures.background.clone());
+        board.set_stone_skin(config.stone_skin);
+        board.set_stone_pictures(pictures.stones.clone());
+        board.set_marker_style(config.marker_style);
+        board.set_click_snap(config.click_snap);
+        board.set_show_hit_regions(config.show_hit_regions);
+        board.set_high_contrast(config.high_contrast);
+    }
+
+    /// Shows the tab `id`, returns whether there is one.
+    fn select_tab(&mut self, id: u32) -> bool {
+        let Some(i) = self.background_tabs.iter().position(|tab| tab.id == id) else { return id == self.tab.id; };
+        std::mem::swap(&mut self.tab, &mut self.background_tabs[i]);
+        // settings may have changed while the tab was in the background
+        Self::configure_board(&mut self.tab.board, &self.config, &self.pictures);
+        self.update_threats();
+        self.update_half_turn();
+        self.update_analysis();
+        true
+    }
+
+    /// Tab titles in the order they are shown, with whether the tab is the shown one.
+    fn tab_titles(&self) -> Vec<(u32, String, bool)> {
+        let mut titles: Vec<_> = std::iter::once(&self.tab).chain(&self.background_tabs)
+            .map(|tab| (tab.id, tab.title(), tab.id == self.tab.id))
+            .collect();
+        titles.sort_by_key(|&(id, _, _)| id);
+        titles
+    }
+
+    fn tab_bar(&self) -> Element<'_, Message> {
+        let closable = !self.background_tabs.is_empty();
+        let mut bar = Row::new().spacing(4).padding([10, 10, 0, 10]).align_items(iced::Alignment::Center);
+        for (id, title, shown) in self.tab_titles() {
+            let style = if shown { iced::theme::Button::Primary } else { iced::theme::Button::Secondary };
+            bar = bar.push(button(text(title)).style(style).on_press(Message::SelectTab(id)));
+            if closable {
+                bar = bar.push(button("×").style(iced::theme::Button::Text).on_press(Message::CloseTab(id)));
+            }
+        }
+        bar.push(button("+").on_press(Message::NewTab)).into()
+    }
+
+    fn new_game(&mut self, rule_set: RuleSet) {
+        self.tab.game.restart(rule_set);
+        self.forget_positions();
+        self.tab.opening = OpeningProtocol::new(self.tab.opening.rule());
+        if let Some(hotseat) = self.tab.hotseat.as_mut() {
+            hotseat.next_game();
+        }
+        self.tab.result_counted = false;
+        self.chesses_changed();
+        self.autosave();
+        self.tab.celebration_start = None;
+        self.tab.notice = None;
+    }
+
+    /// Replaces the moves of the game with `opening`.
+    fn put_opening(&mut self, opening: &Opening) {
+        self.tab.game.restart(self.tab.game.rule_set());
+        self.forget_positions();
+        if let Err(e) = self.tab.game.play_moves(&opening.move_list(self.tab.game.board())) {
+            self.tab.notice = Some(format!("Cannot load {}: {}", opening.name, e));
+        } else {
+            self.tab.notice = None;
+        }
+        self.chesses_changed();
+        self.autosave();
+        self.tab.celebration_start = None;
+    }
+
+    /// Clicks put chesses on the game's own position only, and not while the engine is to move.
+    fn update_clickable(&mut self) {
+        let clickable = self.tab.browsed.is_none() && !self.engine_to_move();
+        self.tab.board.set_clickable(clickable);
+    }
+
+    /// Must be called whenever chesses are put on or removed from the board.
+    fn chesses_changed(&mut self) {
+        self.tab.reviewing = false;
+        self.tab.browsed = None;
+        self.update_clickable();
+        // comments on moves which were taken back do not belong to the moves put there later
+        let ply = self.tab.game.board().ply();
+        self.tab.comments.retain(|&commented, _| commented < ply);
+        self.tab.board.chesses_changed();
+        self.tab.turn_start = Instant::now();
+        self.tab.low_time_warned = false;
+        // a question about the previous position does not stand after the chesses changed
+        self.tab.prompt = None;
+        self.tab.opponent.stop();
+        self.tab.board.set_offers(self.tab.opening.offers().to_vec());
+        self.tab.board.set_forbidden(Self::forbidden_points(&self.tab.game));
+        self.update_heatmap();
+        self.update_threats();
+        self.update_half_turn();
+        self.update_analysis();
+    }
+
+    /// Shows the position after `ply` moves, or goes back to the game's own position when `ply`
+    /// is its current ply or more. Clicks do not put chesses while an earlier position is shown.
+    fn show_ply(&mut self, ply: usize) {
+        let board = self.tab.game.board();
+        let ply = ply.max(self.tab.game.setup_plies());
+        self.tab.browsed = (ply < board.ply()).then(|| {
+            let mut earlier = board.clone();
+            while earlier.ply() > ply {
+                earlier.undo();
             }
+            earlier
         });
-        vec![grid, chesses, overlay]
+        // the marks of the game's position do not belong to an earlier one
+        self.tab.board.chesses_changed();
+        self.tab.board.close_context_menu();
+        self.update_clickable();
+        if self.tab.browsed.is_some() {
+            self.tab.board.set_offers(vec![]);
+            self.tab.board.set_forbidden(vec![]);
+        } else {
+            self.tab.board.set_offers(self.tab.opening.offers().to_vec());
+            self.tab.board.set_forbidden(Self::forbidden_points(&self.tab.game));
+            if let Some(line) = self.tab.game.winning_line() {
+                self.tab.board.set_winning_line(line.to_vec());
+            }
+            self.update_heatmap();
+            self.update_threats();
+        }
+    }
+
+    fn update_heatmap(&mut self) {
+        let scores = if self.show_heatmap { Engine::new(Self::HEATMAP_DEPTH).score_moves(&self.tab.game) } else { vec![] };
+        self.tab.board.set_heatmap(&scores);
+    }
+
+    /// Shows the threats the side to move has to answer, those of the side which just moved.
+    fn update_threats(&mut self) {
+        let threats = match self.tab.game.state() {
+            GameState::Wait(ChessColor::Black) if self.config.show_threats => find_threats(self.tab.game.board(), ChessColor::White),
+            GameState::Wait(ChessColor::White) if self.config.show_threats => find_threats(self.tab.game.board(), ChessColor::Black),
+            _ => vec![],
+        };
+        self.tab.board.set_threats(threats);
     }
 
-    fn mouse_interaction(
-        &self,
-        _state: &Self::State,
-        bounds: Rectangle,
-        cursor: Cursor,
-    ) -> Interaction {
-        match cursor.position_in(bounds) {
-            Some(pos) => {
-                match self.grid_pos(pos.x, pos.y, 0.6) {
-                    Some(_) => Interaction::Pointer,
-                    None => Interaction::default(),
+    /// Turns the board half around while white is to move when playing face to face, so the
+    /// board faces whoever is to move.
+    fn update_half_turn(&mut self) {
+        let half_turn = self.config.auto_rotate && self.tab.game.state() == GameState::Wait(ChessColor::White);
+        self.tab.board.set_half_turn(half_turn);
+    }
+
+    /// Where black to move may not play, shown so Renju players are not surprised by refused moves.
+    fn forbidden_points(game: &Game) -> Vec<usize> {
+        if game.rule_set() == RuleSet::Renju && game.state() == GameState::Wait(ChessColor::Black) {
+            Renju::forbidden_points(game.board())
+        } else {
+            vec![]
+        }
+    }
+
+    /// Plays the effects for a game which has just ended.
+    fn on_state_changed(&mut self, state: GameState) {
+        if state.is_over() && self.replay.is_none() && self.tab.trial_of.is_none() && self.tab.game.setup_plies() == 0 {
+            if let Some(opening) = Opening::of(self.tab.game.board()) {
+                self.opening_stats.add(opening, state);
+            }
+        }
+        if state.is_over() && self.tab.trial_of.is_none() && !self.tab.result_counted {
+            self.tab.result_counted = true;
+            if let Some(hotseat) = self.tab.hotseat.as_mut() {
+                hotseat.record(state, self.tab.opening.first_player_black());
+            }
+            if self.config.json_game_log && self.replay.is_none() {
+                let engine_time = (self.tab.engine_side != EngineSide::Off).then_some(self.config.engine_time.0);
+                let players = [self.player(ChessColor::Black), self.player(ChessColor::White)];
+                game_log::append(&self.tab.game, &players, self.tab.opening.rule(), engine_time);
+            }
+            if let (Some(black), Some(white), None) = (self.player(ChessColor::Black), self.player(ChessColor::White), &self.replay) {
+                self.scoreboard.add(black, white, state);
+            }
+        }
+        match state {
+            GameState::Win(_) => {
+                println!("Game over, {:?}", state);
+                if let Some(line) = self.tab.game.winning_line() {
+                    self.tab.board.set_winning_line(line.to_vec());
+                    self.tab.board.set_celebration(Some(0.0));
+                    self.tab.celebration_start = Some(Instant::now());
                 }
+                self.sound.play(self.result_sound(state));
             },
-            None => Interaction::default(),
-        }
+            GameState::Draw => {
+                println!("Game over, {:?}", state);
+                self.sound.play(SoundEffect::Draw);
+            },
+            _ => ()
+        };
+    }
+}
+
+/// Seconds with one decimal below a minute, minutes and seconds above.
+fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs_f32();
+    if seconds < 60.0 {
+        format!("{:.1} s", seconds)
+    } else {
+        format!("{}:{:02}", time.as_secs() / 60, time.as_secs() % 60)
     }
 }
 
+/// Mixes `seed` into a pseudo random number with the SplitMix64 generator, enough to draw colors
+/// and openings from the time the player took to press a button.
+fn split_mix(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
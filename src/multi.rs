@@ -0,0 +1,112 @@
+//! The experimental mode for three or more players at one board: picking the players' colors,
+//! then a freestyle [`Game`] on a larger board where they take turns. Five or more in a row wins.
+
+use iced::Element;
+
+use gomoku_rs::board::{Board, ChessColor};
+use gomoku_rs::board_view::{BoardEvent, BoardView};
+use gomoku_rs::game::{Game, GameState, MoveError};
+use gomoku_rs::rules::RuleSet;
+
+use crate::Message;
+
+pub const MIN_PLAYERS: usize = 3;
+pub const MAX_PLAYERS: usize = 6;
+/// Size of the board, larger than for two so the players do not just block each other.
+const BOARD_SIZE: usize = 19;
+
+pub struct Multi {
+    colors: Vec<ChessColor>,
+    /// `None` while the players pick their colors.
+    game: Option<Game>,
+    board: BoardView,
+}
+
+impl Multi {
+    pub fn new() -> Self {
+        let board = BoardView::new(BOARD_SIZE, 30.0, 36.0, 32.0, 2.0);
+        Self { colors: ChessColor::ALL[..MIN_PLAYERS].to_vec(), game: None, board }
+    }
+
+    pub fn colors(&self) -> &[ChessColor] {
+        &self.colors
+    }
+
+    pub fn game(&self) -> Option<&Game> {
+        self.game.as_ref()
+    }
+
+    pub fn board_mut(&mut self) -> &mut BoardView {
+        &mut self.board
+    }
+
+    /// Whether the hover marker still glides to the point under the mouse.
+    pub fn is_hover_gliding(&self) -> bool {
+        self.board.is_hover_gliding()
+    }
+
+    /// Plays with `count` players, the new ones taking colors nobody has yet.
+    pub fn set_players(&mut self, count: usize) {
+        self.colors.truncate(count);
+        while self.colors.len() < count {
+            let Some(&free) = ChessColor::ALL.iter().find(|color| !self.colors.contains(color)) else { break; };
+            self.colors.push(free);
+        }
+    }
+
+    pub fn set_color(&mut self, player: usize, color: ChessColor) {
+        if let Some(old) = self.colors.get_mut(player) {
+            *old = color;
+        }
+    }
+
+    /// Whether every player has a color of their own, so the chesses can be told apart.
+    pub fn colors_differ(&self) -> bool {
+        self.colors.iter().enumerate().all(|(i, color)| !self.colors[..i].contains(color))
+    }
+
+    /// Starts a game on an empty board with the picked colors.
+    pub fn start(&mut self) {
+        self.game = Some(Game::new(Board::with_players(BOARD_SIZE, self.colors.clone()), RuleSet::Freestyle));
+        self.board.set_winning_line(vec![]);
+        self.board.chesses_changed();
+    }
+
+    /// Puts a chess of the player to move at `index`.
+    pub fn put_chess(&mut self, index: usize) -> Result<GameState, MoveError> {
+        let Some(game) = &mut self.game else { return Err(MoveError::GameOver); };
+        let state = game.put_chess(index)?;
+        self.board.set_winning_line(game.winning_line().map(<[usize]>::to_vec).unwrap_or_default());
+        self.board.chesses_changed();
+        Ok(state)
+    }
+
+    /// Takes back the last chess.
+    pub fn undo(&mut self) {
+        if let Some(game) = &mut self.game {
+            game.undo();
+            self.board.set_winning_line(vec![]);
+            self.board.chesses_changed();
+        }
+    }
+
+    /// Whose turn it is or how the game ended, like "Red to move".
+    pub fn state_text(&self) -> String {
+        match self.game.as_ref().map(Game::state) {
+            None => String::from("Pick the players' colors"),
+            Some(GameState::Wait(color)) => format!("{} to move", color),
+            Some(GameState::Win(color)) => format!("{} wins", color),
+            Some(GameState::Draw) => String::from("Draw"),
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        match &self.game {
+            Some(game) => self.board.view(game.board(), |event| match event {
+                BoardEvent::Click(index) => Message::ClickMulti(index),
+                event => Message::from_board(event),
+            }),
+            None => iced::widget::horizontal_space().height(iced::Length::Fill).into(),
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//! Desktop notifications telling the player it is their turn while the window is in the
+//! background. They are only shown when the crate is built with the `notifications` feature.
+
+/// Shows a notification with `summary` and `body`.
+pub fn notify(summary: &str, body: &str) {
+    #[cfg(feature = "notifications")]
+    if let Err(e) = notify_rust::Notification::new().appname("Gomoku").summary(summary).body(body).show() {
+        println!("Cannot show notification: {}", e);
+    }
+    #[cfg(not(feature = "notifications"))]
+    println!("Notification \"{}\": {}", summary, body);
+}
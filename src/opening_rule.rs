@@ -0,0 +1,290 @@
+//! Tournament procedures for the first moves of a game, which decide who plays which color by
+//! letting the players swap and black offer several fifth moves for white to choose from.
+//!
+//! The procedure is written as a list of [`Step`]s, an [`OpeningProtocol`] walks a game through it.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::board::Board;
+use crate::game::{Game, MoveError};
+use crate::symmetry::Symmetry;
+
+/// How the first moves of a game are decided.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum OpeningRule {
+    /// The players simply take turns from the first move on.
+    #[default]
+    Free,
+    /// Black puts one of the 26 openings, white may swap, white puts the fourth move and declares
+    /// up to 8 fifth moves, black may swap, black offers them and white picks one.
+    Soosorv8,
+    /// The first four moves are put in the center and within 1, 2 and 3 lines of it, and the side
+    /// to move may swap after each of them. Black then offers 10 fifth moves and white picks one.
+    Taraguchi10,
+}
+
+/// One part of an opening procedure.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Step {
+    /// The side to move puts a chess at most `reach` lines away from the center, anywhere when `None`.
+    Put { reach: Option<usize> },
+    /// The side to move decides whether the players swap colors.
+    Swap,
+    /// The side to move declares how many fifth moves black offers, from 1 to `max`.
+    Declare { max: usize },
+    /// Black puts the declared number of fifth moves, or `count` when nothing was declared.
+    /// No two of them may be the same move in a rotated or mirrored position.
+    Offer { count: usize },
+    /// White picks one of the offered moves as black's fifth move.
+    Pick,
+}
+
+impl OpeningRule {
+    pub const ALL: [OpeningRule; 3] = [OpeningRule::Free, OpeningRule::Soosorv8, OpeningRule::Taraguchi10];
+
+    pub fn steps(self) -> &'static [Step] {
+        match self {
+            OpeningRule::Free => &[],
+            OpeningRule::Soosorv8 => &[
+                Step::Put { reach: Some(0) },
+                Step::Put { reach: Some(1) },
+                Step::Put { reach: Some(2) },
+                Step::Swap,
+                Step::Put { reach: None },
+                Step::Declare { max: 8 },
+                Step::Swap,
+                Step::Offer { count: 8 },
+                Step::Pick,
+            ],
+            OpeningRule::Taraguchi10 => &[
+                Step::Put { reach: Some(0) },
+                Step::Swap,
+                Step::Put { reach: Some(1) },
+                Step::Swap,
+                Step::Put { reach: Some(2) },
+                Step::Swap,
+                Step::Put { reach: Some(3) },
+                Step::Swap,
+                Step::Offer { count: 10 },
+                Step::Pick,
+            ],
+        }
+    }
+}
+
+impl fmt::Display for OpeningRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OpeningRule::Free => "Free",
+            OpeningRule::Soosorv8 => "Soosõrv-8",
+            OpeningRule::Taraguchi10 => "Taraguchi-10",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for OpeningRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // accept the name without diacritics, it is easier to type
+        let name = s.trim().to_lowercase().replace('õ', "o");
+        OpeningRule::ALL.iter()
+            .find(|rule| rule.to_string().to_lowercase().replace('õ', "o") == name)
+            .copied()
+            .ok_or_else(|| format!("unknown opening rule \"{}\"", s))
+    }
+}
+
+/// Why an [`OpeningProtocol`] refused a click or an answer.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum OpeningError {
+    /// The current step is not about this, e.g. a click while a swap is waiting to be answered.
+    WrongStep,
+    /// The chess must be at most this many lines away from the center.
+    TooFar(usize),
+    /// The number of offers must be from 1 to this.
+    TooManyOffers(usize),
+    /// The offer is the same as an earlier one in a rotated or mirrored position.
+    Symmetric,
+    /// Only an offered move can be picked.
+    NotOffered,
+    Illegal(MoveError),
+}
+
+impl fmt::Display for OpeningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpeningError::WrongStep => write!(f, "the opening waits for something else"),
+            OpeningError::TooFar(reach) => write!(f, "must be at most {} lines from the center", reach),
+            OpeningError::TooManyOffers(max) => write!(f, "offer from 1 to {} moves", max),
+            OpeningError::Symmetric => write!(f, "same as an earlier offer when the board is turned"),
+            OpeningError::NotOffered => write!(f, "pick one of the offered moves"),
+            OpeningError::Illegal(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// How far a game got through the steps of its [`OpeningRule`].
+#[derive(Clone, Debug)]
+pub struct OpeningProtocol {
+    rule: OpeningRule,
+    step: usize,
+    /// Whether the player who started the game holds black, flipped by every swap.
+    first_player_black: bool,
+    declared: Option<usize>,
+    offers: Vec<usize>,
+}
+
+impl OpeningProtocol {
+    /// Starts the procedure of `rule` for a game on an empty board.
+    pub fn new(rule: OpeningRule) -> Self {
+        Self { rule, step: 0, first_player_black: true, declared: None, offers: vec![] }
+    }
+
+    pub fn rule(&self) -> OpeningRule {
+        self.rule
+    }
+
+    /// The step waiting to be done, `None` once the opening is over.
+    pub fn step(&self) -> Option<Step> {
+        self.rule.steps().get(self.step).copied()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.step().is_none()
+    }
+
+    pub fn first_player_black(&self) -> bool {
+        self.first_player_black
+    }
+
+    /// Fifth moves black offered so far.
+    pub fn offers(&self) -> &[usize] {
+        &self.offers
+    }
+
+    /// Number of fifth moves black has to offer.
+    pub fn offer_count(&self) -> usize {
+        match self.step() {
+            Some(Step::Offer { count }) => self.declared.unwrap_or(count),
+            _ => self.declared.unwrap_or(0),
+        }
+    }
+
+    /// Handles a click at `index` during a [`Step::Put`], [`Step::Offer`] or [`Step::Pick`].
+    /// Offering a move again takes the offer back.
+    pub fn click(&mut self, game: &mut Game, index: usize) -> Result<(), OpeningError> {
+        match self.step() {
+            Some(Step::Put { reach }) => {
+                if let Some(reach) = reach {
+                    if distance_from_center(game.board(), index) > reach {
+                        return Err(OpeningError::TooFar(reach));
+                    }
+                }
+                game.put_chess(index).map_err(OpeningError::Illegal)?;
+                self.step += 1;
+            },
+            Some(Step::Offer { .. }) => {
+                if let Some(i) = self.offers.iter().position(|&offer| offer == index) {
+                    self.offers.remove(i);
+                    return Ok(());
+                }
+                game.rules().is_legal(game.board(), index)
+                    .map_err(|violation| OpeningError::Illegal(MoveError::Illegal(violation)))?;
+                if self.offers.iter().any(|&offer| symmetric(game.board(), offer, index)) {
+                    return Err(OpeningError::Symmetric);
+                }
+                self.offers.push(index);
+                if self.offers.len() >= self.offer_count() {
+                    self.step += 1;
+                }
+            },
+            Some(Step::Pick) => {
+                if !self.offers.contains(&index) {
+                    return Err(OpeningError::NotOffered);
+                }
+                game.put_chess(index).map_err(OpeningError::Illegal)?;
+                self.offers.clear();
+                self.step += 1;
+            },
+            _ => return Err(OpeningError::WrongStep),
+        }
+        Ok(())
+    }
+
+    /// Answers a [`Step::Swap`], the players exchange colors when `swap` is `true`.
+    pub fn swap(&mut self, swap: bool) -> Result<(), OpeningError> {
+        if self.step() != Some(Step::Swap) {
+            return Err(OpeningError::WrongStep);
+        }
+        if swap {
+            self.first_player_black = !self.first_player_black;
+        }
+        self.step += 1;
+        Ok(())
+    }
+
+    /// Answers a [`Step::Declare`] with the number of fifth moves black will offer.
+    pub fn declare(&mut self, count: usize) -> Result<(), OpeningError> {
+        let Some(Step::Declare { max }) = self.step() else { return Err(OpeningError::WrongStep); };
+        if count == 0 || count > max {
+            return Err(OpeningError::TooManyOffers(max));
+        }
+        self.declared = Some(count);
+        self.step += 1;
+        Ok(())
+    }
+
+    /// The rule and how far the game got through it, like "Soosõrv-8; 7; white; 3; 96 98",
+    /// in the form [`Self::restore`] reads. The last parts are the declared number of fifth
+    /// moves, `-` when none was declared, and the indices of the offered ones.
+    pub fn saved(&self) -> String {
+        let side = if self.first_player_black { "black" } else { "white" };
+        let declared = self.declared.map_or(String::from("-"), |count| count.to_string());
+        let offers = self.offers.iter().map(usize::to_string).collect::<Vec<_>>().join(" ");
+        format!("{}; {}; {}; {}; {}", self.rule, self.step, side, declared, offers)
+    }
+
+    /// Reads what [`Self::saved`] wrote for a game on `board`.
+    pub fn restore(saved: &str, board: &Board) -> Result<Self, String> {
+        let parts: Vec<&str> = saved.split(';').map(str::trim).collect();
+        let [rule, step, side, declared, offers] = parts[..] else {
+            return Err(format!("cannot read opening \"{}\"", saved));
+        };
+        let rule: OpeningRule = rule.parse()?;
+        let step = step.parse().ok().filter(|&step| step <= rule.steps().len())
+            .ok_or_else(|| format!("bad opening step \"{}\"", step))?;
+        let first_player_black = match side {
+            "black" => true,
+            "white" => false,
+            _ => return Err(format!("bad first player \"{}\"", side)),
+        };
+        let declared = match declared {
+            "-" => None,
+            count => Some(count.parse().map_err(|_| format!("bad number of offers \"{}\"", count))?),
+        };
+        let offers = offers.split_whitespace()
+            .map(|offer| offer.parse().ok().filter(|&index| index < board.cells().len())
+                .ok_or_else(|| format!("bad offer \"{}\"", offer)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rule, step, first_player_black, declared, offers })
+    }
+}
+
+/// Lines between `index` and the center, counting diagonal steps as one.
+fn distance_from_center(board: &Board, index: usize) -> usize {
+    let center = board.cells_per_row() / 2;
+    let pos = board.index_to_pos(index);
+    pos.x.abs_diff(center).max(pos.y.abs_diff(center))
+}
+
+/// Whether a rotation or reflection around the center which leaves `board` as it is turns
+/// the point `a` into the point `b`.
+fn symmetric(board: &Board, a: usize, b: usize) -> bool {
+    Symmetry::ALL[1..].iter().any(|&symmetry| {
+        symmetry.apply_index(board, a) == b
+            && (0..board.cells().len()).all(|index| board.cell(symmetry.apply_index(board, index)) == board.cell(index))
+    })
+}
@@ -0,0 +1,71 @@
+//! How finished games went for every named opening, stored under the `opening_stats` storage key
+//! as `<name> <black wins> <white wins> <draws>` lines. The lines of an opening add up, each game
+//! appends one.
+
+use std::collections::HashMap;
+
+use gomoku_rs::board::ChessColor;
+use gomoku_rs::game::GameState;
+use gomoku_rs::openings::Opening;
+
+use crate::storage;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Record {
+    pub black_wins: u32,
+    pub white_wins: u32,
+    pub draws: u32,
+}
+
+impl Record {
+    pub fn games(&self) -> u32 {
+        self.black_wins + self.white_wins + self.draws
+    }
+}
+
+/// One [`Record`] per opening, keyed by [`Opening::key`] so the games of an opening count
+/// together however it was turned or mirrored on the board.
+pub struct OpeningStats {
+    records: HashMap<u64, Record>,
+}
+
+impl OpeningStats {
+    const KEY: &'static str = "opening_stats";
+    /// Rows of the board the keys are taken on, any size fitting the openings gives one key per opening.
+    const KEY_BOARD_SIZE: usize = 15;
+
+    pub fn load() -> Self {
+        let mut records = HashMap::new();
+        for line in storage::load(Self::KEY).unwrap_or_default().lines() {
+            let mut words = line.split_whitespace();
+            let Some(key) = words.next().and_then(Opening::by_name).and_then(Self::key) else { continue; };
+            let mut count = || words.next().and_then(|count| count.parse::<u32>().ok()).unwrap_or(0);
+            let record: &mut Record = records.entry(key).or_default();
+            record.black_wins += count();
+            record.white_wins += count();
+            record.draws += count();
+        }
+        Self { records }
+    }
+
+    fn key(opening: &Opening) -> Option<u64> {
+        opening.key(Self::KEY_BOARD_SIZE)
+    }
+
+    pub fn record_of(&self, opening: &Opening) -> Record {
+        Self::key(opening).and_then(|key| self.records.get(&key)).copied().unwrap_or_default()
+    }
+
+    /// Counts a game which ended as `state` after `opening` and appends it to the saved statistics.
+    pub fn add(&mut self, opening: &Opening, state: GameState) {
+        let Some(key) = Self::key(opening) else { return; };
+        let record = self.records.entry(key).or_default();
+        let line = match state {
+            GameState::Win(ChessColor::Black) => { record.black_wins += 1; "1 0 0" },
+            GameState::Win(ChessColor::White) => { record.white_wins += 1; "0 1 0" },
+            GameState::Draw => { record.draws += 1; "0 0 1" },
+            _ => return,
+        };
+        storage::append(Self::KEY, &format!("{} {}\n", opening.name, line));
+    }
+}
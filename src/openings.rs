@@ -0,0 +1,117 @@
+//! The 26 named Renju openings, made of black in the center, white next to it and a third
+//! chess within two lines of the center.
+
+use crate::board::{Board, ChessColor, Pos};
+use crate::symmetry::canonical_key;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum OpeningKind {
+    /// White is put straight next to the center.
+    Direct,
+    /// White is put diagonally next to the center.
+    Indirect,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Opening {
+    pub name: &'static str,
+    pub kind: OpeningKind,
+    /// Offset of the third chess from the center, with white above the center for direct openings
+    /// and to the upper right for indirect ones. Columns grow to the right, rows grow upwards.
+    third: (i32, i32),
+}
+
+const fn direct(name: &'static str, dx: i32, dy: i32) -> Opening {
+    Opening { name, kind: OpeningKind::Direct, third: (dx, dy) }
+}
+
+const fn indirect(name: &'static str, dx: i32, dy: i32) -> Opening {
+    Opening { name, kind: OpeningKind::Indirect, third: (dx, dy) }
+}
+
+pub const OPENINGS: [Opening; 26] = [
+    direct("Kansei", 0, 2),
+    direct("Keigetsu", 1, 2),
+    direct("Sosei", 2, 2),
+    direct("Kagetsu", 1, 1),
+    direct("Zangetsu", 2, 1),
+    direct("Ugetsu", 1, 0),
+    direct("Kinsei", 2, 0),
+    direct("Shogetsu", 0, -1),
+    direct("Kyugetsu", 1, -1),
+    direct("Shingetsu", 2, -1),
+    direct("Zuisei", 0, -2),
+    direct("Sangetsu", 1, -2),
+    direct("Yusei", 2, -2),
+    indirect("Chosei", 2, 2),
+    indirect("Kyogetsu", 1, 2),
+    indirect("Kosei", 0, 2),
+    indirect("Suigetsu", -1, 2),
+    indirect("Ryusei", -2, 2),
+    indirect("Ungetsu", 0, 1),
+    indirect("Hogetsu", -1, 1),
+    indirect("Rangetsu", -2, 1),
+    indirect("Gingetsu", -1, 0),
+    indirect("Myojo", -2, 0),
+    indirect("Shagetsu", -1, -1),
+    indirect("Meigetsu", -2, -1),
+    indirect("Suisei", -2, -2),
+];
+
+impl Opening {
+    pub fn by_name(name: &str) -> Option<&'static Opening> {
+        OPENINGS.iter().find(|opening| opening.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The three moves of the opening on `board`'s size, like "h8 h9 i10".
+    pub fn move_list(&self, board: &Board) -> String {
+        self.offsets().iter()
+            .filter_map(|&offset| from_center(board, offset))
+            .map(|pos| board.pos_to_coord(pos))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Key of the opening's position on a board of `cells_per_row` rows, see [`canonical_key`].
+    /// `None` when the board is too small for it.
+    pub fn key(&self, cells_per_row: usize) -> Option<u64> {
+        let board = Board::new(cells_per_row);
+        let points = self.offsets().iter().map(|&offset| from_center(&board, offset)).collect::<Option<Vec<_>>>()?;
+        let colors = [ChessColor::Black, ChessColor::White, ChessColor::Black];
+        Some(canonical_key(&position(cells_per_row, colors.into_iter().zip(points))?).0)
+    }
+
+    /// The opening the first three moves of `board` make, in any rotation or reflection.
+    pub fn of(board: &Board) -> Option<&'static Opening> {
+        let chesses = board.chesses().get(..3)?;
+        let first_moves = position(board.cells_per_row(), chesses.iter().map(|chess| (chess.color, chess.pos)))?;
+        let (key, _) = canonical_key(&first_moves);
+        OPENINGS.iter().find(|opening| opening.key(board.cells_per_row()) == Some(key))
+    }
+
+    /// Offsets of the three moves from the center.
+    fn offsets(&self) -> [(i32, i32); 3] {
+        let white = match self.kind {
+            OpeningKind::Direct => (0, 1),
+            OpeningKind::Indirect => (1, 1),
+        };
+        [(0, 0), white, self.third]
+    }
+}
+
+/// A board of `cells_per_row` rows with just `chesses` put in order, `None` when they do not fit.
+fn position(cells_per_row: usize, chesses: impl IntoIterator<Item = (ChessColor, Pos)>) -> Option<Board> {
+    let mut board = Board::new(cells_per_row);
+    for (color, pos) in chesses {
+        let index = board.pos_to_index(pos);
+        board.try_put_chess(color, index).ok()?;
+    }
+    Some(board)
+}
+
+fn from_center(board: &Board, (dx, dy): (i32, i32)) -> Option<Pos> {
+    let center = board.cells_per_row() as i32 / 2;
+    let (x, y) = (center + dx, center - dy);
+    let size = board.cells_per_row() as i32;
+    (x >= 0 && y >= 0 && x < size && y < size).then(|| Pos::new(x as usize, y as usize))
+}
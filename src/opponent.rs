@@ -0,0 +1,119 @@
+//! The engine playing one side of the game. It thinks in the background like the analysis, and
+//! plays the best move found once the search is through or its time per move is used up.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use iced::time::Duration;
+
+use gomoku_rs::board::ChessColor;
+use gomoku_rs::engine::{Engine, Style};
+use gomoku_rs::game::Game;
+
+use crate::thinking::{lock, Thinking};
+
+/// Which side the engine plays.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum EngineSide {
+    #[default]
+    Off,
+    Black,
+    White,
+}
+
+impl EngineSide {
+    pub const ALL: [EngineSide; 3] = [EngineSide::Off, EngineSide::Black, EngineSide::White];
+
+    pub fn plays(self, color: ChessColor) -> bool {
+        match self {
+            EngineSide::Off => false,
+            EngineSide::Black => color == ChessColor::Black,
+            EngineSide::White => color == ChessColor::White,
+        }
+    }
+}
+
+impl fmt::Display for EngineSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EngineSide::Off => "Nobody",
+            EngineSide::Black => "Black",
+            EngineSide::White => "White",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for EngineSide {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EngineSide::ALL.iter()
+            .find(|side| side.to_string().eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("unknown engine side \"{}\"", s))
+    }
+}
+
+/// What the engine came to after thinking for a step.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Decision {
+    Thinking,
+    Play(usize),
+    /// The search is through without finding a move the engine may play.
+    NoMove,
+}
+
+/// The engine of one game, thinking about a move while it is the engine's turn.
+pub struct Opponent {
+    /// Held by the search while it runs.
+    engine: Arc<Mutex<Engine>>,
+    /// The style of the engine, known without waiting for the search.
+    style: Style,
+    /// The search of the move being thought about, `None` between moves.
+    thinking: Option<Thinking>,
+}
+
+impl Opponent {
+    /// Deepest search, reached only when the time per move allows.
+    const MAX_DEPTH: u32 = 6;
+
+    pub fn new() -> Self {
+        Self { engine: Arc::new(Mutex::new(Engine::new(Self::MAX_DEPTH))), style: Style::default(), thinking: None }
+    }
+
+    /// Looks how far the search of `game` got, starting to think when it was not yet. Decides on
+    /// the move to play once there is nothing more to search or `time` has passed since the
+    /// engine started thinking.
+    pub fn step(&mut self, game: &Game, time: Duration) -> Decision {
+        let engine = &self.engine;
+        let progress = self.thinking.get_or_insert_with(|| Thinking::start(engine, game, Some(time))).step();
+        if !progress.finished {
+            return Decision::Thinking;
+        }
+        self.thinking = None;
+        progress.result.best_move.map_or(Decision::NoMove, Decision::Play)
+    }
+
+    /// Plays in `style` from the next move on, a move being thought about in another style is
+    /// thought about again.
+    pub fn set_style(&mut self, style: Style) {
+        if style != self.style {
+            self.stop();
+            lock(&self.engine).set_style(style);
+            self.style = style;
+        }
+    }
+
+    /// Stops thinking, the next step starts over from the game as it is then.
+    pub fn stop(&mut self) {
+        self.thinking = None;
+    }
+
+    /// Stops thinking and forgets what the engine learned about the game, for a new one.
+    pub fn forget(&mut self) {
+        self.stop();
+        lock(&self.engine).forget();
+    }
+}
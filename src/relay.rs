@@ -0,0 +1,130 @@
+//! Correspondence games through a relay server, which keeps the games while the app is closed.
+//! The player logs in with a name, the pending games are fetched then and every
+//! [`REFRESH_PERIOD`], and moves are sent without waiting for the answer.
+//!
+//! The server speaks a line protocol over TCP with the fields separated by tabs, one request per
+//! connection:
+//! - `pending <player>` is answered with a `game <id> <opponent> <rules> <color> <moves>` line for
+//!   every game of the player which is going on or which the opponent's last move ended, then
+//!   `end`. The color is the one the player holds, `black` or `white`, and the moves are
+//!   coordinates like "h8 i9".
+//! - `move <player> <id> <coord>` is answered with `ok`.
+//!
+//! Any request may be answered with `error <message>` instead.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use iced::futures::channel::oneshot;
+use iced::futures::SinkExt;
+use iced::Subscription;
+
+use gomoku_rs::board::ChessColor;
+use gomoku_rs::rules::RuleSet;
+
+/// How often the pending games are fetched again while logged in.
+pub const REFRESH_PERIOD: Duration = Duration::from_secs(60);
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A game waiting on the relay server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RemoteGame {
+    pub id: String,
+    pub opponent: String,
+    pub rule_set: RuleSet,
+    /// The color the player holds.
+    pub color: ChessColor,
+    pub moves: String,
+}
+
+impl RemoteGame {
+    fn parse(fields: &[&str]) -> Result<Self, String> {
+        let &[id, opponent, rule_set, color, moves] = fields else {
+            return Err(format!("cannot read game \"{}\"", fields.join("\t")));
+        };
+        let color = match color {
+            "black" => ChessColor::Black,
+            "white" => ChessColor::White,
+            _ => return Err(format!("bad color \"{}\"", color)),
+        };
+        Ok(Self { id: id.to_string(), opponent: opponent.to_string(), rule_set: rule_set.parse()?, color, moves: moves.to_string() })
+    }
+
+    /// Whether the player is to move, black starts.
+    pub fn my_turn(&self) -> bool {
+        let black_to_move = self.moves.split_whitespace().count().is_multiple_of(2);
+        black_to_move == (self.color == ChessColor::Black)
+    }
+}
+
+/// Fetches the games of `player` which are going on.
+pub async fn pending(server: String, player: String) -> Result<Vec<RemoteGame>, String> {
+    run(move || {
+        let lines = request(&server, &["pending", &player])?;
+        lines.iter()
+            .take_while(|line| line.as_str() != "end")
+            .map(|line| {
+                let fields: Vec<&str> = line.split('\t').collect();
+                match fields.split_first() {
+                    Some((&"game", game)) => RemoteGame::parse(game),
+                    _ => Err(format!("unexpected answer \"{}\"", line)),
+                }
+            })
+            .collect()
+    }).await
+}
+
+/// Sends the move at `coord` of `player` in the game `id`.
+pub async fn send_move(server: String, player: String, id: String, coord: String) -> Result<(), String> {
+    run(move || {
+        let lines = request(&server, &["move", &player, &id, &coord])?;
+        match lines.first().map(String::as_str) {
+            Some("ok") => Ok(()),
+            other => Err(format!("unexpected answer \"{}\"", other.unwrap_or_default())),
+        }
+    }).await
+}
+
+/// Produces a message every [`REFRESH_PERIOD`].
+pub fn refresh<Message: Clone + Send + 'static>(message: Message) -> Subscription<Message> {
+    iced::subscription::channel("relay refresh", 1, move |mut output| async move {
+        loop {
+            run(|| std::thread::sleep(REFRESH_PERIOD)).await;
+            let _ = output.send(message.clone()).await;
+        }
+    })
+}
+
+/// Runs `work`, which blocks, on a thread of its own so the app goes on meanwhile.
+#[cfg(not(target_arch = "wasm32"))]
+async fn run<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> T {
+    let (sender, receiver) = oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(work());
+    });
+    receiver.await.expect("relay thread ended without an answer")
+}
+
+/// Browsers have no threads, nor TCP, so `work` fails right away there.
+#[cfg(target_arch = "wasm32")]
+async fn run<T>(work: impl FnOnce() -> T) -> T {
+    work()
+}
+
+/// Sends one request and reads the answer lines until the server closes the connection.
+fn request(server: &str, fields: &[&str]) -> Result<Vec<String>, String> {
+    let connect = || -> std::io::Result<Vec<String>> {
+        let mut stream = TcpStream::connect(server)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        let line = fields.iter().map(|field| field.replace(['\t', '\n', '\r'], " ")).collect::<Vec<_>>().join("\t");
+        stream.write_all(format!("{}\n", line).as_bytes())?;
+        BufReader::new(stream).lines().collect()
+    };
+    let lines = connect().map_err(|e| format!("cannot reach {}: {}", server, e))?;
+    match lines.first().and_then(|line| line.strip_prefix("error\t")) {
+        Some(message) => Err(message.to_string()),
+        None => Ok(lines),
+    }
+}
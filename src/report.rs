@@ -0,0 +1,166 @@
+//! A printable HTML report of a game for going through it afterwards: the final position, every
+//! move with the engine's evaluation, the moves which threw away much of the position flagged as
+//! blunders, and the comments written on the moves.
+
+use std::collections::BTreeMap;
+
+use crate::board::{Board, CellState, ChessColor};
+use crate::engine::{Engine, WIN_SCORE};
+use crate::game::{Ending, Game, GameState};
+
+/// A move scoring this much less than the best one is flagged as a blunder, about the value of
+/// letting the opponent make a four.
+pub const BLUNDER_MARGIN: i32 = 1000;
+
+/// What the engine thinks of one move of the game.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveReview {
+    /// Score of the played move for the side which played it.
+    pub score: i32,
+    /// The move the engine likes best in the position, with its score, which is the played one's
+    /// when nothing was better.
+    pub best_move: Option<usize>,
+    pub best_score: i32,
+}
+
+impl MoveReview {
+    pub fn is_blunder(&self) -> bool {
+        self.best_score - self.score >= BLUNDER_MARGIN
+    }
+}
+
+/// Reviews every move of `game` after its setup by searching `depth` plies after each move
+/// which could have been played instead.
+pub fn review(game: &Game, depth: u32) -> Vec<MoveReview> {
+    let moves: Vec<usize> = game.board().moves().skip(game.setup_plies()).map(|mv| mv.index).collect();
+    let mut replay = game.clone();
+    while replay.board().ply() > game.setup_plies() || replay.ending().is_some() {
+        if !replay.undo() {
+            break;
+        }
+    }
+    let mut engine = Engine::new(depth);
+    let mut reviews = Vec::new();
+    for index in moves {
+        let scores = engine.score_moves(&replay);
+        let (best_move, best_score) = scores.iter()
+            .max_by_key(|&&(_, score)| score)
+            .map_or((None, 0), |&(best, score)| (Some(best), score));
+        let played = scores.iter().find(|&&(m, _)| m == index).map(|&(_, score)| score);
+        if replay.put_chess(index).is_err() {
+            break;
+        }
+        // a move the engine did not consider is scored from the opponent's side after it
+        let score = played.unwrap_or_else(|| match replay.state() {
+            GameState::Win(_) => WIN_SCORE,
+            GameState::Draw => 0,
+            _ => -engine.search(&replay).score,
+        });
+        reviews.push(MoveReview { score, best_move, best_score: best_score.max(score) });
+    }
+    reviews
+}
+
+/// The report as a standalone HTML page. `reviews` are those of the moves after the setup,
+/// `comments` are keyed by the ply of the move they are about.
+pub fn write(game: &Game, reviews: &[MoveReview], comments: &BTreeMap<usize, String>) -> String {
+    let board = game.board();
+    let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Gomoku game report</title>\n");
+    html.push_str("<style>\n\
+        body { font-family: sans-serif; max-width: 48em; margin: 2em auto; }\n\
+        table { border-collapse: collapse; width: 100%; }\n\
+        th, td { text-align: left; padding: 0.2em 0.6em; border-bottom: 1px solid #ddd; }\n\
+        tr.blunder { background: #fde2e2; }\n\
+        .flag { color: #c62828; font-weight: bold; }\n\
+        svg { display: block; margin: 1em auto; }\n\
+        </style>\n</head>\n<body>\n");
+    html.push_str("<h1>Gomoku game report</h1>\n");
+    html.push_str(&format!("<p>{} rules, {} moves. {}</p>\n", game.rule_set(), board.ply() - game.setup_plies(), result_text(game)));
+    html.push_str(&diagram(board, game.setup_plies()));
+    let blunders = reviews.iter().filter(|review| review.is_blunder()).count();
+    html.push_str(&format!("<h2>Moves</h2>\n<p>{} blunder{} flagged.</p>\n", blunders, if blunders == 1 { "" } else { "s" }));
+    html.push_str("<table>\n<tr><th>#</th><th>Side</th><th>Move</th><th>Evaluation</th><th>Best</th><th>Comment</th></tr>\n");
+    for (mv, review) in board.moves().skip(game.setup_plies()).zip(reviews.iter().map(Some).chain(std::iter::repeat(None))) {
+        let side = if mv.player == ChessColor::Black { "Black" } else { "White" };
+        let coord = board.pos_to_coord(board.index_to_pos(mv.index));
+        let (evaluation, best, blunder) = match review {
+            Some(review) => {
+                let best = review.best_move
+                    .filter(|_| review.best_score > review.score)
+                    .map_or(String::new(), |best| format!("{} ({})", board.pos_to_coord(board.index_to_pos(best)), score_text(review.best_score)));
+                (score_text(review.score), best, review.is_blunder())
+            },
+            None => (String::new(), String::new(), false),
+        };
+        let mut comment = String::new();
+        if blunder {
+            comment.push_str("<span class=\"flag\">Blunder</span> ");
+        }
+        if let Some(text) = comments.get(&mv.ply) {
+            comment.push_str(&escape(text));
+        }
+        html.push_str(&format!(
+            "<tr{}><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            if blunder { " class=\"blunder\"" } else { "" }, mv.ply + 1, side, coord, evaluation, best, comment));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+/// How the game ended, like "White wins by resignation."
+fn result_text(game: &Game) -> String {
+    let resigned = game.ending() == Some(Ending::Resignation);
+    match game.state() {
+        GameState::Win(color) if resigned => format!("{} wins by resignation.", color),
+        GameState::Win(color) => format!("{} wins.", color),
+        GameState::Draw => String::from("Draw."),
+        GameState::Wait(_) => String::from("The game is not over."),
+    }
+}
+
+/// A score like "+120", or "wins" and "loses" for a forced result.
+fn score_text(score: i32) -> String {
+    if score >= WIN_SCORE - 100 {
+        String::from("wins")
+    } else if score <= -(WIN_SCORE - 100) {
+        String::from("loses")
+    } else {
+        format!("{:+}", score)
+    }
+}
+
+/// The final position as an SVG picture, the moves after the setup numbered on their chesses.
+fn diagram(board: &Board, setup_plies: usize) -> String {
+    const CELL: usize = 24;
+    let size = board.cells_per_row();
+    let extent = (size + 1) * CELL;
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\n", extent);
+    svg.push_str(&format!("<rect width=\"{0}\" height=\"{0}\" fill=\"#e3b56b\"/>\n", extent));
+    for line in 0..size {
+        let at = (line + 1) * CELL;
+        svg.push_str(&format!(
+            "<line x1=\"{1}\" y1=\"{0}\" x2=\"{2}\" y2=\"{0}\" stroke=\"#333\"/><line x1=\"{0}\" y1=\"{1}\" x2=\"{0}\" y2=\"{2}\" stroke=\"#333\"/>\n",
+            at, CELL, size * CELL));
+    }
+    for (ply, chess) in board.chesses().iter().enumerate() {
+        // captured chesses are gone, a chess put on the point later is drawn over an earlier one
+        if board.cell(board.pos_to_index(chess.pos)) == Some(CellState::Empty) {
+            continue;
+        }
+        let (cx, cy) = ((chess.pos.x + 1) * CELL, (chess.pos.y + 1) * CELL);
+        let (fill, ink) = if chess.color == ChessColor::Black { ("#111", "#fff") } else { ("#fafafa", "#111") };
+        svg.push_str(&format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" stroke=\"#111\"/>\n", cx, cy, CELL / 2 - 1, fill));
+        if ply >= setup_plies {
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{}\">{}</text>\n",
+                cx, cy, ink, ply + 1));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// `text` with the characters which mean something in HTML written as entities.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,132 @@
+//! A local control server for bots and other programs, started with `--serve <port>`. It speaks
+//! JSON-RPC 2.0 over TCP on 127.0.0.1, one request per line and one response per line.
+//!
+//! Methods, each about the shown tab unless the params are an object naming another one with
+//! `"tab": id`:
+//! - `position`: the tab id, the board, the rules, the moves and whose turn it is.
+//! - `play` with `["h8"]` or `{"move": "h8"}`: puts a chess like a click, answers the new position.
+//! - `new_game`: starts a new game with the same rules, answers the new position.
+//! - `analyze` with `[depth]` or `{"depth": depth}`, 4 by default: the engine's best move, its
+//!   score for the side to move and the line it expects.
+//!
+//! The connection threads pass each call to the app through a subscription and wait for its answer.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use iced::futures::channel::mpsc as async_mpsc;
+use iced::futures::{SinkExt, StreamExt};
+use iced::Subscription;
+
+use crate::json::{self, Value};
+
+/// Error codes of the JSON-RPC specification, and one for a refused call.
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+pub const REFUSED: i32 = -32000;
+
+/// A request waiting for the app to answer it.
+#[derive(Clone, Debug)]
+pub struct Call {
+    pub method: String,
+    pub params: Value,
+    id: Value,
+    reply: mpsc::Sender<String>,
+}
+
+impl Call {
+    /// Sends the result, already written as JSON, or an error code with its message.
+    pub fn answer(&self, result: Result<String, (i32, String)>) {
+        let response = match result {
+            Ok(result) => format!("{{\"jsonrpc\":\"2.0\",\"id\":{},\"result\":{}}}", self.id, result),
+            Err((code, message)) => error(&self.id, code, &message),
+        };
+        // the connection may have closed meanwhile, nobody is left to tell
+        let _ = self.reply.send(response);
+    }
+
+    /// The parameter at `position` of a list, or named `name` in an object.
+    pub fn param(&self, position: usize, name: &str) -> Option<&Value> {
+        match &self.params {
+            Value::Array(items) => items.get(position),
+            params => params.get(name),
+        }
+    }
+}
+
+fn error(id: &Value, code: i32, message: &str) -> String {
+    format!("{{\"jsonrpc\":\"2.0\",\"id\":{},\"error\":{{\"code\":{},\"message\":{}}}}}", id, code, json::string(message))
+}
+
+/// Listens on `port` and produces the calls of all connections.
+pub fn subscription(port: u16) -> Subscription<Call> {
+    iced::subscription::channel(("rpc", port), 16, move |mut output| async move {
+        let (calls, mut received) = async_mpsc::unbounded();
+        std::thread::spawn(move || listen(port, calls));
+        while let Some(call) = received.next().await {
+            if output.send(call).await.is_err() {
+                break;
+            }
+        }
+        std::future::pending().await
+    })
+}
+
+fn listen(port: u16, calls: async_mpsc::UnboundedSender<Call>) {
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            println!("Cannot serve on port {}: {}", port, e);
+            return;
+        },
+    };
+    println!("Serving JSON-RPC on 127.0.0.1:{}", port);
+    for stream in listener.incoming().flatten() {
+        let calls = calls.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = serve(stream, calls) {
+                println!("JSON-RPC connection closed: {}", e);
+            }
+        });
+    }
+}
+
+/// Answers the requests of one connection until it closes.
+fn serve(stream: TcpStream, calls: async_mpsc::UnboundedSender<Call>) -> std::io::Result<()> {
+    const ANSWER_TIMEOUT: Duration = Duration::from_secs(60);
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match request(&line) {
+            Ok((id, method, params)) => {
+                let (reply, answer) = mpsc::channel();
+                let call = Call { method, params, id: id.clone(), reply };
+                if calls.unbounded_send(call).is_err() {
+                    return Ok(());
+                }
+                answer.recv_timeout(ANSWER_TIMEOUT).unwrap_or_else(|_| error(&id, REFUSED, "the app did not answer"))
+            },
+            Err((id, code, message)) => error(&id, code, &message),
+        };
+        writer.write_all(response.as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// The id, method and params of a request line.
+fn request(line: &str) -> Result<(Value, String, Value), (Value, i32, String)> {
+    let request = json::parse(line).map_err(|e| (Value::Null, PARSE_ERROR, e))?;
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let method = request.get("method").and_then(Value::as_str)
+        .ok_or_else(|| (id.clone(), INVALID_REQUEST, String::from("the request has no method")))?;
+    let params = request.get("params").cloned().unwrap_or(Value::Array(vec![]));
+    Ok((id.clone(), method.to_string(), params))
+}
@@ -0,0 +1,371 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::board::{Board, CellState, ChessColor, Pos};
+
+/// Why a chess may not be put at some point.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum RuleViolation {
+    OutOfBoard,
+    Occupied,
+    DoubleThree,
+    DoubleFour,
+    Overline,
+    /// Refused by the `is_legal` hook of a rules script.
+    Scripted,
+    /// A hook of a rules script failed, the message tells how.
+    ScriptFailed(String),
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            RuleViolation::OutOfBoard => "out of the board",
+            RuleViolation::Occupied => "point is occupied",
+            RuleViolation::DoubleThree => "forbidden double three",
+            RuleViolation::DoubleFour => "forbidden double four",
+            RuleViolation::Overline => "forbidden overline",
+            RuleViolation::Scripted => "forbidden by the rules script",
+            RuleViolation::ScriptFailed(e) => return write!(f, "{}", e),
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Outcome {
+    /// `line` holds the indices of the winning chesses.
+    Win { color: ChessColor, line: Vec<usize> },
+    /// `color` captured enough pairs to win.
+    Captures { color: ChessColor },
+    Draw,
+}
+
+/// A set of rules deciding which moves are legal and when a game is over.
+pub trait Rules {
+    /// Checks whether the side to move may put a chess at `index`.
+    fn is_legal(&self, board: &Board, index: usize) -> Result<(), RuleViolation> {
+        check_empty(board, index)
+    }
+
+    /// Chesses taken off the board by the chess just put at `last_index`, none unless the rules capture.
+    fn captures(&self, _board: &Board, _last_index: usize) -> Vec<usize> {
+        vec![]
+    }
+
+    /// Decides whether the game is over after the chess at `last_index` was put and its captures removed.
+    /// Only rules which run a script can fail to decide.
+    fn check_result(&self, board: &Board, last_index: usize) -> Result<Option<Outcome>, RuleViolation>;
+}
+
+/// Five or more in a row wins.
+pub struct Freestyle;
+
+/// Exactly five in a row wins, overlines do not count.
+pub struct Standard;
+
+/// Exactly five wins for black, who must not make double threes, double fours or overlines.
+/// Five or more wins for white.
+pub struct Renju;
+
+/// Five or more in a row wins unless both ends are blocked by the opponent.
+pub struct Caro;
+
+/// Five or more in a row wins, and so do five captured pairs. A pair of opponent chesses
+/// bracketed by the chess just put and another own chess is captured.
+pub struct Pente;
+
+impl Rules for Freestyle {
+    fn check_result(&self, board: &Board, last_index: usize) -> Result<Option<Outcome>, RuleViolation> {
+        Ok(check_lines(board, last_index, |line| line.len >= 5))
+    }
+}
+
+impl Rules for Standard {
+    fn check_result(&self, board: &Board, last_index: usize) -> Result<Option<Outcome>, RuleViolation> {
+        Ok(check_lines(board, last_index, |line| line.len == 5))
+    }
+}
+
+impl Rules for Renju {
+    fn is_legal(&self, board: &Board, index: usize) -> Result<(), RuleViolation> {
+        check_empty(board, index)?;
+        if board.next_is_black() {
+            if let Some(violation) = Renju::forbidden(board, index) {
+                return Err(violation);
+            }
+        }
+        Ok(())
+    }
+
+    fn check_result(&self, board: &Board, last_index: usize) -> Result<Option<Outcome>, RuleViolation> {
+        let is_black = board.cell(last_index) == Some(CellState::Black);
+        Ok(check_lines(board, last_index, |line| if is_black { line.len == 5 } else { line.len >= 5 }))
+    }
+}
+
+impl Rules for Caro {
+    fn check_result(&self, board: &Board, last_index: usize) -> Result<Option<Outcome>, RuleViolation> {
+        let opponent = if board.cell(last_index) == Some(CellState::Black) { CellState::White } else { CellState::Black };
+        Ok(check_lines(board, last_index, |line| {
+            line.len >= 5 && !(line.before == Some(opponent) && line.after == Some(opponent))
+        }))
+    }
+}
+
+impl Rules for Pente {
+    fn captures(&self, board: &Board, last_index: usize) -> Vec<usize> {
+        let own = board.cell(last_index);
+        let opponent = match own {
+            Some(CellState::Black) => Some(CellState::White),
+            Some(CellState::White) => Some(CellState::Black),
+            _ => return vec![],
+        };
+        let pos = board.index_to_pos(last_index);
+        let cell_at = |dx: i32, dy: i32, step: i32| offset(board, pos, dx, dy, step).map(|p| board.pos_to_index(p));
+        let mut captured = vec![];
+        for &(dx, dy) in Board::DIRECTIONS.iter() {
+            for (dx, dy) in [(dx, dy), (-dx, -dy)] {
+                let (Some(first), Some(second), Some(end)) = (cell_at(dx, dy, 1), cell_at(dx, dy, 2), cell_at(dx, dy, 3)) else {
+                    continue;
+                };
+                if board.cell(first) == opponent && board.cell(second) == opponent && board.cell(end) == own {
+                    captured.extend([first, second]);
+                }
+            }
+        }
+        captured
+    }
+
+    fn check_result(&self, board: &Board, last_index: usize) -> Result<Option<Outcome>, RuleViolation> {
+        let color = if board.cell(last_index) == Some(CellState::Black) { ChessColor::Black } else { ChessColor::White };
+        Ok(match check_lines(board, last_index, |line| line.len >= 5) {
+            Some(Outcome::Draw) | None if board.captured_by(color) >= Pente::PAIRS_TO_WIN * 2 => Some(Outcome::Captures { color }),
+            outcome => outcome,
+        })
+    }
+}
+
+impl Pente {
+    /// Captured pairs which win the game.
+    pub const PAIRS_TO_WIN: usize = 5;
+}
+
+impl Renju {
+    /// Why black may not put a chess at the empty point `index`, `None` if it is allowed or the
+    /// point is not empty.
+    pub fn forbidden(board: &Board, index: usize) -> Option<RuleViolation> {
+        let mut board = board.clone();
+        board.try_put_chess(ChessColor::Black, index).ok()?;
+        let pos = board.index_to_pos(index);
+
+        let lines = Board::DIRECTIONS.map(|(dx, dy)| Line::through(&board, pos, dx, dy));
+        // making five wins even if the move is forbidden otherwise
+        if lines.iter().any(|line| line.len == 5) {
+            return None;
+        }
+        if lines.iter().any(|line| line.len > 5) {
+            return Some(RuleViolation::Overline);
+        }
+        let fours: usize = Board::DIRECTIONS.iter().map(|&(dx, dy)| Self::count_fours(&board, pos, dx, dy)).sum();
+        if fours >= 2 {
+            return Some(RuleViolation::DoubleFour);
+        }
+        let threes = Board::DIRECTIONS.iter().filter(|&&(dx, dy)| Self::is_three(&board, pos, dx, dy)).count();
+        if threes >= 2 {
+            return Some(RuleViolation::DoubleThree);
+        }
+        None
+    }
+
+    /// All empty points where black may not put a chess.
+    pub fn forbidden_points(board: &Board) -> Vec<usize> {
+        (0..board.cells().len())
+            .filter(|&index| board.cell(index) == Some(CellState::Empty) && Self::forbidden(board, index).is_some())
+            .collect()
+    }
+
+    /// Empty points near `pos` in one direction where black would make exactly five including `pos`.
+    fn five_points(board: &Board, pos: Pos, dx: i32, dy: i32) -> Vec<i32> {
+        (-4..=4).filter(|&step| {
+            step != 0 && match offset(board, pos, dx, dy, step) {
+                Some(target) => {
+                    let mut board = board.clone();
+                    board.try_put_chess(ChessColor::Black, board.pos_to_index(target)).is_ok() && {
+                        let line = Line::through(&board, target, dx, dy);
+                        line.len == 5 && line.contains(-step)
+                    }
+                },
+                _ => false,
+            }
+        }).collect()
+    }
+
+    /// Number of fours black has through `pos` in one direction, a straight four counts once.
+    fn count_fours(board: &Board, pos: Pos, dx: i32, dy: i32) -> usize {
+        let points = Self::five_points(board, pos, dx, dy);
+        let straight = points.iter().any(|a| points.iter().any(|b| b - a == 5));
+        if straight { points.len() - 1 } else { points.len() }
+    }
+
+    /// Whether black has a real three through `pos` in one direction, i.e. one allowed move makes a straight four.
+    fn is_three(board: &Board, pos: Pos, dx: i32, dy: i32) -> bool {
+        (-4..=4).any(|step| {
+            step != 0 && match offset(board, pos, dx, dy, step) {
+                Some(target) => {
+                    let target_index = board.pos_to_index(target);
+                    let mut next = board.clone();
+                    if next.try_put_chess(ChessColor::Black, target_index).is_err() {
+                        return false;
+                    }
+                    let line = Line::through(&next, pos, dx, dy);
+                    // a straight four: both ends are empty and filling either one makes exactly five
+                    let not_black = |step: i32| offset(&next, pos, dx, dy, step)
+                        .is_none_or(|p| next.cell(next.pos_to_index(p)) != Some(CellState::Black));
+                    line.len == 4 && line.contains(step)
+                        && line.before == Some(CellState::Empty) && line.after == Some(CellState::Empty)
+                        && not_black(-(line.backward as i32) - 2) && not_black(line.forward as i32 + 2)
+                        && Self::forbidden(board, target_index).is_none()
+                },
+                _ => false,
+            }
+        })
+    }
+}
+
+/// Which rules a game is played with, selected when the game is set up.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum RuleSet {
+    #[default]
+    Freestyle,
+    Standard,
+    Renju,
+    Caro,
+    Pente,
+}
+
+impl RuleSet {
+    pub const ALL: [RuleSet; 5] = [RuleSet::Freestyle, RuleSet::Standard, RuleSet::Renju, RuleSet::Caro, RuleSet::Pente];
+
+    pub fn rules(self) -> &'static dyn Rules {
+        match self {
+            RuleSet::Freestyle => &Freestyle,
+            RuleSet::Standard => &Standard,
+            RuleSet::Renju => &Renju,
+            RuleSet::Caro => &Caro,
+            RuleSet::Pente => &Pente,
+        }
+    }
+
+    /// Whether chesses can be captured, so the number of captured pairs matters.
+    pub fn has_captures(self) -> bool {
+        self == RuleSet::Pente
+    }
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RuleSet::Freestyle => "Freestyle",
+            RuleSet::Standard => "Standard",
+            RuleSet::Renju => "Renju",
+            RuleSet::Caro => "Caro",
+            RuleSet::Pente => "Pente",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RuleSet::ALL.iter()
+            .find(|rule_set| rule_set.to_string().eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("unknown rules \"{}\"", s))
+    }
+}
+
+/// The run of same colored chesses through a point in one direction.
+struct Line {
+    pos: Pos,
+    dx: i32,
+    dy: i32,
+    backward: usize,
+    forward: usize,
+    len: usize,
+    /// Cell right before the run, `None` at the edge of the board.
+    before: Option<CellState>,
+    /// Cell right after the run, `None` at the edge of the board.
+    after: Option<CellState>,
+}
+
+impl Line {
+    fn through(board: &Board, pos: Pos, dx: i32, dy: i32) -> Self {
+        let state = board.cell(board.pos_to_index(pos)).unwrap_or(CellState::Empty);
+        let backward = board.count_direction(pos, -dx, -dy, state);
+        let forward = board.count_direction(pos, dx, dy, state);
+        let cell_at = |step: i32| offset(board, pos, dx, dy, step).and_then(|p| board.cell(board.pos_to_index(p)));
+        Self {
+            pos,
+            dx,
+            dy,
+            backward,
+            forward,
+            len: 1 + backward + forward,
+            before: cell_at(-(backward as i32) - 1),
+            after: cell_at(forward as i32 + 1),
+        }
+    }
+
+    /// Whether the point `step` times (`dx`, `dy`) away from `pos` is in the run.
+    fn contains(&self, step: i32) -> bool {
+        step >= -(self.backward as i32) && step <= self.forward as i32
+    }
+
+    fn indices(&self, board: &Board) -> Vec<usize> {
+        (-(self.backward as i32)..=self.forward as i32).map(|step| {
+            let col = self.pos.x as i32 + self.dx * step;
+            let row = self.pos.y as i32 + self.dy * step;
+            board.pos_to_index(Pos::new(col as usize, row as usize))
+        }).collect()
+    }
+}
+
+/// The check every rule set starts with, that `index` is an empty point of the board.
+pub fn check_empty(board: &Board, index: usize) -> Result<(), RuleViolation> {
+    if !board.valid_index(index) {
+        Err(RuleViolation::OutOfBoard)
+    } else if !board.is_empty_at(index) {
+        Err(RuleViolation::Occupied)
+    } else {
+        Ok(())
+    }
+}
+
+/// The point `step` times (`dx`, `dy`) away from `pos`, if it is on the board.
+fn offset(board: &Board, pos: Pos, dx: i32, dy: i32, step: i32) -> Option<Pos> {
+    let col = pos.x as i32 + dx * step;
+    let row = pos.y as i32 + dy * step;
+    let size = board.cells_per_row() as i32;
+    if col >= 0 && row >= 0 && col < size && row < size {
+        Some(Pos::new(col as usize, row as usize))
+    } else {
+        None
+    }
+}
+
+/// Finds a line through `last_index` accepted by `wins`, or a draw when the board is full.
+fn check_lines(board: &Board, last_index: usize, wins: impl Fn(&Line) -> bool) -> Option<Outcome> {
+    let color = board.cell(last_index).and_then(CellState::color)?;
+    let pos = board.index_to_pos(last_index);
+    let winning_line = Board::DIRECTIONS.iter()
+        .map(|&(dx, dy)| Line::through(board, pos, dx, dy))
+        .find(|line| wins(line));
+    match winning_line {
+        Some(line) => Some(Outcome::Win { color, line: line.indices(board) }),
+        None if board.is_full() => Some(Outcome::Draw),
+        None => None,
+    }
+}
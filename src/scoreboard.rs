@@ -0,0 +1,208 @@
+//! Results of all games between named players or against the engine, stored under the
+//! `scoreboard` storage key as `<result> <black> <white>` lines with the fields separated by tabs.
+//! The result is `B`, `W` or `D`, and the engine is written as `@<milliseconds per move>`.
+//! Games are only ever appended.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use gomoku_rs::board::ChessColor;
+use gomoku_rs::game::GameState;
+
+use crate::storage;
+
+/// Who played one side of a recorded game.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Player {
+    Named(String),
+    /// The engine with this many milliseconds per move.
+    Engine(u32),
+}
+
+impl Player {
+    fn parse(s: &str) -> Option<Self> {
+        match s.strip_prefix('@') {
+            Some(millis) => millis.parse().ok().map(Player::Engine),
+            None if !s.is_empty() => Some(Player::Named(s.to_string())),
+            None => None,
+        }
+    }
+
+    fn write(&self) -> String {
+        match self {
+            // tabs and line breaks would break the line apart
+            Player::Named(name) => name.replace(['\t', '\n', '\r'], " "),
+            Player::Engine(millis) => format!("@{}", millis),
+        }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Player::Named(name) => write!(f, "{}", name),
+            Player::Engine(millis) => write!(f, "Engine {:.1} s", *millis as f64 / 1000.0),
+        }
+    }
+}
+
+/// A column the scoreboard can be sorted by.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum ScoreColumn {
+    /// Pairings by name and engine levels from the fastest, the other columns from the most.
+    #[default]
+    Name,
+    Games,
+    Wins,
+    Losses,
+    Draws,
+}
+
+impl ScoreColumn {
+    pub const ALL: [ScoreColumn; 5] = [
+        ScoreColumn::Name, ScoreColumn::Games, ScoreColumn::Wins, ScoreColumn::Losses, ScoreColumn::Draws,
+    ];
+}
+
+impl fmt::Display for ScoreColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ScoreColumn::Name => "Name",
+            ScoreColumn::Games => "Games",
+            ScoreColumn::Wins => "Wins",
+            ScoreColumn::Losses => "Losses",
+            ScoreColumn::Draws => "Draws",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for ScoreColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ScoreColumn::ALL.iter()
+            .find(|column| column.to_string().eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("unknown score column \"{}\"", s))
+    }
+}
+
+/// Games of one pairing or engine level. Wins are those of the first named player of a pairing
+/// and of the players against an engine level, losses those of the other side.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScoreRow {
+    pub name: String,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl ScoreRow {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    fn add(&mut self, first_won: Option<bool>) {
+        match first_won {
+            Some(true) => self.wins += 1,
+            Some(false) => self.losses += 1,
+            None => self.draws += 1,
+        }
+    }
+}
+
+struct Entry {
+    black: Player,
+    white: Player,
+    state: GameState,
+}
+
+pub struct Scoreboard {
+    entries: Vec<Entry>,
+}
+
+impl Scoreboard {
+    const KEY: &'static str = "scoreboard";
+
+    pub fn load() -> Self {
+        let entries = storage::load(Self::KEY).unwrap_or_default().lines().filter_map(|line| {
+            let mut fields = line.split('\t');
+            let state = match fields.next()? {
+                "B" => GameState::Win(ChessColor::Black),
+                "W" => GameState::Win(ChessColor::White),
+                "D" => GameState::Draw,
+                _ => return None,
+            };
+            Some(Entry { black: Player::parse(fields.next()?)?, white: Player::parse(fields.next()?)?, state })
+        }).collect();
+        Self { entries }
+    }
+
+    /// Records a game which ended as `state` and appends it to the saved scoreboard.
+    pub fn add(&mut self, black: Player, white: Player, state: GameState) {
+        let result = match state {
+            GameState::Win(ChessColor::Black) => "B",
+            GameState::Win(ChessColor::White) => "W",
+            GameState::Draw => "D",
+            GameState::Win(_) | GameState::Wait(_) => return,
+        };
+        storage::append(Self::KEY, &format!("{}\t{}\t{}\n", result, black.write(), white.write()));
+        self.entries.push(Entry { black, white, state });
+    }
+
+    /// One row per pair of named players who played each other.
+    pub fn pairings(&self, sort: ScoreColumn) -> Vec<ScoreRow> {
+        let mut rows: BTreeMap<(&str, &str), ScoreRow> = BTreeMap::new();
+        for entry in self.entries.iter() {
+            let (Player::Named(black), Player::Named(white)) = (&entry.black, &entry.white) else { continue; };
+            let (first, second) = if black <= white { (black, white) } else { (white, black) };
+            let row = rows.entry((first, second)).or_insert_with(|| ScoreRow {
+                name: format!("{} – {}", first, second),
+                ..ScoreRow::default()
+            });
+            row.add(winner(entry.state).map(|black_won| black_won == (first == black)));
+        }
+        sorted(rows.into_values().collect(), sort)
+    }
+
+    /// One row per engine time per move, counting the games of all players against it.
+    pub fn engine_levels(&self, sort: ScoreColumn) -> Vec<ScoreRow> {
+        let mut rows: BTreeMap<u32, ScoreRow> = BTreeMap::new();
+        for entry in self.entries.iter() {
+            let (millis, engine_black) = match (&entry.black, &entry.white) {
+                (Player::Engine(millis), Player::Named(_)) => (*millis, true),
+                (Player::Named(_), Player::Engine(millis)) => (*millis, false),
+                _ => continue,
+            };
+            let row = rows.entry(millis).or_insert_with(|| ScoreRow {
+                name: Player::Engine(millis).to_string(),
+                ..ScoreRow::default()
+            });
+            row.add(winner(entry.state).map(|black_won| black_won != engine_black));
+        }
+        sorted(rows.into_values().collect(), sort)
+    }
+}
+
+/// Whether black won, `None` for a draw.
+fn winner(state: GameState) -> Option<bool> {
+    match state {
+        GameState::Win(ChessColor::Black) => Some(true),
+        GameState::Win(ChessColor::White) => Some(false),
+        _ => None,
+    }
+}
+
+/// `rows` in the order of `sort`, the rows come in the order of their names.
+fn sorted(mut rows: Vec<ScoreRow>, sort: ScoreColumn) -> Vec<ScoreRow> {
+    match sort {
+        ScoreColumn::Name => {},
+        ScoreColumn::Games => rows.sort_by_key(|row| std::cmp::Reverse(row.games())),
+        ScoreColumn::Wins => rows.sort_by_key(|row| std::cmp::Reverse(row.wins)),
+        ScoreColumn::Losses => rows.sort_by_key(|row| std::cmp::Reverse(row.losses)),
+        ScoreColumn::Draws => rows.sort_by_key(|row| std::cmp::Reverse(row.draws)),
+    }
+    rows
+}
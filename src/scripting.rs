@@ -0,0 +1,173 @@
+//! Rule variants and bots written in [Rhai](https://rhai.rs) scripts, built with the `scripting`
+//! feature.
+//!
+//! A script defines any of these functions, each getting the board as its first argument:
+//!
+//! ```text
+//! fn is_legal(board, x, y)   // true when the side to move may put a chess at x, y
+//! fn winner(board, x, y)     // after a chess was put at x, y: "black", "white", "draw" or ""
+//! fn choose_move(board)      // the move of a bot, as [x, y]
+//! ```
+//!
+//! Points count from 0 with `x` to the right and `y` down from the top row. The board has the
+//! properties `size`, `ply` and `next`, and `board.get(x, y)` tells what is at a point. Colors
+//! are the strings `"black"` and `"white"`, an empty point or a point off the board is `""`.
+
+use std::fmt;
+
+use rhai::{Array, Dynamic, Engine, Scope, AST};
+
+use crate::board::{Board, CellState, ChessColor};
+use crate::game::Game;
+use crate::rules::{self, Outcome, RuleSet, RuleViolation, Rules};
+
+/// Why a script could not be used.
+#[derive(Debug)]
+pub enum ScriptError {
+    Compile(String),
+    /// A hook failed while running.
+    Run(String),
+    /// A hook returned something other than described in the module documentation.
+    BadResult(String),
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Compile(e) => write!(f, "cannot compile script: {}", e),
+            ScriptError::Run(e) => write!(f, "script failed: {}", e),
+            ScriptError::BadResult(e) => write!(f, "script returned {}", e),
+        }
+    }
+}
+
+/// The board as scripts see it.
+#[derive(Clone)]
+struct ScriptBoard(Board);
+
+impl ScriptBoard {
+    fn get(&mut self, x: i64, y: i64) -> String {
+        let board = &self.0;
+        let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else { return String::new(); };
+        if !board.valid_pos(x, y) {
+            return String::new();
+        }
+        match board.cell(x + y * board.cells_per_row()) {
+            Some(CellState::Black) => String::from("black"),
+            Some(CellState::White) => String::from("white"),
+            _ => String::new(),
+        }
+    }
+}
+
+/// A compiled script.
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+}
+
+impl Script {
+    /// Operations a hook may run before it is stopped, so a looping script cannot hang the game.
+    const MAX_OPERATIONS: u64 = 10_000_000;
+
+    pub fn compile(source: &str) -> Result<Self, ScriptError> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(Self::MAX_OPERATIONS);
+        engine.register_type_with_name::<ScriptBoard>("Board")
+            .register_get("size", |board: &mut ScriptBoard| board.0.cells_per_row() as i64)
+            .register_get("ply", |board: &mut ScriptBoard| board.0.ply() as i64)
+            .register_get("next", |board: &mut ScriptBoard| {
+                String::from(if board.0.next_is_black() { "black" } else { "white" })
+            })
+            .register_fn("get", ScriptBoard::get);
+        let ast = engine.compile(source).map_err(|e| ScriptError::Compile(e.to_string()))?;
+        Ok(Self { engine, ast })
+    }
+
+    /// Whether the script defines the function `name` taking `params` arguments.
+    pub fn defines(&self, name: &str, params: usize) -> bool {
+        self.ast.iter_functions().any(|function| function.name == name && function.params.len() == params)
+    }
+
+    fn call<T: Clone + Send + Sync + 'static>(&self, name: &str, board: &Board, args: Vec<Dynamic>) -> Result<T, ScriptError> {
+        let mut all = vec![Dynamic::from(ScriptBoard(board.clone()))];
+        all.extend(args);
+        let result: Dynamic = self.engine.call_fn(&mut Scope::new(), &self.ast, name, all)
+            .map_err(|e| ScriptError::Run(e.to_string()))?;
+        let type_name = result.type_name();
+        result.try_cast().ok_or_else(|| ScriptError::BadResult(format!("{} from {}", type_name, name)))
+    }
+
+    /// Arguments for a hook about the point at `index`.
+    fn point(board: &Board, index: usize) -> Vec<Dynamic> {
+        let pos = board.index_to_pos(index);
+        vec![Dynamic::from(pos.x as i64), Dynamic::from(pos.y as i64)]
+    }
+
+    /// Asks `choose_move` for the next move of `game`, as an index on its board.
+    pub fn choose_move(&self, game: &Game) -> Result<usize, ScriptError> {
+        let board = game.board();
+        let point: Array = self.call("choose_move", board, vec![])?;
+        let bad = || ScriptError::BadResult(format!("{:?} from choose_move", point));
+        let [x, y] = point.as_slice() else { return Err(bad()); };
+        let (Some(x), Some(y)) = (x.as_int().ok(), y.as_int().ok()) else { return Err(bad()); };
+        let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else { return Err(bad()); };
+        if !board.valid_pos(x, y) {
+            return Err(bad());
+        }
+        Ok(x + y * board.cells_per_row())
+    }
+}
+
+/// The rules of `base` with the hooks the script defines taking their place.
+/// A hook failing refuses the move with [`RuleViolation::ScriptFailed`].
+pub struct ScriptRules {
+    script: Script,
+    base: RuleSet,
+    has_is_legal: bool,
+    has_winner: bool,
+}
+
+impl ScriptRules {
+    pub fn new(script: Script, base: RuleSet) -> Self {
+        let has_is_legal = script.defines("is_legal", 3);
+        let has_winner = script.defines("winner", 3);
+        Self { script, base, has_is_legal, has_winner }
+    }
+}
+
+impl Rules for ScriptRules {
+    fn is_legal(&self, board: &Board, index: usize) -> Result<(), RuleViolation> {
+        if !self.has_is_legal {
+            return self.base.rules().is_legal(board, index);
+        }
+        rules::check_empty(board, index)?;
+        match self.script.call::<bool>("is_legal", board, Script::point(board, index)) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(RuleViolation::Scripted),
+            Err(e) => Err(RuleViolation::ScriptFailed(e.to_string())),
+        }
+    }
+
+    fn captures(&self, board: &Board, last_index: usize) -> Vec<usize> {
+        self.base.rules().captures(board, last_index)
+    }
+
+    fn check_result(&self, board: &Board, last_index: usize) -> Result<Option<Outcome>, RuleViolation> {
+        if !self.has_winner {
+            return self.base.rules().check_result(board, last_index);
+        }
+        let winner = self.script.call::<rhai::ImmutableString>("winner", board, Script::point(board, last_index))
+            .map_err(|e| RuleViolation::ScriptFailed(e.to_string()))?;
+        match winner.as_str() {
+            "black" => Ok(Some(Outcome::Win { color: ChessColor::Black, line: vec![] })),
+            "white" => Ok(Some(Outcome::Win { color: ChessColor::White, line: vec![] })),
+            "draw" => Ok(Some(Outcome::Draw)),
+            "" if board.is_full() => Ok(Some(Outcome::Draw)),
+            "" => Ok(None),
+            other => Err(RuleViolation::ScriptFailed(
+                ScriptError::BadResult(format!("\"{}\" from winner", other)).to_string(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! The open tabs, panels and window size, stored under the `session` storage key so the next
+//! launch continues where the last one stopped.
+//!
+//! The session is written as `key = value` lines followed by one `game` line per tab, each
+//! followed by the four lines of the tab's saved game and the tab's own `opening`, `players`
+//! and `engine_side` keys. Sessions written before tabs kept those have just the game.
+
+use crate::storage;
+
+/// What is kept of one tab.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SavedTab {
+    pub game: String,
+    /// How far the game got through its opening rule, see `OpeningProtocol::saved`, empty for a free game.
+    pub opening: String,
+    /// The hotseat players and their score, see `Hotseat::saved`, empty when nobody sat down.
+    pub players: String,
+    /// Name of the side the engine plays, empty when it plays none.
+    pub engine_side: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Session {
+    /// The tabs in the order they are shown.
+    pub tabs: Vec<SavedTab>,
+    /// Position of the shown tab in `tabs`.
+    pub shown_tab: usize,
+    /// Name of the panel next to the board, empty when none is shown.
+    pub side_panel: String,
+    pub show_settings: bool,
+    pub show_heatmap: bool,
+    /// Window size in logical pixels, `None` for the default size.
+    pub window_size: Option<(u32, u32)>,
+}
+
+impl Session {
+    const KEY: &'static str = "session";
+    /// Lines of one saved game after its `game` line.
+    const GAME_LINES: usize = 4;
+
+    /// Reads the saved session, `None` when there is none.
+    pub fn load() -> Option<Self> {
+        storage::load(Self::KEY).map(|text| Self::parse(&text))
+    }
+
+    /// Reads a session written by [`Self::text`].
+    pub fn parse(text: &str) -> Self {
+        let mut session = Self::default();
+        let mut lines = text.lines();
+        while let Some(line) = lines.next() {
+            if line == "game" {
+                let game: String = lines.by_ref().take(Self::GAME_LINES).map(|line| format!("{}\n", line)).collect();
+                session.tabs.push(SavedTab { game, ..SavedTab::default() });
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else { continue; };
+            let value = value.trim();
+            match (key.trim(), session.tabs.last_mut()) {
+                ("opening", Some(tab)) => { tab.opening = value.to_string(); },
+                ("players", Some(tab)) => { tab.players = value.to_string(); },
+                ("engine_side", Some(tab)) => { tab.engine_side = value.to_string(); },
+                ("shown_tab", _) => { session.shown_tab = value.parse().unwrap_or(0); },
+                ("side_panel", _) => { session.side_panel = value.to_string(); },
+                ("show_settings", _) => { session.show_settings = value == "true"; },
+                ("show_heatmap", _) => { session.show_heatmap = value == "true"; },
+                ("window_size", _) => {
+                    session.window_size = value.split_once('x')
+                        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+                },
+                (key, _) => println!("Ignore session key \"{}\"", key),
+            }
+        }
+        session
+    }
+
+    /// The session in the form [`Self::parse`] reads.
+    pub fn text(&self) -> String {
+        let mut text = format!(
+            "shown_tab = {}\nside_panel = {}\nshow_settings = {}\nshow_heatmap = {}\n",
+            self.shown_tab, self.side_panel, self.show_settings, self.show_heatmap);
+        if let Some((width, height)) = self.window_size {
+            text.push_str(&format!("window_size = {}x{}\n", width, height));
+        }
+        for tab in &self.tabs {
+            text.push_str("game\n");
+            text.push_str(&tab.game);
+            for (key, value) in [("opening", &tab.opening), ("players", &tab.players), ("engine_side", &tab.engine_side)] {
+                if !value.is_empty() {
+                    text.push_str(&format!("{} = {}\n", key, value));
+                }
+            }
+        }
+        text
+    }
+
+    pub fn save(&self) {
+        storage::save(Self::KEY, &self.text());
+    }
+}
@@ -0,0 +1,57 @@
+//! Writing games in the Smart Game Format, where gomoku is game number 4.
+//!
+//! Measured thinking times are written in seconds as the private `BT` and `WT` properties of each
+//! move, named after the standard `BL` and `WL` which hold the time left rather than the time spent.
+//! A game started from a composed position lists its chesses with `AB` and `AW` and the side to
+//! move with `PL` in the root node.
+
+use crate::board::{ChessColor, Pos};
+use crate::game::{Ending, Game, GameState};
+
+/// The SGF `RE` value of a game, like "B+" or "W+Resign", `None` while it is still going on.
+pub fn result(game: &Game) -> Option<&'static str> {
+    let resigned = game.ending() == Some(Ending::Resignation);
+    match game.state() {
+        GameState::Win(ChessColor::Black) if resigned => Some("B+Resign"),
+        GameState::Win(ChessColor::White) if resigned => Some("W+Resign"),
+        GameState::Win(ChessColor::Black) => Some("B+"),
+        GameState::Win(ChessColor::White) => Some("W+"),
+        // SGF knows two players only
+        GameState::Win(_) => Some("?"),
+        GameState::Draw => Some("0"),
+        GameState::Wait(_) => None,
+    }
+}
+
+/// Two letters for the column and the row of `pos`, counted from the top left corner.
+pub fn point(pos: Pos) -> String {
+    format!("{}{}", (b'a' + pos.x as u8) as char, (b'a' + pos.y as u8) as char)
+}
+
+/// The whole game as a single SGF collection.
+pub fn write(game: &Game) -> String {
+    let board = game.board();
+    let mut sgf = format!("(;FF[4]GM[4]CA[UTF-8]AP[gomoku-rs]SZ[{}]RU[{}]", board.cells_per_row(), game.rule_set());
+    if let Some(result) = result(game) {
+        sgf.push_str(&format!("RE[{}]", result));
+    }
+    let (setup, moves) = board.chesses().split_at(game.setup_plies());
+    if !setup.is_empty() {
+        for (property, color) in [("AB", ChessColor::Black), ("AW", ChessColor::White)] {
+            let points: String = setup.iter().filter(|c| c.color == color).map(|c| format!("[{}]", point(c.pos))).collect();
+            if !points.is_empty() {
+                sgf.push_str(&format!("{}{}", property, points));
+            }
+        }
+        sgf.push_str(if game.first_player() == ChessColor::Black { "PL[B]" } else { "PL[W]" });
+    }
+    for (chess, time) in moves.iter().zip(&game.move_times()[game.setup_plies()..]) {
+        let color = if chess.color == ChessColor::Black { "B" } else { "W" };
+        sgf.push_str(&format!("\n;{}[{}]", color, point(chess.pos)));
+        if let Some(time) = time {
+            sgf.push_str(&format!("{}T[{:.1}]", color, time.as_secs_f32()));
+        }
+    }
+    sgf.push_str(")\n");
+    sgf
+}
@@ -0,0 +1,93 @@
+#[cfg(feature = "sound")]
+use std::time::Duration;
+
+#[cfg(feature = "sound")]
+use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
+#[cfg(feature = "sound")]
+use rodio::source::SineWave;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum SoundEffect {
+    PutBlack,
+    PutWhite,
+    Win,
+    /// The engine or a relay opponent beat the player at the board.
+    Loss,
+    Draw,
+    /// The player to move is about to run out of time.
+    LowTime,
+}
+
+/// Plays short synthesized effects, silent unless the crate is built with the `sound` feature.
+pub struct SoundPlayer {
+    muted: bool,
+    #[cfg(feature = "sound")]
+    output: Option<(OutputStream, OutputStreamHandle)>,
+}
+
+impl SoundPlayer {
+    pub fn new() -> Self {
+        Self {
+            muted: false,
+            #[cfg(feature = "sound")]
+            output: match OutputStream::try_default() {
+                Ok(output) => Some(output),
+                Err(e) => {
+                    println!("No audio output available, sound is disabled: {}", e);
+                    None
+                },
+            },
+        }
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    #[cfg(feature = "sound")]
+    pub fn play(&self, effect: SoundEffect) {
+        if self.muted {
+            return;
+        }
+        let Some((_, handle)) = &self.output else { return; };
+
+        // (frequency, milliseconds) of every note in the effect
+        let notes: &[(f32, u64)] = match effect {
+            SoundEffect::PutBlack => &[(220.0, 40)],
+            SoundEffect::PutWhite => &[(330.0, 40)],
+            SoundEffect::Win => &[(523.25, 120), (659.25, 120), (783.99, 240)],
+            SoundEffect::Loss => &[(392.0, 120), (329.63, 120), (261.63, 240)],
+            SoundEffect::Draw => &[(440.0, 160), (440.0, 160)],
+            SoundEffect::LowTime => &[(880.0, 60), (0.0, 60), (880.0, 60)],
+        };
+        match Sink::try_new(handle) {
+            Ok(sink) => {
+                for &(freq, millis) in notes {
+                    sink.append(SineWave::new(freq)
+                        .take_duration(Duration::from_millis(millis))
+                        .fade_in(Duration::from_millis(5))
+                        .amplify(0.2));
+                }
+                sink.detach();
+            },
+            Err(e) => { println!("Cannot play sound {:?}: {}", effect, e); }
+        }
+    }
+
+    #[cfg(not(feature = "sound"))]
+    pub fn play(&self, effect: SoundEffect) {
+        if !self.muted {
+            println!("Play sound {:?}", effect);
+        }
+    }
+}
+
+impl Default for SoundPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
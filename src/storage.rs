@@ -0,0 +1,226 @@
+//! Small named text blobs which survive restarts: files in the user's data directory with the
+//! `native` feature, the browser's local storage on the web, nowhere otherwise.
+//!
+//! The settings, the scores and the finished games can be kept in a folder of the user's choice
+//! instead, like one a sync tool shares between machines, see [`set_sync_dir`]. There every
+//! machine appends the records of its games to files of its own, named after the key and the
+//! machine, and loading merges the files of all machines, so games finished on two machines at
+//! once are all kept. The settings are a single file replaced in one step: when two machines
+//! change them, the one saving last wins.
+
+#[cfg(feature = "native")]
+use std::path::PathBuf;
+
+/// Names the sync folder, kept in the data directory of each machine.
+#[cfg(feature = "native")]
+const SYNC_DIR_KEY: &str = "sync_dir";
+
+/// Names this machine in the sync folder, kept in the data directory of each machine.
+#[cfg(feature = "native")]
+const MACHINE_ID_KEY: &str = "machine_id";
+
+/// Keys kept in the sync folder when there is one. The session and the event log stay on the machine.
+#[cfg(feature = "native")]
+const SYNCED_KEYS: [&str; 4] = ["config", "scoreboard", "opening_stats", "games.jsonl"];
+
+/// Synced keys which only grow by appended lines, kept in one file per machine.
+#[cfg(feature = "native")]
+const RECORD_KEYS: [&str; 3] = ["scoreboard", "opening_stats", "games.jsonl"];
+
+#[cfg(feature = "native")]
+fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("gomoku-rs"))
+}
+
+/// The folder the synced keys are kept in, `None` when they stay in the data directory.
+#[cfg(feature = "native")]
+pub fn sync_dir() -> Option<PathBuf> {
+    let dir = std::fs::read_to_string(data_dir()?.join(SYNC_DIR_KEY)).ok()?;
+    let dir = dir.trim();
+    (!dir.is_empty()).then(|| PathBuf::from(dir))
+}
+
+/// Keeps the synced keys in `dir` from now on, back in the data directory when it is empty.
+/// Keys missing there are copied over, those already there are kept as another machine wrote them.
+/// The records of this machine join those of the others in a sync folder, and the records of all
+/// machines come back to the data directory.
+#[cfg(feature = "native")]
+pub fn set_sync_dir(dir: &str) -> Result<(), String> {
+    let data = data_dir().ok_or_else(|| String::from("no data directory"))?;
+    let dir = dir.trim();
+    let target = if dir.is_empty() { data.clone() } else { PathBuf::from(dir) };
+    std::fs::create_dir_all(&target).map_err(|e| format!("cannot create {}: {}", target.display(), e))?;
+    for key in SYNCED_KEYS {
+        let to = match machine_id().filter(|_| !dir.is_empty() && RECORD_KEYS.contains(&key)) {
+            Some(machine) => target.join(format!("{}.{}", key, machine)),
+            None => target.join(key),
+        };
+        // another sync folder only gets this machine's records, the others bring their own
+        let value = if dir.is_empty() { load(key) } else { path(key).and_then(|from| std::fs::read_to_string(from).ok()) };
+        if let Some(value) = value.filter(|_| !to.exists()) {
+            write_whole(&to, &value).map_err(|e| format!("cannot copy {} to {}: {}", key, to.display(), e))?;
+        }
+    }
+    std::fs::create_dir_all(&data)
+        .and_then(|_| write_whole(&data.join(SYNC_DIR_KEY), dir))
+        .map_err(|e| format!("cannot remember the sync folder: {}", e))
+}
+
+/// A random name for this machine, made up the first time it is asked for.
+#[cfg(feature = "native")]
+fn machine_id() -> Option<String> {
+    use std::hash::BuildHasher;
+
+    let path = data_dir()?.join(MACHINE_ID_KEY);
+    if let Ok(id) = std::fs::read_to_string(&path) {
+        return Some(id.trim().to_string()).filter(|id| !id.is_empty());
+    }
+    // hashers are seeded randomly, which is all the randomness the standard library offers
+    let id = format!("{:016x}", std::collections::hash_map::RandomState::new().hash_one(std::process::id()));
+    let result = path.parent().map_or(Ok(()), std::fs::create_dir_all).and_then(|_| write_whole(&path, &id));
+    if let Err(e) = result {
+        println!("Cannot save the machine id to {}: {}", path.display(), e);
+        return None;
+    }
+    Some(id)
+}
+
+/// File backing `key`, when there is a data directory. The records of this machine when `key`
+/// is a record in the sync folder.
+#[cfg(feature = "native")]
+pub fn path(key: &str) -> Option<PathBuf> {
+    match sync_dir().filter(|_| SYNCED_KEYS.contains(&key)) {
+        Some(dir) if RECORD_KEYS.contains(&key) => Some(dir.join(format!("{}.{}", key, machine_id()?))),
+        Some(dir) => Some(dir.join(key)),
+        None => data_dir().map(|dir| dir.join(key)),
+    }
+}
+
+/// The records under `key` of every machine in the sync folder `dir`, each machine's in the order
+/// they were appended. A file named just `key` is from before records were kept per machine.
+#[cfg(feature = "native")]
+fn load_records(dir: &std::path::Path, key: &str) -> Option<String> {
+    let prefix = format!("{}.", key);
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir).ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_str().is_some_and(|name| name == key || name.starts_with(&prefix)))
+        .map(|entry| entry.path())
+        .collect();
+    files.sort();
+    let mut records = String::new();
+    for file in files {
+        let Ok(text) = std::fs::read_to_string(&file) else { continue; };
+        records.push_str(&text);
+        if !records.is_empty() && !records.ends_with('\n') {
+            records.push('\n');
+        }
+    }
+    Some(records)
+}
+
+/// Writes `value` to a temporary file next to `path` and moves it over `path`, so nobody reading
+/// `path` meanwhile sees half of it.
+#[cfg(feature = "native")]
+fn write_whole(path: &std::path::Path, value: &str) -> std::io::Result<()> {
+    let name = path.file_name().map_or(String::new(), |name| name.to_string_lossy().into_owned());
+    let temporary = path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+    std::fs::write(&temporary, value)?;
+    std::fs::rename(&temporary, path)
+}
+
+#[cfg(feature = "native")]
+pub fn load(key: &str) -> Option<String> {
+    match sync_dir().filter(|_| RECORD_KEYS.contains(&key)) {
+        Some(dir) => load_records(&dir, key),
+        None => std::fs::read_to_string(path(key)?).ok(),
+    }
+}
+
+#[cfg(feature = "native")]
+pub fn save(key: &str, value: &str) {
+    let Some(path) = path(key) else {
+        println!("No data directory, cannot save {}", key);
+        return;
+    };
+    let result = path.parent().map_or(Ok(()), std::fs::create_dir_all).and_then(|_| write_whole(&path, value));
+    if let Err(e) = result {
+        println!("Cannot save {} to {}: {}", key, path.display(), e);
+    }
+}
+
+/// Adds `text` to the end of what is saved under `key`, without reading it first.
+#[cfg(feature = "native")]
+pub fn append(key: &str, text: &str) {
+    use std::io::Write;
+
+    let Some(path) = path(key) else {
+        println!("No data directory, cannot append to {}", key);
+        return;
+    };
+    let result = path.parent().map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::OpenOptions::new().create(true).append(true).open(&path))
+        .and_then(|mut file| file.write_all(text.as_bytes()));
+    if let Err(e) = result {
+        println!("Cannot append to {} in {}: {}", key, path.display(), e);
+    }
+}
+
+#[cfg(not(feature = "native"))]
+pub fn append(key: &str, text: &str) {
+    save(key, &(load(key).unwrap_or_default() + text));
+}
+
+#[cfg(not(feature = "native"))]
+pub fn sync_dir() -> Option<std::path::PathBuf> {
+    None
+}
+
+#[cfg(not(feature = "native"))]
+pub fn set_sync_dir(_dir: &str) -> Result<(), String> {
+    Err(String::from("a sync folder is not supported here"))
+}
+
+/// Writes a file meant for the user, like an exported game, to the downloads directory and
+/// returns where it went.
+#[cfg(feature = "native")]
+pub fn export(file_name: &str, contents: &[u8]) -> Result<std::path::PathBuf, String> {
+    let dir = dirs::download_dir().or_else(dirs::home_dir).ok_or_else(|| String::from("no downloads directory"))?;
+    let path = dir.join(file_name);
+    std::fs::write(&path, contents).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+#[cfg(not(feature = "native"))]
+pub fn export(_file_name: &str, _contents: &[u8]) -> Result<std::path::PathBuf, String> {
+    Err(String::from("exporting files is not supported here"))
+}
+
+#[cfg(all(not(feature = "native"), target_arch = "wasm32"))]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+#[cfg(all(not(feature = "native"), target_arch = "wasm32"))]
+pub fn load(key: &str) -> Option<String> {
+    local_storage()?.get_item(&format!("gomoku-rs.{}", key)).ok().flatten()
+}
+
+#[cfg(all(not(feature = "native"), target_arch = "wasm32"))]
+pub fn save(key: &str, value: &str) {
+    match local_storage() {
+        Some(storage) => {
+            if storage.set_item(&format!("gomoku-rs.{}", key), value).is_err() {
+                println!("Cannot save {} to local storage", key);
+            }
+        },
+        None => { println!("No local storage, cannot save {}", key); }
+    }
+}
+
+#[cfg(all(not(feature = "native"), not(target_arch = "wasm32")))]
+pub fn load(_key: &str) -> Option<String> {
+    None
+}
+
+#[cfg(all(not(feature = "native"), not(target_arch = "wasm32")))]
+pub fn save(_key: &str, _value: &str) {}
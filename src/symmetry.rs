@@ -0,0 +1,65 @@
+//! The eight rotations and reflections of the square board, and position keys which are the same
+//! for all eight images of a position so tables can share one entry between them.
+
+use crate::board::{Board, ChessColor, Pos};
+
+/// One of the eight rotations and reflections of a board: mirrored left to right when bit 2 is
+/// set, then turned a quarter clockwise as often as the two lower bits say. `Symmetry(0)` leaves
+/// the board as it is.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Symmetry(pub u8);
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry(0), Symmetry(1), Symmetry(2), Symmetry(3), Symmetry(4), Symmetry(5), Symmetry(6), Symmetry(7),
+    ];
+
+    /// Where `pos` of a board with `size` rows goes.
+    pub fn apply(self, pos: Pos, size: usize) -> Pos {
+        let last = size - 1;
+        let mut pos = if self.0 & 4 != 0 { Pos::new(last - pos.x, pos.y) } else { pos };
+        for _ in 0..self.0 & 3 {
+            pos = Pos::new(last - pos.y, pos.x);
+        }
+        pos
+    }
+
+    /// Like [`Symmetry::apply`] for the index of a point on `board`.
+    pub fn apply_index(self, board: &Board, index: usize) -> usize {
+        board.pos_to_index(self.apply(board.index_to_pos(index), board.cells_per_row()))
+    }
+
+    /// The symmetry which undoes this one.
+    pub fn inverse(self) -> Self {
+        // a mirror followed by turns is its own inverse, plain turns are undone by turning back
+        if self.0 & 4 != 0 { self } else { Symmetry((4 - self.0) % 4) }
+    }
+}
+
+/// Key of the chesses on `board` and the side to move, the same for all eight images of the
+/// position. Also returns the symmetry turning `board` into the image the key was taken from, to
+/// store points in that image and turn them back with [`Symmetry::inverse`].
+pub fn canonical_key(board: &Board) -> (u64, Symmetry) {
+    let size = board.cells_per_row();
+    let mut keys = [if board.next_is_black() { 0 } else { WHITE_TO_MOVE }; 8];
+    for chess in board.stones() {
+        let color = if chess.color == ChessColor::Black { 0 } else { 1 };
+        for (key, symmetry) in keys.iter_mut().zip(Symmetry::ALL) {
+            let pos = symmetry.apply(chess.pos, size);
+            *key ^= point_key((pos.x + pos.y * size) * 2 + color);
+        }
+    }
+    let (i, &key) = keys.iter().enumerate().min_by_key(|&(_, key)| *key).expect("there are eight symmetries");
+    (key, Symmetry::ALL[i])
+}
+
+const WHITE_TO_MOVE: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// A fixed pseudo random number for a chess of one color on one point, from the SplitMix64
+/// generator so no table has to be kept for every board size.
+fn point_key(n: usize) -> u64 {
+    let mut z = (n as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,113 @@
+//! Searches running on a thread of their own, so the window keeps drawing while the engine
+//! thinks. Browsers have no threads, there a search goes one depth further on every frame.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use iced::time::{Duration, Instant};
+
+use gomoku_rs::engine::{Engine, Search, SearchResult};
+use gomoku_rs::game::Game;
+
+/// How far a search got.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    /// What the deepest finished iteration found.
+    pub result: SearchResult,
+    /// Whether there is nothing more to search, or the time was up.
+    pub finished: bool,
+    /// Time spent searching so far.
+    pub elapsed: Duration,
+    /// Positions the engine remembers the best move of, see [`Engine::best_moves_len`].
+    pub best_moves_len: usize,
+}
+
+/// A search of one position, stopped when dropped.
+pub struct Thinking {
+    progress: Arc<Mutex<Progress>>,
+    stop: Arc<AtomicBool>,
+    #[cfg(target_arch = "wasm32")]
+    search: (Arc<Mutex<Engine>>, Search, Instant),
+}
+
+impl Thinking {
+    /// Starts searching `game` with `engine` for at most `time`, as deep as the engine goes
+    /// without. The engine is busy until the search finished or was stopped.
+    pub fn start(engine: &Arc<Mutex<Engine>>, game: &Game, time: Option<Duration>) -> Self {
+        let empty = SearchResult { best_move: None, score: 0, depth: 0, nodes: 0, pv: vec![] };
+        let progress = Arc::new(Mutex::new(Progress { result: empty, finished: false, elapsed: Duration::ZERO, best_moves_len: 0 }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let start = Instant::now();
+        let deadline = time.map(|time| start + time);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let (engine, shared, stopped, game) = (engine.clone(), progress.clone(), stop.clone(), game.clone());
+            std::thread::spawn(move || {
+                // a search stopped before may still hold the engine for a moment
+                let mut engine = lock(&engine);
+                if stopped.load(Ordering::Relaxed) {
+                    return;
+                }
+                let mut search = Self::prepare(&mut engine, &game, deadline, stopped);
+                while Self::deepen(&mut engine, &mut search, &shared, start) {}
+            });
+            Self { progress, stop }
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let search = Self::prepare(&mut lock(engine), game, deadline, stop.clone());
+            Self { progress, stop, search: (engine.clone(), search, start) }
+        }
+    }
+
+    fn prepare(engine: &mut Engine, game: &Game, deadline: Option<Instant>, stop: Arc<AtomicBool>) -> Search {
+        engine.set_deadline(deadline);
+        engine.set_stop(stop);
+        engine.start(game)
+    }
+
+    /// How far the search got, moving it on by a depth in the browser.
+    pub fn step(&mut self) -> Progress {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let (engine, search, start) = &mut self.search;
+            if !lock(&self.progress).finished {
+                Self::deepen(&mut lock(engine), search, &self.progress, *start);
+            }
+        }
+        self.progress()
+    }
+
+    /// How far the search got.
+    pub fn progress(&self) -> Progress {
+        lock(&self.progress).clone()
+    }
+
+    /// Finishes the search at the next node, letting go of the engine.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// Searches one depth further and tells `progress`, returns whether there is more to search.
+    fn deepen(engine: &mut Engine, search: &mut Search, progress: &Mutex<Progress>, start: Instant) -> bool {
+        let more = engine.deepen(search);
+        *lock(progress) = Progress {
+            result: search.result().clone(),
+            finished: !more,
+            elapsed: start.elapsed(),
+            best_moves_len: engine.best_moves_len(),
+        };
+        more
+    }
+}
+
+impl Drop for Thinking {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Locks `mutex`, also after a search panicked while holding it.
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,95 @@
+//! Fours and open threes on the board with the points where they can be blocked, shown to
+//! beginners so they see which moves of the opponent have to be answered.
+
+use crate::board::{Board, CellState, ChessColor, Pos};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ThreatKind {
+    /// Three in a row with empty points around it, one more chess makes a four open at both ends.
+    OpenThree,
+    /// Four chesses within five points, one more chess makes five.
+    Four,
+}
+
+/// Chesses of one color which threaten to make five, and the empty points blocking them.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Threat {
+    pub kind: ThreatKind,
+    /// Indices of the chesses making the threat, in order along the line.
+    pub chesses: Vec<usize>,
+    /// Indices of the empty points where the opponent can stop it.
+    pub blocks: Vec<usize>,
+}
+
+/// The fours and then the open threes `color` has on `board`.
+///
+/// A four is blocked at its empty point, a straight four at both ends. An open three is blocked
+/// at the points inside every window of six it could grow to a straight four in.
+pub fn find_threats(board: &Board, color: ChessColor) -> Vec<Threat> {
+    let own = CellState::from(color);
+    let mut fours: Vec<Threat> = vec![];
+    let mut threes: Vec<Threat> = vec![];
+    for index in 0..board.cells().len() {
+        let start = board.index_to_pos(index);
+        for &(dx, dy) in Board::DIRECTIONS.iter() {
+            if let Some(window) = window(board, start, dx, dy, 5) {
+                let (chesses, empty) = split(board, &window, own);
+                if chesses.len() == 4 && empty.len() == 1 {
+                    merge(&mut fours, ThreatKind::Four, chesses, empty, true);
+                }
+            }
+            if let Some(window) = window(board, start, dx, dy, 6) {
+                let ends_empty = board.cell(window[0]) == Some(CellState::Empty) && board.cell(window[5]) == Some(CellState::Empty);
+                let (chesses, empty) = split(board, &window[1..5], own);
+                if ends_empty && chesses.len() == 3 && empty.len() == 1 {
+                    let blocks = vec![window[0], empty[0], window[5]];
+                    merge(&mut threes, ThreatKind::OpenThree, chesses, blocks, false);
+                }
+            }
+        }
+    }
+    // a three which is part of a four is not worth showing on its own
+    threes.retain(|three| !fours.iter().any(|four| three.chesses.iter().all(|c| four.chesses.contains(c))));
+    fours.extend(threes);
+    fours
+}
+
+/// Indices of `len` points from `start` in the direction (`dx`, `dy`), `None` if they leave the board.
+fn window(board: &Board, start: Pos, dx: i32, dy: i32, len: i32) -> Option<Vec<usize>> {
+    let size = board.cells_per_row() as i32;
+    (0..len).map(|step| {
+        let col = start.x as i32 + dx * step;
+        let row = start.y as i32 + dy * step;
+        (col >= 0 && row >= 0 && col < size && row < size).then(|| board.pos_to_index(Pos::new(col as usize, row as usize)))
+    }).collect()
+}
+
+/// The `own` chesses and the empty points in `window`, or nothing when an opponent chess is in it.
+fn split(board: &Board, window: &[usize], own: CellState) -> (Vec<usize>, Vec<usize>) {
+    let mut chesses = vec![];
+    let mut empty = vec![];
+    for &index in window {
+        match board.cell(index) {
+            Some(CellState::Empty) => empty.push(index),
+            Some(state) if state == own => chesses.push(index),
+            _ => return (vec![], vec![]),
+        }
+    }
+    (chesses, empty)
+}
+
+/// Adds a threat found in one window, joining the blocks of a threat of the same chesses found in
+/// another window, all of them with `union` or only the shared ones otherwise.
+fn merge(threats: &mut Vec<Threat>, kind: ThreatKind, chesses: Vec<usize>, blocks: Vec<usize>, union: bool) {
+    match threats.iter_mut().find(|threat| threat.chesses == chesses) {
+        Some(threat) if union => {
+            for block in blocks {
+                if !threat.blocks.contains(&block) {
+                    threat.blocks.push(block);
+                }
+            }
+        },
+        Some(threat) => threat.blocks.retain(|block| blocks.contains(block)),
+        None => threats.push(Threat { kind, chesses, blocks }),
+    }
+}
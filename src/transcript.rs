@@ -0,0 +1,46 @@
+//! Games written the way players post them on forums, numbered moves in coordinates followed by
+//! the result: "1. h8 h9 2. i8 i9 3. j8 1-0". A game started from a composed position is written
+//! without its setup, which only SGF keeps.
+
+use crate::board::{Board, ChessColor, Pos};
+use crate::game::{Game, GameState};
+
+/// Results as written after the moves.
+const BLACK_WINS: &str = "1-0";
+const WHITE_WINS: &str = "0-1";
+const DRAW: &str = "1/2-1/2";
+
+/// The moves of `game` after its setup, then the result when it is over.
+pub fn write(game: &Game) -> String {
+    let board = game.board();
+    let mut words = vec![];
+    for (turn, chess) in board.chesses()[game.setup_plies()..].iter().enumerate() {
+        // a turn is a black move and the white answer, a game going on from white starts with "1..."
+        let turn = if game.first_player() == ChessColor::Black { turn } else { turn + 1 };
+        if turn % 2 == 0 {
+            words.push(format!("{}.", turn / 2 + 1));
+        } else if words.is_empty() {
+            words.push(String::from("1..."));
+        }
+        words.push(board.pos_to_coord(chess.pos));
+    }
+    match game.state() {
+        GameState::Win(ChessColor::Black) => words.push(String::from(BLACK_WINS)),
+        GameState::Win(ChessColor::White) => words.push(String::from(WHITE_WINS)),
+        GameState::Draw => words.push(String::from(DRAW)),
+        // the result notation is about black and white only
+        GameState::Win(_) | GameState::Wait(_) => {},
+    }
+    words.join(" ")
+}
+
+/// The moves of a transcript, also of a plain list of coordinates separated by spaces, commas or
+/// semicolons. Move numbers like "1.", "1..." or "12)" and the result are skipped.
+pub fn read(text: &str, board: &Board) -> Result<Vec<Pos>, String> {
+    text.split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .filter(|word| ![BLACK_WINS, WHITE_WINS, DRAW, "*"].contains(word))
+        .map(|word| word.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == ')'))
+        .filter(|word| !word.is_empty())
+        .map(|word| board.coord_to_pos(word).ok_or_else(|| format!("cannot read move \"{}\"", word)))
+        .collect()
+}
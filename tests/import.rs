@@ -0,0 +1,147 @@
+//! Games written by other programs, read by the importers.
+
+use gomoku_rs::board::{Board, Chess, ChessColor, Pos};
+use gomoku_rs::game::{Ending, Game, GameState};
+use gomoku_rs::import;
+use gomoku_rs::rules::RuleSet;
+use gomoku_rs::sgf;
+
+/// `text` loaded onto an empty 15x15 freestyle game.
+fn load(text: &str) -> Result<Game, String> {
+    let mut game = Game::default();
+    import::load(text, &mut game)?;
+    Ok(game)
+}
+
+fn positions(chesses: &[Chess]) -> Vec<Pos> {
+    chesses.iter().map(|chess| chess.pos).collect()
+}
+
+#[test]
+fn numbered_transcript() {
+    let game = load("1. h8 h9 2. i8 i9 3. j8 j9 4. k8 k9 5. l8 1-0").unwrap();
+    assert_eq!(game.board().move_list(), "h8 h9 i8 i9 j8 j9 k8 k9 l8");
+    assert_eq!(game.state(), GameState::Win(ChessColor::Black));
+}
+
+#[test]
+fn transcript_with_move_numbers_in_parentheses_and_commas() {
+    let game = load("1) h8, h9\n2) i8, i9").unwrap();
+    assert_eq!(game.board().move_list(), "h8 h9 i8 i9");
+    assert_eq!(game.state(), GameState::Wait(ChessColor::Black));
+}
+
+#[test]
+fn transcript_with_unknown_move() {
+    assert!(load("1. h8 z99").is_err());
+    assert!(load("1-0").is_err());
+}
+
+#[test]
+fn psq_on_the_same_board() {
+    let record = import::read("Piskvorky 15x15, 11:11, 0\n8,8,1000\n9,9,2000\n-1\nplayer.exe", &Board::new(15)).unwrap();
+    assert_eq!(positions(&record.moves), vec![Pos::new(7, 7), Pos::new(8, 8)]);
+    assert_eq!(record.result, None);
+}
+
+#[test]
+fn psq_from_a_larger_board_is_centered() {
+    let record = import::read("Piskvorky 20x20, 11:11, 0\n10,10,0\n11,10,0\n", &Board::new(15)).unwrap();
+    assert_eq!(positions(&record.moves), vec![Pos::new(7, 7), Pos::new(8, 7)]);
+}
+
+#[test]
+fn psq_without_size() {
+    assert!(import::read("Piskvorky\n8,8,0", &Board::new(15)).is_err());
+}
+
+#[test]
+fn sgf_moves_and_size() {
+    let record = import::read("(;FF[4]GM[4]SZ[15];B[hh];W[ii];B[hi])", &Board::new(15)).unwrap();
+    assert_eq!(positions(&record.moves), vec![Pos::new(7, 7), Pos::new(8, 8), Pos::new(7, 8)]);
+}
+
+#[test]
+fn sgf_comment_with_brackets_and_semicolons() {
+    let text = "(;GM[4]SZ[15]C[first; then \\] and (parentheses)];B[hh]C[a;b];W[ii])";
+    let record = import::read(text, &Board::new(15)).unwrap();
+    assert_eq!(positions(&record.moves), vec![Pos::new(7, 7), Pos::new(8, 8)]);
+}
+
+#[test]
+fn sgf_follows_the_first_variation() {
+    let record = import::read("(;SZ[15];B[hh](;W[ii];B[jj])(;W[aa]))", &Board::new(15)).unwrap();
+    assert_eq!(positions(&record.moves), vec![Pos::new(7, 7), Pos::new(8, 8), Pos::new(9, 9)]);
+}
+
+#[test]
+fn sgf_resignation_is_kept() {
+    let game = load("(;GM[4]SZ[15]RE[B+Resign];B[hh];W[ii];B[hi])").unwrap();
+    assert_eq!(game.state(), GameState::Win(ChessColor::Black));
+    assert_eq!(game.ending(), Some(Ending::Resignation));
+}
+
+#[test]
+fn sgf_agreed_draw_is_kept() {
+    let game = load("(;GM[4]SZ[15]RE[0];B[hh];W[ii])").unwrap();
+    assert_eq!(game.state(), GameState::Draw);
+    assert_eq!(game.ending(), Some(Ending::Agreement));
+}
+
+#[test]
+fn sgf_with_broken_values() {
+    assert!(import::read("(;SZ[15];B[hh", &Board::new(15)).is_err());
+    assert!(import::read("(;SZ[x];B[hh])", &Board::new(15)).is_err());
+    assert!(import::read("(;SZ[15];B[7])", &Board::new(15)).is_err());
+}
+
+#[test]
+fn sgf_move_colors() {
+    let record = import::read("(;SZ[15];W[hh];B[ii])", &Board::new(15)).unwrap();
+    assert_eq!(record.first_player, ChessColor::White);
+    assert_eq!(record.moves.iter().map(|chess| chess.color).collect::<Vec<_>>(), vec![ChessColor::White, ChessColor::Black]);
+    let game = load("(;SZ[15];W[hh];B[ii])").unwrap();
+    assert_eq!(game.first_player(), ChessColor::White);
+    assert_eq!(game.state(), GameState::Wait(ChessColor::White));
+}
+
+#[test]
+fn sgf_move_out_of_turn() {
+    assert!(load("(;SZ[15];B[hh];B[ii])").is_err());
+    assert!(load("(;SZ[15]PL[B];W[hh])").is_err());
+}
+
+#[test]
+fn sgf_setup_and_player() {
+    let game = load("(;SZ[15]AB[hh][ii]AW[hi]PL[W];W[jj];B[kk])").unwrap();
+    assert_eq!(game.setup_plies(), 3);
+    assert_eq!(game.first_player(), ChessColor::White);
+    assert_eq!(game.board().move_list(), "h8 i7 h7 j6 k5");
+    assert_eq!(game.state(), GameState::Wait(ChessColor::White));
+}
+
+#[test]
+fn sgf_setup_without_moves() {
+    let game = load("(;SZ[15]AB[hh]AW[ii]PL[B])").unwrap();
+    assert_eq!(game.setup_plies(), 2);
+    assert_eq!(game.state(), GameState::Wait(ChessColor::Black));
+}
+
+#[test]
+fn composed_position_reads_back_from_sgf() {
+    let mut board = Board::new(15);
+    for (color, coord) in [(ChessColor::Black, "h8"), (ChessColor::Black, "i9"), (ChessColor::White, "g7"), (ChessColor::White, "j10")] {
+        let index = board.pos_to_index(board.coord_to_pos(coord).unwrap());
+        board.try_put_chess(color, index).unwrap();
+    }
+    board.set_next(ChessColor::White);
+    let mut game = Game::new(board, RuleSet::Freestyle);
+    game.play_moves("k11 h9 h10").unwrap();
+
+    let mut read = Game::new(Board::new(15), RuleSet::Freestyle);
+    import::load(&sgf::write(&game), &mut read).unwrap();
+    assert_eq!(read.setup_plies(), game.setup_plies());
+    assert_eq!(read.first_player(), ChessColor::White);
+    assert_eq!(read.board().chesses(), game.board().chesses());
+    assert_eq!(read.state(), game.state());
+}
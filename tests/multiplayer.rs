@@ -0,0 +1,152 @@
+//! Turns and wins of games between three or more players.
+
+use gomoku_rs::board::{Board, CellState, ChessColor};
+use gomoku_rs::game::{Game, GameState, MoveError};
+use gomoku_rs::rules::{RuleSet, RuleViolation};
+
+const SIZE: usize = 19;
+
+fn index(x: usize, y: usize) -> usize {
+    x + y * SIZE
+}
+
+/// The first `count` colors, in the order the players move.
+fn colors(count: usize) -> Vec<ChessColor> {
+    ChessColor::ALL[..count].to_vec()
+}
+
+fn new_game(players: usize, size: usize) -> Game {
+    Game::new(Board::with_players(size, colors(players)), RuleSet::Freestyle)
+}
+
+/// Plays `indices` in order, failing the test on a refused move.
+fn play(players: usize, indices: &[usize]) -> Game {
+    let mut game = new_game(players, SIZE);
+    for &index in indices {
+        game.put_chess(index).unwrap();
+    }
+    game
+}
+
+#[test]
+fn players_take_turns_in_order() {
+    for players in 3..=6 {
+        let colors = colors(players);
+        let mut game = new_game(players, SIZE);
+        for turn in 0..players * 2 {
+            let color = colors[turn % players];
+            assert_eq!(game.state(), GameState::Wait(color));
+            game.put_chess(index(turn, 0)).unwrap();
+            assert_eq!(game.board().cell(index(turn, 0)), Some(CellState::from(color)));
+        }
+        assert_eq!(game.state(), GameState::Wait(colors[0]));
+    }
+}
+
+#[test]
+fn two_players_by_default() {
+    assert_eq!(Board::new(SIZE).players(), [ChessColor::Black, ChessColor::White]);
+    assert_eq!(Board::with_players(SIZE, vec![]).players(), [ChessColor::Black, ChessColor::White]);
+}
+
+#[test]
+fn refused_moves_keep_the_turn() {
+    let mut game = play(3, &[index(0, 0)]);
+    assert_eq!(game.put_chess(index(0, 0)), Err(MoveError::Illegal(RuleViolation::Occupied)));
+    assert_eq!(game.put_chess(SIZE * SIZE), Err(MoveError::Illegal(RuleViolation::OutOfBoard)));
+    assert_eq!(game.state(), GameState::Wait(ChessColor::White));
+    assert_eq!(game.board().ply(), 1);
+}
+
+#[test]
+fn five_in_a_row_wins_for_any_player() {
+    for players in 3..=6 {
+        let colors = colors(players);
+        for (winner, &color) in colors.iter().enumerate() {
+            // every player fills a row of their own, those moving before the winner waste their
+            // first turn on a far point so the winner's row is the first to reach five
+            let mut game = new_game(players, SIZE);
+            'rounds: for column in 0..5 {
+                for player in 0..players {
+                    if game.state().is_over() {
+                        break 'rounds;
+                    }
+                    let index = if column == 0 && player < winner { index(SIZE - 1, player) } else { index(column, player * 2) };
+                    game.put_chess(index).unwrap();
+                }
+            }
+            assert_eq!(game.state(), GameState::Win(color));
+            let line = game.winning_line().unwrap();
+            assert_eq!(line.len(), 5);
+            assert!(line.iter().all(|&index| game.board().cell(index) == Some(CellState::from(color))));
+        }
+    }
+}
+
+#[test]
+fn diagonal_five_wins() {
+    let mut indices = vec![];
+    for step in 0..5 {
+        indices.extend([index(step + 2, step + 2), index(step, 10), index(step, 12)]);
+    }
+    let game = play(3, &indices[..13]);
+    assert_eq!(game.state(), GameState::Win(ChessColor::Black));
+    let mut line = game.winning_line().unwrap().to_vec();
+    line.sort();
+    assert_eq!(line, (0..5).map(|step| index(step + 2, step + 2)).collect::<Vec<_>>());
+}
+
+#[test]
+fn lines_of_other_players_do_not_add_up() {
+    // the first two players alternate along one row, the third leaves gaps in another, nobody has five
+    let indices: Vec<usize> = (0..9).flat_map(|column| [index(column * 2, 0), index(column * 2 + 1, 0), index(column * 2, 5)]).collect();
+    let game = play(3, &indices);
+    assert_eq!(game.state(), GameState::Wait(ChessColor::Black));
+    assert_eq!(game.winning_line(), None);
+}
+
+#[test]
+fn finished_game_refuses_moves() {
+    let mut game = play(3, &(0..5).flat_map(|column| [index(column, 0), index(column, 2), index(column, 4)]).take(13).collect::<Vec<_>>());
+    assert_eq!(game.state(), GameState::Win(ChessColor::Black));
+    assert_eq!(game.put_chess(index(10, 10)), Err(MoveError::GameOver));
+}
+
+#[test]
+fn undo_gives_the_turn_back() {
+    let mut game = play(3, &(0..5).flat_map(|column| [index(column, 0), index(column, 2), index(column, 4)]).take(13).collect::<Vec<_>>());
+    assert!(game.undo());
+    assert_eq!(game.state(), GameState::Wait(ChessColor::Black));
+    assert_eq!(game.winning_line(), None);
+    assert_eq!(game.board().cell(index(4, 0)), Some(CellState::Empty));
+    assert!(game.undo());
+    assert_eq!(game.state(), GameState::Wait(ChessColor::Red));
+    let mut empty = new_game(3, SIZE);
+    assert!(!empty.undo());
+}
+
+#[test]
+fn restart_keeps_the_players() {
+    let mut game = play(4, &[index(0, 0), index(1, 0)]);
+    game.restart(RuleSet::Freestyle);
+    assert_eq!(game.state(), GameState::Wait(ChessColor::Black));
+    assert_eq!(game.board().players(), colors(4));
+}
+
+#[test]
+fn resigning_needs_a_single_opponent() {
+    let mut game = play(3, &[index(0, 0)]);
+    assert_eq!(game.resign(), Err(MoveError::SeveralOpponents));
+    assert_eq!(game.state(), GameState::Wait(ChessColor::White));
+}
+
+#[test]
+fn full_board_without_five_is_a_draw() {
+    // no line of five fits on a board of four rows
+    let mut game = new_game(3, 4);
+    for index in 0..16 {
+        assert!(!game.state().is_over());
+        game.put_chess(index).unwrap();
+    }
+    assert_eq!(game.state(), GameState::Draw);
+}
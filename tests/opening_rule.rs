@@ -0,0 +1,198 @@
+//! Games walked through the tournament opening rules step by step.
+
+use gomoku_rs::board::ChessColor;
+use gomoku_rs::game::{Game, GameState, MoveError};
+use gomoku_rs::opening_rule::{OpeningError, OpeningProtocol, OpeningRule, Step};
+
+fn index(game: &Game, coord: &str) -> usize {
+    let board = game.board();
+    board.pos_to_index(board.coord_to_pos(coord).unwrap())
+}
+
+fn click(opening: &mut OpeningProtocol, game: &mut Game, coord: &str) -> Result<(), OpeningError> {
+    let index = index(game, coord);
+    opening.click(game, index)
+}
+
+/// A Soosõrv-8 game with the first four moves on the diagonal through the center, which the
+/// board mirrored along that diagonal leaves as it is, waiting for two declared offers.
+fn soosorv8_at_offers() -> (OpeningProtocol, Game) {
+    let mut game = Game::default();
+    let mut opening = OpeningProtocol::new(OpeningRule::Soosorv8);
+    for coord in ["h8", "i9", "j10"] {
+        click(&mut opening, &mut game, coord).unwrap();
+    }
+    opening.swap(false).unwrap();
+    click(&mut opening, &mut game, "l12").unwrap();
+    opening.declare(2).unwrap();
+    opening.swap(false).unwrap();
+    (opening, game)
+}
+
+#[test]
+fn soosorv8_first_moves_keep_near_the_center() {
+    let mut game = Game::default();
+    let mut opening = OpeningProtocol::new(OpeningRule::Soosorv8);
+    assert_eq!(opening.step(), Some(Step::Put { reach: Some(0) }));
+    assert_eq!(click(&mut opening, &mut game, "h9"), Err(OpeningError::TooFar(0)));
+    click(&mut opening, &mut game, "h8").unwrap();
+    assert_eq!(click(&mut opening, &mut game, "j10"), Err(OpeningError::TooFar(1)));
+    click(&mut opening, &mut game, "i9").unwrap();
+    assert_eq!(click(&mut opening, &mut game, "k11"), Err(OpeningError::TooFar(2)));
+    click(&mut opening, &mut game, "j10").unwrap();
+    assert_eq!(game.board().move_list(), "h8 i9 j10");
+    assert_eq!(opening.step(), Some(Step::Swap));
+}
+
+#[test]
+fn soosorv8_swaps_after_the_third_and_the_fourth_move() {
+    let mut game = Game::default();
+    let mut opening = OpeningProtocol::new(OpeningRule::Soosorv8);
+    for coord in ["h8", "i9", "j10"] {
+        click(&mut opening, &mut game, coord).unwrap();
+    }
+    assert_eq!(click(&mut opening, &mut game, "a1"), Err(OpeningError::WrongStep));
+    opening.swap(true).unwrap();
+    assert!(!opening.first_player_black());
+    // the fourth move may go anywhere
+    click(&mut opening, &mut game, "a1").unwrap();
+    assert_eq!(opening.step(), Some(Step::Declare { max: 8 }));
+    assert_eq!(opening.swap(true), Err(OpeningError::WrongStep));
+    opening.declare(3).unwrap();
+    opening.swap(true).unwrap();
+    assert!(opening.first_player_black());
+    assert_eq!(opening.step(), Some(Step::Offer { count: 8 }));
+}
+
+#[test]
+fn soosorv8_declares_from_one_to_eight_offers() {
+    let mut game = Game::default();
+    let mut opening = OpeningProtocol::new(OpeningRule::Soosorv8);
+    for coord in ["h8", "i9", "j10"] {
+        click(&mut opening, &mut game, coord).unwrap();
+    }
+    opening.swap(false).unwrap();
+    click(&mut opening, &mut game, "a1").unwrap();
+    assert_eq!(opening.declare(0), Err(OpeningError::TooManyOffers(8)));
+    assert_eq!(opening.declare(9), Err(OpeningError::TooManyOffers(8)));
+    opening.declare(8).unwrap();
+    opening.swap(false).unwrap();
+    assert_eq!(opening.offer_count(), 8);
+}
+
+#[test]
+fn soosorv8_offers_the_declared_number_of_different_moves() {
+    let (mut opening, mut game) = soosorv8_at_offers();
+    assert_eq!(opening.offer_count(), 2);
+    assert!(matches!(click(&mut opening, &mut game, "h8"), Err(OpeningError::Illegal(MoveError::Illegal(_)))));
+    click(&mut opening, &mut game, "g9").unwrap();
+    // the same move with the board mirrored along the diagonal
+    assert_eq!(click(&mut opening, &mut game, "i7"), Err(OpeningError::Symmetric));
+    // offering a move again takes it back
+    click(&mut opening, &mut game, "g9").unwrap();
+    assert!(opening.offers().is_empty());
+    click(&mut opening, &mut game, "i7").unwrap();
+    assert_eq!(opening.step(), Some(Step::Offer { count: 8 }));
+    click(&mut opening, &mut game, "a15").unwrap();
+    assert_eq!(opening.offers(), [index(&game, "i7"), index(&game, "a15")]);
+    assert_eq!(opening.step(), Some(Step::Pick));
+    // offers are not moves
+    assert_eq!(game.board().ply(), 4);
+}
+
+#[test]
+fn soosorv8_white_picks_one_of_the_offers() {
+    let (mut opening, mut game) = soosorv8_at_offers();
+    click(&mut opening, &mut game, "i7").unwrap();
+    click(&mut opening, &mut game, "a15").unwrap();
+    assert_eq!(click(&mut opening, &mut game, "a1"), Err(OpeningError::NotOffered));
+    click(&mut opening, &mut game, "a15").unwrap();
+    assert!(opening.is_done());
+    assert!(opening.offers().is_empty());
+    assert_eq!(game.board().move_list(), "h8 i9 j10 l12 a15");
+    assert_eq!(game.state(), GameState::Wait(ChessColor::White));
+    assert_eq!(click(&mut opening, &mut game, "a1"), Err(OpeningError::WrongStep));
+}
+
+#[test]
+fn soosorv8_progress_reads_back() {
+    let (mut opening, mut game) = soosorv8_at_offers();
+    click(&mut opening, &mut game, "i7").unwrap();
+    let read = OpeningProtocol::restore(&opening.saved(), game.board()).unwrap();
+    assert_eq!(read.saved(), opening.saved());
+    assert_eq!(read.rule(), OpeningRule::Soosorv8);
+    assert_eq!(read.step(), opening.step());
+    assert_eq!(read.offers(), opening.offers());
+    assert_eq!(read.offer_count(), 2);
+    assert!(OpeningProtocol::restore("Soosõrv-8; 10; black; -; ", game.board()).is_err());
+}
+
+/// A Taraguchi-10 game through its four moves, swapping after the first, the third and the fourth.
+fn taraguchi10_at_offers() -> (OpeningProtocol, Game) {
+    let mut game = Game::default();
+    let mut opening = OpeningProtocol::new(OpeningRule::Taraguchi10);
+    for (coord, swap) in [("h8", true), ("h9", false), ("j10", true), ("k5", true)] {
+        click(&mut opening, &mut game, coord).unwrap();
+        opening.swap(swap).unwrap();
+    }
+    (opening, game)
+}
+
+#[test]
+fn taraguchi10_moves_reach_one_line_further_each() {
+    let mut game = Game::default();
+    let mut opening = OpeningProtocol::new(OpeningRule::Taraguchi10);
+    for (reach, (too_far, coord)) in [("h9", "h8"), ("j10", "h9"), ("k11", "j10"), ("l8", "k5")].into_iter().enumerate() {
+        assert_eq!(opening.step(), Some(Step::Put { reach: Some(reach) }));
+        assert_eq!(click(&mut opening, &mut game, too_far), Err(OpeningError::TooFar(reach)));
+        click(&mut opening, &mut game, coord).unwrap();
+        assert_eq!(opening.step(), Some(Step::Swap));
+        opening.swap(false).unwrap();
+    }
+    assert_eq!(game.board().move_list(), "h8 h9 j10 k5");
+    assert_eq!(opening.step(), Some(Step::Offer { count: 10 }));
+}
+
+#[test]
+fn taraguchi10_swaps_after_each_of_the_first_four_moves() {
+    let mut game = Game::default();
+    let mut opening = OpeningProtocol::new(OpeningRule::Taraguchi10);
+    let mut first_player_black = true;
+    for (coord, swap) in [("h8", true), ("h9", false), ("j10", true), ("k5", true)] {
+        assert_eq!(opening.swap(true), Err(OpeningError::WrongStep));
+        click(&mut opening, &mut game, coord).unwrap();
+        assert_eq!(click(&mut opening, &mut game, "a1"), Err(OpeningError::WrongStep));
+        opening.swap(swap).unwrap();
+        first_player_black ^= swap;
+        assert_eq!(opening.first_player_black(), first_player_black);
+    }
+    assert!(!opening.first_player_black());
+    assert_eq!(opening.declare(5), Err(OpeningError::WrongStep));
+}
+
+#[test]
+fn taraguchi10_offers_ten_moves() {
+    let (mut opening, mut game) = taraguchi10_at_offers();
+    assert_eq!(opening.offer_count(), 10);
+    for row in 1..=9 {
+        click(&mut opening, &mut game, &format!("a{}", row)).unwrap();
+        assert_eq!(opening.step(), Some(Step::Offer { count: 10 }));
+    }
+    click(&mut opening, &mut game, "a10").unwrap();
+    assert_eq!(opening.offers().len(), 10);
+    assert_eq!(opening.step(), Some(Step::Pick));
+    assert_eq!(game.board().ply(), 4);
+}
+
+#[test]
+fn taraguchi10_white_picks_one_of_the_offers() {
+    let (mut opening, mut game) = taraguchi10_at_offers();
+    for row in 1..=10 {
+        click(&mut opening, &mut game, &format!("a{}", row)).unwrap();
+    }
+    assert_eq!(click(&mut opening, &mut game, "b1"), Err(OpeningError::NotOffered));
+    click(&mut opening, &mut game, "a7").unwrap();
+    assert!(opening.is_done());
+    assert_eq!(game.board().move_list(), "h8 h9 j10 k5 a7");
+    assert_eq!(game.state(), GameState::Wait(ChessColor::White));
+}
@@ -0,0 +1,205 @@
+//! Invariants of the board and the rules, checked on random games.
+
+use proptest::prelude::*;
+
+use gomoku_rs::board::{Board, CellState, ChessColor, Move, PlaceError, Pos};
+use gomoku_rs::game::Game;
+use gomoku_rs::import;
+use gomoku_rs::openings::{Opening, OPENINGS};
+use gomoku_rs::rules::{Freestyle, Outcome, RuleSet, Rules};
+use gomoku_rs::symmetry::canonical_key;
+use gomoku_rs::threats::{find_threats, ThreatKind};
+use gomoku_rs::transcript;
+
+const SIZE: usize = 15;
+
+fn rule_sets() -> impl Strategy<Value = RuleSet> {
+    prop::sample::select(RuleSet::ALL.to_vec())
+}
+
+/// Plays the legal ones of `indices` in order, until the game is over.
+fn play(rule_set: RuleSet, indices: &[usize]) -> Game {
+    let mut game = Game::new(Board::new(SIZE), rule_set);
+    for &index in indices {
+        if game.state().is_over() {
+            break;
+        }
+        let _ = game.put_chess(index);
+    }
+    game
+}
+
+/// Games crowded around the center, so lines and wins actually happen.
+fn games() -> impl Strategy<Value = Game> {
+    let index = (4..11usize, 4..11usize).prop_map(|(x, y)| x + y * SIZE);
+    (rule_sets(), prop::collection::vec(index, 0..60)).prop_map(|(rule_set, indices)| play(rule_set, &indices))
+}
+
+/// One of the eight rotations and reflections of the square board.
+fn transform(symmetry: u8, index: usize) -> usize {
+    let (x, y) = (index % SIZE, index / SIZE);
+    let last = SIZE - 1;
+    let (x, y) = match symmetry {
+        0 => (x, y),
+        1 => (last - y, x),
+        2 => (last - x, last - y),
+        3 => (y, last - x),
+        4 => (last - x, y),
+        5 => (x, last - y),
+        6 => (y, x),
+        _ => (last - y, last - x),
+    };
+    x + y * SIZE
+}
+
+/// Replays the moves of `game` transformed, removing what the rules capture after each.
+fn transform_board(symmetry: u8, game: &Game) -> Board {
+    let rules = game.rule_set().rules();
+    let mut transformed = Board::new(SIZE);
+    for mv in game.board().moves() {
+        let index = transform(symmetry, mv.index);
+        transformed.apply(Move::new(mv.player, index, mv.ply)).unwrap();
+        let captured = rules.captures(&transformed, index);
+        transformed.remove_chesses(&captured);
+    }
+    transformed
+}
+
+/// Who won, `Some(None)` for a draw. The line is left out since a move may make two fives at
+/// once and which of them is reported depends on the order directions are looked at.
+fn winner(outcome: Option<Outcome>) -> Option<Option<ChessColor>> {
+    outcome.map(|outcome| match outcome {
+        Outcome::Win { color, .. } | Outcome::Captures { color } => Some(color),
+        Outcome::Draw => None,
+    })
+}
+
+proptest! {
+    #[test]
+    fn cells_match_chesses(game in games()) {
+        let board = game.board();
+        let occupied = board.cells().iter().filter(|&&cell| cell != CellState::Empty).count();
+        prop_assert_eq!(occupied, board.stones().count());
+        for chess in board.stones() {
+            let expected = if chess.color == ChessColor::Black { CellState::Black } else { CellState::White };
+            prop_assert_eq!(board.cell(board.pos_to_index(chess.pos)), Some(expected));
+        }
+        // captures take pairs off the board
+        let (black, white) = (board.captured_by(ChessColor::Black), board.captured_by(ChessColor::White));
+        prop_assert_eq!(black + white, board.chesses().len() - occupied);
+        prop_assert!(black % 2 == 0 && white % 2 == 0);
+        for (ply, mv) in board.moves().enumerate() {
+            prop_assert_eq!(mv.ply, ply);
+            prop_assert_eq!(mv.player == ChessColor::Black, ply % 2 == 0);
+        }
+    }
+
+    #[test]
+    fn result_is_symmetric(game in games(), symmetry in 1..8u8) {
+        let board = game.board();
+        let Some(last) = board.last_move() else { return Ok(()); };
+        let rules = game.rule_set().rules();
+        let transformed = transform_board(symmetry, &game);
+        let result = rules.check_result(&transformed, transform(symmetry, last.index)).unwrap();
+        if let Some(Outcome::Win { line, .. }) = &result {
+            prop_assert!(line.contains(&transform(symmetry, last.index)));
+        }
+        prop_assert_eq!(winner(result), winner(rules.check_result(board, last.index).unwrap()));
+    }
+
+    #[test]
+    fn canonical_key_is_symmetric(game in games(), symmetry in 1..8u8) {
+        let transformed = transform_board(symmetry, &game);
+        prop_assert_eq!(canonical_key(&transformed).0, canonical_key(game.board()).0);
+    }
+
+    #[test]
+    fn openings_are_found_turned_and_mirrored(opening in prop::sample::select(OPENINGS.to_vec()), symmetry in 0..8u8) {
+        let mut game = Game::new(Board::new(SIZE), RuleSet::Freestyle);
+        game.play_moves(&opening.move_list(game.board())).unwrap();
+        let transformed = transform_board(symmetry, &game);
+        prop_assert_eq!(Opening::of(&transformed).map(|found| found.name), Some(opening.name));
+    }
+
+    #[test]
+    fn legality_is_symmetric(game in games(), symmetry in 1..8u8, x in 0..SIZE, y in 0..SIZE) {
+        let board = game.board();
+        let index = board.pos_to_index(Pos::new(x, y));
+        let rules = game.rule_set().rules();
+        let transformed = transform_board(symmetry, &game);
+        prop_assert_eq!(rules.is_legal(&transformed, transform(symmetry, index)), rules.is_legal(board, index));
+    }
+
+    #[test]
+    fn unapply_reverses_apply(game in games(), x in 0..SIZE, y in 0..SIZE) {
+        let mut board = game.board().clone();
+        let index = board.pos_to_index(Pos::new(x, y));
+        prop_assume!(board.is_empty_at(index));
+        let before = board.clone();
+        let player = if board.next_is_black() { ChessColor::Black } else { ChessColor::White };
+        let mv = Move::new(player, index, board.ply());
+        prop_assert_eq!(board.apply(mv), Ok(()));
+        prop_assert_eq!(board.last_move(), Some(mv));
+        prop_assert_eq!(board.unapply(mv), Ok(()));
+        prop_assert_eq!(board.cells(), before.cells());
+        prop_assert_eq!(board.move_list(), before.move_list());
+    }
+
+    #[test]
+    fn misplaced_moves_are_refused(game in games(), x in 0..SIZE, y in 0..SIZE) {
+        let mut board = game.board().clone();
+        let before = board.clone();
+        let player = if board.next_is_black() { ChessColor::Black } else { ChessColor::White };
+        let index = board.pos_to_index(Pos::new(x, y));
+        prop_assert_eq!(board.cell(SIZE * SIZE), None);
+        prop_assert_eq!(board.apply(Move::new(player, SIZE * SIZE, board.ply())), Err(PlaceError::OutOfRange));
+        prop_assert_eq!(board.apply(Move::new(player, index, board.ply() + 1)), Err(PlaceError::OutOfOrder));
+        let first_stone = board.stones().next().copied();
+        if let Some(chess) = first_stone {
+            let occupied = board.pos_to_index(chess.pos);
+            prop_assert_eq!(board.apply(Move::new(player, occupied, board.ply())), Err(PlaceError::Occupied));
+        }
+        let first_move = board.moves().next().filter(|_| board.ply() > 1);
+        if let Some(first) = first_move {
+            prop_assert_eq!(board.unapply(first), Err(PlaceError::OutOfOrder));
+        }
+        prop_assert_eq!(board.cells(), before.cells());
+        prop_assert_eq!(board.move_list(), before.move_list());
+    }
+
+    #[test]
+    fn undo_reverses_put_chess(game in games(), x in 0..SIZE, y in 0..SIZE) {
+        let mut after = game.clone();
+        let index = game.board().pos_to_index(Pos::new(x, y));
+        prop_assume!(after.put_chess(index).is_ok());
+        prop_assert!(after.undo());
+        prop_assert_eq!(after.state(), game.state());
+        prop_assert_eq!(after.board().cells(), game.board().cells());
+        prop_assert_eq!(after.board().move_list(), game.board().move_list());
+    }
+
+    #[test]
+    fn blocks_of_fours_make_five(game in games()) {
+        let board = game.board();
+        for color in [ChessColor::Black, ChessColor::White] {
+            for threat in find_threats(board, color).iter().filter(|threat| threat.kind == ThreatKind::Four) {
+                prop_assert!(!threat.blocks.is_empty());
+                for &block in threat.blocks.iter() {
+                    let mut next = board.clone();
+                    next.apply(Move::new(color, block, next.ply())).unwrap();
+                    let five = matches!(Freestyle.check_result(&next, block), Ok(Some(Outcome::Win { .. })));
+                    prop_assert!(five, "block {} makes no five", next.pos_to_coord(next.index_to_pos(block)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn transcript_reads_back(game in games()) {
+        prop_assume!(game.board().has_chess());
+        let mut read = Game::new(Board::new(SIZE), game.rule_set());
+        prop_assert_eq!(import::load(&transcript::write(&game), &mut read), Ok(()));
+        prop_assert_eq!(read.board().move_list(), game.board().move_list());
+        prop_assert_eq!(read.state(), game.state());
+    }
+}
@@ -0,0 +1,96 @@
+//! Rules and bots written in Rhai scripts.
+#![cfg(feature = "scripting")]
+
+use std::sync::Arc;
+
+use gomoku_rs::board::ChessColor;
+use gomoku_rs::game::{Game, GameState, MoveError};
+use gomoku_rs::rules::{RuleSet, RuleViolation};
+use gomoku_rs::scripting::{Script, ScriptError, ScriptRules};
+
+fn scripted(source: &str) -> Game {
+    let mut game = Game::default();
+    game.set_rules(Arc::new(ScriptRules::new(Script::compile(source).unwrap(), RuleSet::Freestyle)));
+    game
+}
+
+#[test]
+fn script_decides_which_moves_are_legal() {
+    // the first move must be in the center
+    let mut game = scripted("fn is_legal(board, x, y) { board.ply > 0 || (x == 7 && y == 7) }");
+    let corner = game.board().pos_to_index(game.board().coord_to_pos("a1").unwrap());
+    assert_eq!(game.put_chess(corner), Err(MoveError::Illegal(RuleViolation::Scripted)));
+    game.play_moves("h8 a1").unwrap();
+    // occupied points stay illegal whatever the script says
+    assert!(game.play_moves("h8").is_err());
+}
+
+#[test]
+fn failing_hooks_refuse_the_move() {
+    let mut game = scripted(r#"fn is_legal(board, x, y) { throw "no rules yet"; }"#);
+    let center = game.board().pos_to_index(game.board().coord_to_pos("h8").unwrap());
+    assert!(matches!(game.put_chess(center), Err(MoveError::Illegal(RuleViolation::ScriptFailed(_)))));
+    let mut game = scripted("fn winner(board, x, y) { 1 }");
+    assert!(matches!(game.put_chess(center), Err(MoveError::Illegal(RuleViolation::ScriptFailed(_)))));
+    // the move the rules could not judge is taken off the board again
+    assert_eq!(game.board().ply(), 0);
+    assert_eq!(game.state(), GameState::Wait(ChessColor::Black));
+}
+
+#[test]
+fn script_decides_who_wins() {
+    let mut game = scripted(r#"
+        fn winner(board, x, y) {
+            let color = board.get(x, y);
+            let count = 1;
+            for dx in [-1, 1] {
+                let step = 1;
+                while board.get(x + dx * step, y) == color {
+                    count += 1;
+                    step += 1;
+                }
+            }
+            if count >= 4 { color } else { "" }
+        }
+    "#);
+    game.play_moves("a1 a2 b1 b2 c1 c2").unwrap();
+    assert_eq!(game.state(), GameState::Wait(ChessColor::Black));
+    game.play_moves("d1").unwrap();
+    assert_eq!(game.state(), GameState::Win(ChessColor::Black));
+}
+
+#[test]
+fn rules_without_hooks_are_the_base_rules() {
+    let mut game = scripted("fn choose_move(board) { [0, 0] }");
+    game.play_moves("a1 a2 b1 b2 c1 c2 d1 d2").unwrap();
+    assert_eq!(game.state(), GameState::Wait(ChessColor::Black));
+    game.play_moves("e1").unwrap();
+    assert_eq!(game.state(), GameState::Win(ChessColor::Black));
+}
+
+#[test]
+fn bot_chooses_a_move() {
+    let script = Script::compile(r#"
+        fn choose_move(board) {
+            for y in 0..board.size {
+                for x in 0..board.size {
+                    if board.get(x, y) == "" { return [x, y]; }
+                }
+            }
+        }
+    "#).unwrap();
+    let mut game = Game::default();
+    game.play_moves("a15").unwrap();
+    let index = script.choose_move(&game).unwrap();
+    assert_eq!(game.board().pos_to_coord(game.board().index_to_pos(index)), "b15");
+}
+
+#[test]
+fn broken_scripts_are_errors() {
+    assert!(matches!(Script::compile("fn choose_move(board) {"), Err(ScriptError::Compile(_))));
+    let game = Game::default();
+    let off_board = Script::compile("fn choose_move(board) { [board.size, 0] }").unwrap();
+    assert!(matches!(off_board.choose_move(&game), Err(ScriptError::BadResult(_))));
+    let looping = Script::compile("fn choose_move(board) { loop {} }").unwrap();
+    assert!(matches!(looping.choose_move(&game), Err(ScriptError::Run(_))));
+}